@@ -0,0 +1,89 @@
+use etcd_client::{DeleteResponse, PutResponse, TxnOpResponse, TxnResponse};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::any::Any;
+
+/// What the callback of `ConnectOptions.with_audit()` is called with after a request
+/// changed the keyspace.
+#[pyclass(get_all, name = "AuditEvent")]
+#[derive(Clone, Debug)]
+pub struct PyAuditEvent {
+    operation: &'static str,
+    key: Option<Py<PyBytes>>,
+    // The modification revision of the key before the request, if it replaced or deleted
+    // a single key which existed.
+    old_revision: Option<i64>,
+    // The revision of the cluster after the request.
+    new_revision: i64,
+    // Set with `Communicator.with_audit_context()`.
+    context: PyObject,
+}
+
+#[pymethods]
+impl PyAuditEvent {
+    pub fn __repr__(&self, py: Python) -> String {
+        format!(
+            "AuditEvent(operation={:?}, key={:?}, old_revision={:?}, new_revision={}, context={})",
+            self.operation,
+            self.key.as_ref().map(|key| key.as_ref(py)),
+            self.old_revision,
+            self.new_revision,
+            self.context.as_ref(py)
+        )
+    }
+}
+
+/// Returns the modification revision of the key before the request, if known, and the revision
+/// of the cluster after it, if `response` is that of a request which changed the keyspace.
+/// Deletes which deleted nothing and transactions which ran no put or delete are left out.
+fn revisions(response: &dyn Any) -> Option<(Option<i64>, i64)> {
+    if let Some(response) = response.downcast_ref::<PutResponse>() {
+        let old_revision = response.prev_key().map(|kv| kv.mod_revision());
+        Some((old_revision, response.header()?.revision()))
+    } else if let Some(response) = response.downcast_ref::<DeleteResponse>() {
+        if response.deleted() == 0 {
+            return None;
+        }
+        let old_revision = match response.prev_kvs() {
+            [kv] => Some(kv.mod_revision()),
+            _ => None,
+        };
+        Some((old_revision, response.header()?.revision()))
+    } else {
+        let response = response.downcast_ref::<TxnResponse>()?;
+        let mutated = response.op_responses().iter().any(|op| {
+            matches!(
+                op,
+                TxnOpResponse::Put(_) | TxnOpResponse::Delete(_) | TxnOpResponse::Txn(_)
+            )
+        });
+        mutated.then_some((None, response.header()?.revision()))
+    }
+}
+
+/// Calls `callback` with an `AuditEvent` for `operation` on `key` if `response` tells that it
+/// changed the keyspace. Like hooks, the audit never fails a request, so its errors are only
+/// logged.
+pub fn audit(
+    callback: &PyObject,
+    context: Option<&PyObject>,
+    operation: &'static str,
+    key: Option<&[u8]>,
+    response: &dyn Any,
+) {
+    let Some((old_revision, new_revision)) = revisions(response) else {
+        return;
+    };
+    Python::with_gil(|py| {
+        let event = PyAuditEvent {
+            operation,
+            key: key.map(|key| PyBytes::new(py, key).into()),
+            old_revision,
+            new_revision,
+            context: context.map_or_else(|| py.None(), |context| context.clone_ref(py)),
+        };
+        if let Err(e) = callback.call1(py, (event,)) {
+            log::warn!("the audit callback failed for {operation}: {e}");
+        }
+    });
+}
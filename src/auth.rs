@@ -0,0 +1,96 @@
+use etcd_client::{Permission, PermissionType};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp as PyO3CompareOp;
+
+#[derive(Clone)]
+#[pyclass(name = "PermissionType")]
+pub struct PyPermissionType(pub PermissionType);
+
+#[pymethods]
+impl PyPermissionType {
+    #[classattr]
+    const READ: Self = Self(PermissionType::Read);
+    #[classattr]
+    const WRITE: Self = Self(PermissionType::Write);
+    #[classattr]
+    const READWRITE: Self = Self(PermissionType::Readwrite);
+
+    pub fn __repr__(&self) -> String {
+        match self.0 {
+            PermissionType::Read => "PermissionType.READ".to_owned(),
+            PermissionType::Write => "PermissionType.WRITE".to_owned(),
+            PermissionType::Readwrite => "PermissionType.READWRITE".to_owned(),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, rhs: &PyPermissionType, op: PyO3CompareOp) -> PyObject {
+        match op {
+            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
+            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "Permission")]
+pub struct PyPermission(pub Permission);
+
+#[pymethods]
+impl PyPermission {
+    pub fn perm_type(&self) -> PyPermissionType {
+        // etcd only defines the three permission types above.
+        PyPermissionType(PermissionType::try_from(self.0.get_type()).unwrap_or_default())
+    }
+
+    pub fn key(&self) -> Vec<u8> {
+        self.0.key().to_owned()
+    }
+
+    pub fn range_end(&self) -> Vec<u8> {
+        self.0.range_end().to_owned()
+    }
+
+    pub fn is_prefix(&self) -> bool {
+        self.0.is_prefix()
+    }
+
+    pub fn is_from_key(&self) -> bool {
+        self.0.is_from_key()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "Permission(perm_type={}, key={:?}, range_end={:?})",
+            self.perm_type().__repr__(),
+            self.0.key(),
+            self.0.range_end()
+        )
+    }
+}
+
+/// Tells whether `perms` authorize an operation of `perm_type` on `key`, the same way etcd does.
+/// A READWRITE operation needs both read and write access, which may come from different permissions.
+pub fn permits(perms: &[Permission], key: &[u8], perm_type: PermissionType) -> bool {
+    let covers = |perm: &Permission| {
+        let range_end = perm.range_end();
+        if range_end.is_empty() {
+            key == perm.key()
+        } else if range_end == [b'\0'] {
+            key >= perm.key()
+        } else {
+            key >= perm.key() && key < range_end
+        }
+    };
+    let grants = |wanted: PermissionType| {
+        perms.iter().filter(|perm| covers(perm)).any(|perm| {
+            let granted = PermissionType::try_from(perm.get_type()).unwrap_or_default();
+            granted == wanted || granted == PermissionType::Readwrite
+        })
+    };
+
+    match perm_type {
+        PermissionType::Readwrite => grants(PermissionType::Read) && grants(PermissionType::Write),
+        perm_type => grants(perm_type),
+    }
+}
@@ -0,0 +1,172 @@
+use etcd_client::{
+    AuthenticateResponse, RoleAddResponse, RoleDeleteResponse, RoleGetResponse,
+    RoleGrantPermissionResponse, RoleListResponse, RoleRevokePermissionResponse,
+    UserChangePasswordResponse, UserGrantRoleResponse, UserRevokeRoleResponse,
+};
+use pyo3::prelude::*;
+
+use crate::auth::PyPermission;
+use crate::response_header::PyResponseHeader;
+
+#[derive(Clone)]
+#[pyclass(name = "AuthenticateResponse")]
+pub struct PyAuthenticateResponse(pub AuthenticateResponse);
+
+#[pymethods]
+impl PyAuthenticateResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn token(&self) -> String {
+        self.0.token().to_owned()
+    }
+
+    pub fn __repr__(&self) -> String {
+        // The token grants access as the user, so keep it out of logs.
+        format!("AuthenticateResponse(header={:?})", self.0.header())
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "UserChangePasswordResponse")]
+pub struct PyUserChangePasswordResponse(pub UserChangePasswordResponse);
+
+#[pymethods]
+impl PyUserChangePasswordResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "UserGrantRoleResponse")]
+pub struct PyUserGrantRoleResponse(pub UserGrantRoleResponse);
+
+#[pymethods]
+impl PyUserGrantRoleResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "UserRevokeRoleResponse")]
+pub struct PyUserRevokeRoleResponse(pub UserRevokeRoleResponse);
+
+#[pymethods]
+impl PyUserRevokeRoleResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleAddResponse")]
+pub struct PyRoleAddResponse(pub RoleAddResponse);
+
+#[pymethods]
+impl PyRoleAddResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleGetResponse")]
+pub struct PyRoleGetResponse(pub RoleGetResponse);
+
+#[pymethods]
+impl PyRoleGetResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn permissions(&self) -> Vec<PyPermission> {
+        self.0.permissions().into_iter().map(PyPermission).collect()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleListResponse")]
+pub struct PyRoleListResponse(pub RoleListResponse);
+
+#[pymethods]
+impl PyRoleListResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn roles(&self) -> Vec<String> {
+        self.0.roles().to_vec()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleDeleteResponse")]
+pub struct PyRoleDeleteResponse(pub RoleDeleteResponse);
+
+#[pymethods]
+impl PyRoleDeleteResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleGrantPermissionResponse")]
+pub struct PyRoleGrantPermissionResponse(pub RoleGrantPermissionResponse);
+
+#[pymethods]
+impl PyRoleGrantPermissionResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "RoleRevokePermissionResponse")]
+pub struct PyRoleRevokePermissionResponse(pub RoleRevokePermissionResponse);
+
+#[pymethods]
+impl PyRoleRevokePermissionResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
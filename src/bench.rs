@@ -0,0 +1,353 @@
+use etcd_client::{DeleteOptions, GetOptions, WatchOptions};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::client::{PyClient, PyConnectOptions, PyEndpoints};
+use crate::communicator::{connected, PyCommunicator};
+use crate::error::{InvalidArgsError, PyClientError};
+use crate::metrics::percentile;
+use crate::runtime::future_into_py;
+
+// How long a watch workload waits for the events of the puts after the last of them.
+const WATCH_GRACE: Duration = Duration::from_secs(10);
+
+// The width of the time a put was sent at, in nanoseconds, written at the start of its value
+// by a watch workload.
+const TIMESTAMP_WIDTH: usize = 20;
+
+/// What `bench()` runs: `requests` puts, gets or watched puts of `value_size` bytes on `keys`
+/// keys under `prefix`, `concurrency` at a time.
+#[pyclass(name = "Workload")]
+#[derive(Clone, Debug)]
+pub struct PyWorkload {
+    #[pyo3(get)]
+    operation: String,
+    #[pyo3(get)]
+    requests: u64,
+    #[pyo3(get)]
+    concurrency: usize,
+    #[pyo3(get)]
+    keys: u64,
+    #[pyo3(get)]
+    value_size: usize,
+    prefix: Vec<u8>,
+}
+
+#[pymethods]
+impl PyWorkload {
+    #[new]
+    #[pyo3(signature = (
+        operation,
+        *,
+        requests=10000,
+        concurrency=64,
+        keys=1000,
+        value_size=256,
+        prefix=None,
+    ))]
+    fn new(
+        operation: String,
+        requests: u64,
+        concurrency: usize,
+        keys: u64,
+        value_size: usize,
+        prefix: Option<&PyBytes>,
+    ) -> PyResult<Self> {
+        if !["put", "get", "watch"].contains(&operation.as_str()) {
+            return Err(InvalidArgsError::new_err(format!(
+                "operation must be \"put\", \"get\" or \"watch\", not {operation:?}"
+            )));
+        }
+        if requests == 0 || concurrency == 0 || keys == 0 {
+            return Err(InvalidArgsError::new_err(
+                "requests, concurrency and keys must be at least 1",
+            ));
+        }
+        let prefix = prefix.map_or(b"bench/".to_vec(), |prefix| prefix.as_bytes().to_vec());
+        // The keys of the benchmark are deleted once it is done.
+        if prefix.is_empty() {
+            return Err(InvalidArgsError::new_err("prefix must be non-empty"));
+        }
+        Ok(Self {
+            operation,
+            requests,
+            concurrency,
+            keys,
+            value_size,
+            prefix,
+        })
+    }
+
+    #[getter]
+    fn prefix<'a>(&self, py: Python<'a>) -> &'a PyBytes {
+        PyBytes::new(py, &self.prefix)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "Workload(operation={:?}, requests={}, concurrency={}, keys={}, value_size={}, prefix={:?})",
+            self.operation,
+            self.requests,
+            self.concurrency,
+            self.keys,
+            self.value_size,
+            String::from_utf8_lossy(&self.prefix)
+        )
+    }
+}
+
+impl PyWorkload {
+    fn key(&self, index: u64) -> Vec<u8> {
+        let suffix = format!("{:08}", index % self.keys);
+        [self.prefix.as_slice(), suffix.as_bytes()].concat()
+    }
+}
+
+/// The throughput and the latencies of the requests of a `bench()` run, in seconds.
+#[pyclass(get_all, name = "BenchResult")]
+#[derive(Clone, Debug)]
+pub struct PyBenchResult {
+    operation: String,
+    // The number of requests which succeeded, or of events delivered for watches.
+    requests: u64,
+    // The number of requests which failed, or of events not delivered for watches.
+    errors: u64,
+    duration: f64,
+    // Requests, or events, per second.
+    throughput: f64,
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    max: f64,
+}
+
+#[pymethods]
+impl PyBenchResult {
+    pub fn __repr__(&self) -> String {
+        format!(
+            "BenchResult(operation={:?}, requests={}, errors={}, duration={}, throughput={}, \
+             p50={}, p95={}, p99={}, max={})",
+            self.operation,
+            self.requests,
+            self.errors,
+            self.duration,
+            self.throughput,
+            self.p50,
+            self.p95,
+            self.p99,
+            self.max
+        )
+    }
+}
+
+/// Runs `operation` for each index below `requests`, `concurrency` at a time, returning the
+/// latencies of those which succeeded and the number of those which failed.
+async fn drive<F, Fut>(requests: u64, concurrency: usize, operation: F) -> (Vec<f64>, u64)
+where
+    F: Fn(u64) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = PyResult<()>> + Send,
+{
+    let operation = Arc::new(operation);
+    let next = Arc::new(AtomicU64::new(0));
+    let workers = (0..concurrency)
+        .map(|_| {
+            let (operation, next) = (operation.clone(), next.clone());
+            tokio::spawn(async move {
+                let (mut latencies, mut errors) = (Vec::new(), 0);
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= requests {
+                        break;
+                    }
+                    let started = Instant::now();
+                    match operation(index).await {
+                        Ok(()) => latencies.push(started.elapsed().as_secs_f64()),
+                        Err(e) => {
+                            errors += 1;
+                            log::debug!("a request of the benchmark failed: {e}");
+                        }
+                    }
+                }
+                (latencies, errors)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let (mut latencies, mut errors) = (Vec::new(), 0);
+    for worker in workers {
+        if let Ok((worker_latencies, worker_errors)) = worker.await {
+            latencies.extend(worker_latencies);
+            errors += worker_errors;
+        }
+    }
+    (latencies, errors)
+}
+
+fn put(
+    communicator: &PyCommunicator,
+    key: Vec<u8>,
+    value: Vec<u8>,
+) -> impl Future<Output = PyResult<()>> {
+    let request = communicator.request("put", Some(key.clone()), move |mut client| {
+        let (key, value) = (key.clone(), value.clone());
+        async move { client.put(key, value, None).await }
+    });
+    async move { request.await.map(|_| ()) }
+}
+
+fn get(communicator: &PyCommunicator, key: Vec<u8>) -> impl Future<Output = PyResult<()>> {
+    let serializable = communicator.connect_options.serializable_reads;
+    let request = communicator.idempotent_request("get", Some(key.clone()), move |mut client| {
+        let key = key.clone();
+        async move {
+            let options = serializable.then(|| GetOptions::new().with_serializable());
+            client.get(key, options).await
+        }
+    });
+    async move { request.await.map(|_| ()) }
+}
+
+/// Puts the keys of `workload` while watching them, returning the latencies from the puts being
+/// sent to their events being delivered, the number of puts which failed or whose events were
+/// not delivered, and the duration.
+async fn watch(
+    communicator: Arc<PyCommunicator>,
+    workload: Arc<PyWorkload>,
+) -> PyResult<(Vec<f64>, u64, f64)> {
+    communicator.check_process()?;
+    let mut client = connected(&communicator.client).await?;
+    let options = WatchOptions::new().with_prefix();
+    let (mut watcher, mut stream) = client
+        .watch(workload.prefix.clone(), Some(options))
+        .await
+        .map_err(PyClientError)?;
+
+    let started = Instant::now();
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let receiver = tokio::spawn({
+        let delivered = delivered.clone();
+        async move {
+            while let Ok(Some(response)) = stream.message().await {
+                let received = started.elapsed().as_secs_f64();
+                for event in response.events() {
+                    let sent = event.kv().and_then(|kv| {
+                        let timestamp = kv.value().get(..TIMESTAMP_WIDTH)?;
+                        std::str::from_utf8(timestamp).ok()?.parse::<u64>().ok()
+                    });
+                    if let Some(sent) = sent {
+                        let latency = received - sent as f64 / 1e9;
+                        delivered.lock().unwrap().push(latency);
+                    }
+                }
+            }
+        }
+    });
+
+    let (requests, concurrency) = (workload.requests, workload.concurrency);
+    let (_, errors) = drive(requests, concurrency, move |index| {
+        let sent = started.elapsed().as_nanos();
+        let mut value = format!("{sent:0TIMESTAMP_WIDTH$}").into_bytes();
+        value.resize(workload.value_size.max(TIMESTAMP_WIDTH), b'x');
+        put(&communicator, workload.key(index), value)
+    })
+    .await;
+
+    let expected = (requests - errors) as usize;
+    let deadline = Instant::now() + WATCH_GRACE;
+    while delivered.lock().unwrap().len() < expected && Instant::now() < deadline {
+        sleep(Duration::from_millis(10)).await;
+    }
+    let duration = started.elapsed().as_secs_f64();
+    let _ = watcher.cancel().await;
+    receiver.abort();
+
+    let latencies = std::mem::take(&mut *delivered.lock().unwrap());
+    let missed = expected.saturating_sub(latencies.len()) as u64;
+    Ok((latencies, errors + missed, duration))
+}
+
+/// Connects to etcd with the options of `client` and runs `workload`, then deletes its keys.
+async fn run(client: PyClient, workload: PyWorkload) -> PyResult<PyBenchResult> {
+    let (etcd, endpoints) = client.connect_etcd_client().await?;
+    let communicator = Arc::new(client.communicator(etcd, endpoints).await?);
+    let workload = Arc::new(workload);
+    let value = vec![b'x'; workload.value_size];
+    let (requests, concurrency) = (workload.requests, workload.concurrency);
+
+    let (mut latencies, errors, duration) = match workload.operation.as_str() {
+        "put" => {
+            let started = Instant::now();
+            let (communicator, workload) = (communicator.clone(), workload.clone());
+            let (latencies, errors) = drive(requests, concurrency, move |index| {
+                put(&communicator, workload.key(index), value.clone())
+            })
+            .await;
+            (latencies, errors, started.elapsed().as_secs_f64())
+        }
+        "get" => {
+            // The keys read are put first, which is not measured.
+            let (seeding, seeded) = (communicator.clone(), workload.clone());
+            drive(workload.keys, concurrency, move |index| {
+                put(&seeding, seeded.key(index), value.clone())
+            })
+            .await;
+            let started = Instant::now();
+            let (communicator, workload) = (communicator.clone(), workload.clone());
+            let (latencies, errors) = drive(requests, concurrency, move |index| {
+                get(&communicator, workload.key(index))
+            })
+            .await;
+            (latencies, errors, started.elapsed().as_secs_f64())
+        }
+        _ => watch(communicator.clone(), workload.clone()).await?,
+    };
+
+    let prefix = workload.prefix.clone();
+    let delete = communicator.request("delete_prefix", Some(prefix.clone()), move |mut client| {
+        let prefix = prefix.clone();
+        async move {
+            let options = DeleteOptions::new().with_prefix();
+            client.delete(prefix, Some(options)).await
+        }
+    });
+    if let Err(e) = delete.await {
+        log::warn!("failed to delete the keys of the benchmark: {e}");
+    }
+
+    latencies.sort_by(f64::total_cmp);
+    let completed = latencies.len() as u64;
+    Ok(PyBenchResult {
+        operation: workload.operation.clone(),
+        requests: completed,
+        errors,
+        duration,
+        throughput: completed as f64 / duration.max(f64::EPSILON),
+        p50: percentile(&latencies, 0.50),
+        p95: percentile(&latencies, 0.95),
+        p99: percentile(&latencies, 0.99),
+        max: latencies.last().copied().unwrap_or(0.0),
+    })
+}
+
+/// Runs `workload` against `endpoints` from the Rust side, connecting with `connect_options`,
+/// and returns its throughput and latency percentiles.
+#[pyfunction]
+#[pyo3(signature = (endpoints, workload, connect_options=None))]
+pub fn bench(
+    py: Python<'_>,
+    endpoints: PyEndpoints,
+    workload: PyWorkload,
+    connect_options: Option<PyConnectOptions>,
+) -> PyResult<&PyAny> {
+    let endpoints = match endpoints {
+        PyEndpoints::One(endpoint) => vec![endpoint],
+        PyEndpoints::Many(endpoints) => endpoints,
+    };
+    let client = PyClient::from_endpoints(endpoints, connect_options, None)?;
+    future_into_py(py, run(client, workload))
+}
@@ -0,0 +1,321 @@
+use etcd_client::{EventType, WatchOptions};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::time::sleep;
+
+use crate::communicator::{connected, wait_closed, PyCommunicator};
+use crate::error::{
+    compacted_error, io_error, CompactedError, ConnectionLostError, InvalidArgsError, IoError,
+    PyClientError, WatchError,
+};
+use crate::export::set_field;
+use crate::runtime::future_into_py;
+use crate::watch_event::PyWatchEventType;
+
+/// What a changefeed delivers for each change under its prefix.
+#[pyclass(get_all, name = "ChangefeedEvent")]
+#[derive(Clone, Debug)]
+pub struct PyChangefeedEvent {
+    key: Py<PyBytes>,
+    event: PyWatchEventType,
+    // `None` for deletes.
+    value: Option<Py<PyBytes>>,
+    // The revision of the change.
+    revision: i64,
+    // When the event was received, in seconds since the epoch,
+    // as etcd does not record when changes were made.
+    timestamp: f64,
+}
+
+#[pymethods]
+impl PyChangefeedEvent {
+    pub fn __repr__(&self, py: Python) -> String {
+        format!(
+            "ChangefeedEvent(key={:?}, event={:?}, value={:?}, revision={}, timestamp={})",
+            self.key.as_ref(py),
+            self.event.0,
+            self.value.as_ref().map(|value| value.as_ref(py)),
+            self.revision,
+            self.timestamp
+        )
+    }
+}
+
+impl PyChangefeedEvent {
+    /// Serializes the event as a JSON object, with the key and the value in base64
+    /// as `key_base64` and `value_base64` if they are not UTF-8.
+    fn to_json(&self, py: Python) -> PyResult<String> {
+        let record = PyDict::new(py);
+        set_field(py, record, "key", self.key.as_ref(py).as_bytes())?;
+        let event = match self.event.0 {
+            EventType::Put => "PUT",
+            EventType::Delete => "DELETE",
+        };
+        record.set_item("type", event)?;
+        if let Some(ref value) = self.value {
+            set_field(py, record, "value", value.as_ref(py).as_bytes())?;
+        }
+        record.set_item("revision", self.revision)?;
+        record.set_item("timestamp", self.timestamp)?;
+        py.import("json")?
+            .call_method1("dumps", (record,))?
+            .extract()
+    }
+}
+
+/// Where the events of a changefeed are delivered.
+enum Destination {
+    // Appended to as JSON lines.
+    File(String),
+    // Called with the events of each batch.
+    Sink(PyObject),
+}
+
+/// Delivers the changes under a prefix to a destination at least once, recording the revision
+/// delivered up to in a checkpoint file to resume from.
+struct Feed {
+    communicator: Py<PyCommunicator>,
+    prefix: Vec<u8>,
+    checkpoint: String,
+    destination: Destination,
+    // The revision delivered up to, or 0 before it is known.
+    revision: AtomicI64,
+}
+
+impl Feed {
+    /// Returns the revision recorded in the checkpoint file, if it exists.
+    async fn read_checkpoint(&self) -> PyResult<Option<i64>> {
+        let text = match fs::read_to_string(&self.checkpoint).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(e)),
+        };
+        match text.trim().parse() {
+            Ok(revision) => Ok(Some(revision)),
+            Err(_) => Err(IoError::new_err(format!(
+                "the checkpoint {} does not hold a revision",
+                self.checkpoint
+            ))),
+        }
+    }
+
+    /// Records `revision` in the checkpoint file, replacing it at once,
+    /// so that a crash leaves either the previous revision or this one.
+    async fn write_checkpoint(&self, revision: i64) -> PyResult<()> {
+        let temporary = format!("{}.tmp", self.checkpoint);
+        let mut file = fs::File::create(&temporary).await.map_err(io_error)?;
+        file.write_all(revision.to_string().as_bytes())
+            .await
+            .map_err(io_error)?;
+        file.sync_all().await.map_err(io_error)?;
+        fs::rename(&temporary, &self.checkpoint)
+            .await
+            .map_err(io_error)?;
+        self.revision.store(revision, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn deliver(&self, events: &[PyChangefeedEvent]) -> PyResult<()> {
+        match self.destination {
+            Destination::File(ref path) => {
+                let lines = Python::with_gil(|py| {
+                    let mut lines = String::new();
+                    for event in events {
+                        lines.push_str(&event.to_json(py)?);
+                        lines.push('\n');
+                    }
+                    PyResult::Ok(lines)
+                })?;
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(io_error)?;
+                file.write_all(lines.as_bytes()).await.map_err(io_error)?;
+                file.sync_all().await.map_err(io_error)
+            }
+            Destination::Sink(ref sink) => Python::with_gil(|py| {
+                let events = PyList::new(py, events.iter().map(|event| event.clone().into_py(py)));
+                sink.call1(py, (events,))?;
+                Ok(())
+            }),
+        }
+    }
+
+    /// Delivers `events`, again after a backoff whenever it fails, as they must not be skipped.
+    async fn deliver_retrying(&self, events: &[PyChangefeedEvent]) {
+        let reconnect =
+            Python::with_gil(|py| self.communicator.borrow(py).connect_options.reconnect);
+        let mut attempts = 0;
+        while let Err(e) = self.deliver(events).await {
+            let backoff = reconnect.backoff(attempts);
+            attempts += 1;
+            log::warn!("delivering the events of the changefeed again in {backoff:?}: {e}");
+            sleep(backoff).await;
+        }
+    }
+
+    /// Delivers the events after `revision` as they come, recording the revision of each batch
+    /// once delivered, until the watch is lost, which resets `attempts` once it is created.
+    async fn watch(&self, attempts: &mut u32) -> PyResult<()> {
+        let client = Python::with_gil(|py| {
+            let communicator = self.communicator.borrow(py);
+            communicator
+                .check_process()
+                .map(|_| communicator.client.clone())
+        })?;
+        let mut client = connected(&client).await?;
+        let revision = self.revision.load(Ordering::SeqCst);
+        let mut options = WatchOptions::new().with_prefix().with_progress_notify();
+        // Without a checkpoint, the changefeed starts from the changes made from now on.
+        if revision > 0 {
+            options = options.with_start_revision(revision + 1);
+        }
+        let (_watcher, mut stream) = client
+            .watch(self.prefix.clone(), Some(options))
+            .await
+            .map_err(PyClientError)?;
+        *attempts = 0;
+
+        while let Some(response) = stream.message().await.map_err(PyClientError)? {
+            if response.canceled() {
+                if response.compact_revision() > 0 {
+                    return Err(compacted_error(response.compact_revision()));
+                }
+                return Err(WatchError::new_err(
+                    "the watch of the changefeed was canceled",
+                ));
+            }
+
+            if response.events().is_empty() {
+                // Progress notifications tell the events are delivered up to their header,
+                // which the creation of a watch without a checkpoint starts after.
+                let revision = response.header().map_or(0, |header| header.revision());
+                let current = self.revision.load(Ordering::SeqCst);
+                let progressed = !response.created() || current == 0;
+                if progressed && revision > current {
+                    self.write_checkpoint(revision).await?;
+                }
+                continue;
+            }
+
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+            let events = Python::with_gil(|py| {
+                response
+                    .events()
+                    .iter()
+                    .filter_map(|event| {
+                        let kv = event.kv()?;
+                        let value = match event.event_type() {
+                            EventType::Put => Some(PyBytes::new(py, kv.value()).into()),
+                            EventType::Delete => None,
+                        };
+                        Some(PyChangefeedEvent {
+                            key: PyBytes::new(py, kv.key()).into(),
+                            event: PyWatchEventType(event.event_type()),
+                            value,
+                            revision: kv.mod_revision(),
+                            timestamp,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            });
+            self.deliver_retrying(&events).await;
+            if let Some(event) = events.last() {
+                self.write_checkpoint(event.revision).await?;
+            }
+        }
+        Err(ConnectionLostError::new_err(
+            "the watch stream of the changefeed ended",
+        ))
+    }
+
+    /// Keeps delivering the changes, watching the prefix again after a backoff whenever the
+    /// watch is lost, unless the revision to resume from was compacted.
+    async fn follow(&self) -> PyResult<()> {
+        if let Some(revision) = self.read_checkpoint().await? {
+            self.revision.store(revision, Ordering::SeqCst);
+        }
+        let reconnect =
+            Python::with_gil(|py| self.communicator.borrow(py).connect_options.reconnect);
+        let mut attempts = 0;
+        loop {
+            let Err(e) = self.watch(&mut attempts).await else {
+                continue;
+            };
+            if Python::with_gil(|py| e.is_instance_of::<CompactedError>(py)) {
+                return Err(e);
+            }
+            let backoff = reconnect.backoff(attempts);
+            attempts += 1;
+            log::warn!("watching the prefix of the changefeed again in {backoff:?}: {e}");
+            sleep(backoff).await;
+        }
+    }
+}
+
+/// Delivers every change under a prefix, at least once and in order, to a file or a sink,
+/// resuming after the revision recorded in a checkpoint file.
+#[pyclass(name = "Changefeed")]
+pub struct PyChangefeed {
+    feed: Arc<Feed>,
+}
+
+impl PyChangefeed {
+    pub fn new(
+        communicator: Py<PyCommunicator>,
+        prefix: Vec<u8>,
+        checkpoint: String,
+        path: Option<String>,
+        sink: Option<PyObject>,
+    ) -> PyResult<Self> {
+        let destination = match (path, sink) {
+            (Some(path), None) => Destination::File(path),
+            (None, Some(sink)) => Destination::Sink(sink),
+            _ => {
+                return Err(InvalidArgsError::new_err(
+                    "exactly one of path and sink must be given",
+                ))
+            }
+        };
+        Ok(Self {
+            feed: Arc::new(Feed {
+                communicator,
+                prefix,
+                checkpoint,
+                destination,
+                revision: AtomicI64::new(0),
+            }),
+        })
+    }
+}
+
+#[pymethods]
+impl PyChangefeed {
+    /// The revision which the events are delivered up to, or 0 before it is known.
+    #[getter]
+    fn revision(&self) -> i64 {
+        self.feed.revision.load(Ordering::SeqCst)
+    }
+
+    /// Delivers the changes until cancelled or the communicator is closed.
+    fn run<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let feed = self.feed.clone();
+        let closed = feed.communicator.borrow(py).closed.subscribe();
+
+        future_into_py(py, async move {
+            tokio::select! {
+                result = feed.follow() => result,
+                _ = wait_closed(closed) => Ok(()),
+            }
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use pyo3::prelude::*;
+use std::time::{Duration, Instant};
+
+use crate::error::CircuitOpenError;
+
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerPolicy {
+    pub failure_threshold: u32,
+    pub reset_timeout: f64,
+}
+
+/// Fails requests fast once `failure_threshold` requests in a row failed on the transport.
+/// After `reset_timeout`, a single request is let through as a probe,
+/// whose outcome closes the circuit again or keeps it open for another `reset_timeout`.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    policy: CircuitBreakerPolicy,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: CircuitBreakerPolicy) -> Self {
+        Self {
+            policy,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Checks whether a request may be sent, raising `CircuitOpenError` if not.
+    pub fn acquire(&mut self) -> PyResult<()> {
+        let Some(opened_at) = self.opened_at else {
+            return Ok(());
+        };
+        let reset_timeout = Duration::from_secs_f64(self.policy.reset_timeout);
+        let elapsed = opened_at.elapsed();
+        if elapsed < reset_timeout {
+            return Err(CircuitOpenError::new_err(format!(
+                "circuit open after {} consecutive transport failures, retrying in {:.1}s",
+                self.consecutive_failures,
+                (reset_timeout - elapsed).as_secs_f64()
+            )));
+        }
+        // Re-arming the timer lets only this request through, and another one
+        // after `reset_timeout` in case this one never reports back.
+        self.opened_at = Some(Instant::now());
+        Ok(())
+    }
+
+    pub fn record(&mut self, transport_failed: bool) {
+        if !transport_failed {
+            self.consecutive_failures = 0;
+            self.opened_at = None;
+            return;
+        }
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= self.policy.failure_threshold {
+            if self.opened_at.is_none() {
+                log::warn!(
+                    "circuit opened after {} consecutive transport failures",
+                    self.consecutive_failures
+                );
+            }
+            self.opened_at = Some(Instant::now());
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use etcd_client::{AlarmAction, AlarmType, Client as EtcdClient, Member};
+use pyo3::prelude::*;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::client::PyConnectOptions;
+use crate::error::{ClientError, PyClientError};
+use crate::maintenance_response::{PyAlarmMember, PyStatusResponse};
+use crate::member::PyMember;
+
+#[pyclass(get_all, name = "MemberInfo")]
+#[derive(Clone)]
+pub struct PyMemberInfo {
+    member: PyMember,
+    is_leader: bool,
+    status: Option<PyStatusResponse>,
+    error: Option<String>,
+}
+
+#[pymethods]
+impl PyMemberInfo {
+    pub fn __repr__(&self) -> String {
+        format!(
+            "MemberInfo(id={}, name={:?}, is_leader={}, version={:?}, db_size={:?}, error={:?})",
+            self.member.id(),
+            self.member.name(),
+            self.is_leader,
+            self.status.as_ref().map(|status| status.version()),
+            self.status.as_ref().map(|status| status.db_size()),
+            self.error
+        )
+    }
+}
+
+impl PyMemberInfo {
+    /// Requests the status of `member` through its own client URLs,
+    /// as a status response only describes the member which served it.
+    /// A member which does not answer within `limit` is reported as unreachable.
+    async fn collect(member: Member, connect_options: PyConnectOptions, limit: Duration) -> Self {
+        let result = if member.client_urls().is_empty() {
+            Err("member has no client URLs".to_owned())
+        } else {
+            let status = async {
+                let mut client =
+                    EtcdClient::connect(member.client_urls(), Some(connect_options.inner)).await?;
+                client.status().await
+            };
+            match timeout(limit, status).await {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err(format!("unreachable: timed out after {limit:?}")),
+            }
+        };
+
+        let (status, error) = match result {
+            Ok(status) => (Some(PyStatusResponse(status)), None),
+            Err(error) => (None, Some(error)),
+        };
+        let is_leader = status
+            .as_ref()
+            .is_some_and(|status| status.leader() == member.id());
+
+        Self {
+            member: PyMember(member),
+            is_leader,
+            status,
+            error,
+        }
+    }
+}
+
+#[pyclass(get_all, name = "ClusterInfo")]
+#[derive(Clone)]
+pub struct PyClusterInfo {
+    cluster_id: u64,
+    leader: Option<u64>,
+    members: Vec<PyMemberInfo>,
+    alarms: Vec<PyAlarmMember>,
+}
+
+#[pymethods]
+impl PyClusterInfo {
+    pub fn __repr__(&self) -> String {
+        let members = self
+            .members
+            .iter()
+            .map(|member| member.__repr__())
+            .collect::<Vec<_>>();
+        let alarms = self
+            .alarms
+            .iter()
+            .map(|alarm| alarm.__repr__())
+            .collect::<Vec<_>>();
+        format!(
+            "ClusterInfo(cluster_id={}, leader={:?}, members=[{}], alarms=[{}])",
+            self.cluster_id,
+            self.leader,
+            members.join(", "),
+            alarms.join(", ")
+        )
+    }
+}
+
+impl PyClusterInfo {
+    pub async fn collect(
+        endpoints: Vec<String>,
+        connect_options: PyConnectOptions,
+        limit: Duration,
+    ) -> PyResult<Self> {
+        let mut client = EtcdClient::connect(endpoints, Some(connect_options.inner.clone()))
+            .await
+            .map_err(PyClientError)?;
+
+        let member_list = client.member_list().await.map_err(PyClientError)?;
+        let alarms = client
+            .alarm(AlarmAction::Get, AlarmType::None, None)
+            .await
+            .map_err(PyClientError)?
+            .alarms()
+            .iter()
+            .cloned()
+            .map(PyAlarmMember)
+            .collect();
+
+        let tasks = member_list
+            .members()
+            .iter()
+            .cloned()
+            .map(|member| {
+                tokio::spawn(PyMemberInfo::collect(
+                    member,
+                    connect_options.clone(),
+                    limit,
+                ))
+            })
+            .collect::<Vec<_>>();
+        // Dropped along with this future when the awaiting task is cancelled.
+        let mut tasks = scopeguard::guard(tasks, |tasks| {
+            tasks.iter().for_each(JoinHandle::abort);
+        });
+
+        let mut members = Vec::with_capacity(tasks.len());
+        for task in tasks.iter_mut() {
+            members.push(
+                task.await
+                    .map_err(|e| ClientError::new_err(e.to_string()))?,
+            );
+        }
+
+        // Members can briefly disagree during an election, so trust the member
+        // which reports itself as the leader over the others.
+        let leader = members
+            .iter()
+            .find(|member| member.is_leader)
+            .map(|member| member.member.id());
+
+        Ok(Self {
+            cluster_id: member_list.header().map_or(0, |header| header.cluster_id()),
+            leader,
+            members,
+            alarms,
+        })
+    }
+}
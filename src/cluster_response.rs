@@ -0,0 +1,91 @@
+use etcd_client::{
+    Member, MemberAddResponse, MemberListResponse, MemberRemoveResponse, MemberUpdateResponse,
+};
+use pyo3::prelude::*;
+
+use crate::member::PyMember;
+use crate::response_header::PyResponseHeader;
+
+fn to_py_members(members: &[Member]) -> Vec<PyMember> {
+    members.iter().cloned().map(PyMember).collect()
+}
+
+#[derive(Clone)]
+#[pyclass(name = "MemberListResponse")]
+pub struct PyMemberListResponse(pub MemberListResponse);
+
+#[pymethods]
+impl PyMemberListResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn members(&self) -> Vec<PyMember> {
+        to_py_members(self.0.members())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "MemberAddResponse")]
+pub struct PyMemberAddResponse(pub MemberAddResponse);
+
+#[pymethods]
+impl PyMemberAddResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn member(&self) -> Option<PyMember> {
+        self.0.member().cloned().map(PyMember)
+    }
+
+    pub fn members(&self) -> Vec<PyMember> {
+        to_py_members(self.0.member_list())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "MemberRemoveResponse")]
+pub struct PyMemberRemoveResponse(pub MemberRemoveResponse);
+
+#[pymethods]
+impl PyMemberRemoveResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn members(&self) -> Vec<PyMember> {
+        to_py_members(self.0.members())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "MemberUpdateResponse")]
+pub struct PyMemberUpdateResponse(pub MemberUpdateResponse);
+
+#[pymethods]
+impl PyMemberUpdateResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn members(&self) -> Vec<PyMember> {
+        to_py_members(self.0.members())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
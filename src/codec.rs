@@ -0,0 +1,44 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+/// Converts values between the Python objects of the application and the bytes stored in etcd.
+#[derive(Clone, Debug)]
+pub enum Codec {
+    Json,
+    Custom { encode: PyObject, decode: PyObject },
+}
+
+impl Codec {
+    pub fn encode(&self, py: Python<'_>, value: &PyAny) -> PyResult<Vec<u8>> {
+        match self {
+            Codec::Json => {
+                let text: String = py
+                    .import("json")?
+                    .call_method1("dumps", (value,))?
+                    .extract()?;
+                Ok(text.into_bytes())
+            }
+            Codec::Custom { encode, .. } => {
+                let encoded = encode.call1(py, (value,))?;
+                Ok(encoded.extract::<&PyBytes>(py)?.as_bytes().to_vec())
+            }
+        }
+    }
+
+    pub fn decode(&self, py: Python<'_>, bytes: &[u8]) -> PyResult<PyObject> {
+        let bytes = PyBytes::new(py, bytes);
+        match self {
+            Codec::Json => Ok(py.import("json")?.call_method1("loads", (bytes,))?.into()),
+            Codec::Custom { decode, .. } => decode.call1(py, (bytes,)),
+        }
+    }
+}
+
+/// Converts `value` given to a request to the bytes to store,
+/// which it must already be unless `codec` is set.
+pub fn encode_value(py: Python<'_>, codec: Option<&Codec>, value: &PyAny) -> PyResult<Vec<u8>> {
+    match codec {
+        Some(codec) => codec.encode(py, value),
+        None => Ok(value.extract::<&PyBytes>()?.as_bytes().to_vec()),
+    }
+}
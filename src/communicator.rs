@@ -1,221 +1,1657 @@
 use etcd_client::Client as EtcdClient;
-use etcd_client::{DeleteOptions, GetOptions, WatchOptions};
+use etcd_client::{
+    AlarmOptions, AlarmType, Compare, CompareOp, DeleteOptions, EventType, GetOptions,
+    MemberAddOptions, Permission, PermissionType, ProclaimOptions, PutOptions, ResignOptions,
+    RoleRevokePermissionOptions, Txn, TxnOp, TxnOpResponse, WatchFilterType, WatchOptions,
+};
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
-use pyo3_asyncio::tokio::future_into_py;
+use pyo3::types::{PyBytes, PyDict};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{watch, Mutex, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
 
+use crate::audit;
+use crate::auth::{permits, PyPermissionType};
+use crate::auth_response::{
+    PyAuthenticateResponse, PyRoleAddResponse, PyRoleDeleteResponse, PyRoleGetResponse,
+    PyRoleGrantPermissionResponse, PyRoleListResponse, PyRoleRevokePermissionResponse,
+    PyUserChangePasswordResponse, PyUserGrantRoleResponse, PyUserRevokeRoleResponse,
+};
+use crate::changefeed::PyChangefeed;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::client::PyConnectOptions;
+use crate::cluster_response::{
+    PyMemberAddResponse, PyMemberListResponse, PyMemberRemoveResponse, PyMemberUpdateResponse,
+};
+use crate::codec::encode_value;
 use crate::condvar::PyCondVar;
-use crate::error::PyClientError;
-use crate::txn::PyTxn;
+use crate::config_watcher::PyConfigWatcher;
+use crate::connection_state::{set_state, ConnectionState, PyConnectionStateChanges};
+use crate::election::PyLeaderKey;
+use crate::election_response::{
+    PyCampaignResponse, PyLeaderResponse, PyProclaimResponse, PyResignResponse,
+};
+use crate::error::{
+    add_context, io_error, not_found_error, ClientError, ConflictError, InvalidArgsError,
+    PyClientError, PyGRPCStatusCode, RequestTimeoutError,
+};
+use crate::export::{check_chunk_size, export_prefix, import_prefix};
+use crate::fault::{Faults, PyFault};
+use crate::key_value::KvBytes;
+use crate::lease_response::PyLeaseGrantResponse;
+use crate::load_balancing::LoadBalancing;
+use crate::maintenance::{PyAlarmAction, PyAlarmType};
+use crate::maintenance_response::{
+    PyAlarmResponse, PyDefragmentResponse, PyHashKvResponse, PyStatusResponse,
+};
+use crate::mapping::PyPrefixMapping;
+use crate::metrics::{self, LatencyStats};
+use crate::migration::{check_prefixes, copy_prefix};
+use crate::mirror::PyMirror;
+use crate::observe::PyObserve;
+use crate::presence::{PyMembers, PyPresence};
+use crate::reconnect::is_transport_error;
+use crate::recording::PyRecordingCommunicator;
+use crate::runtime::{future_into_py, wait_shutdown};
+use crate::schema::PySchema;
+use crate::services::PyServiceRegistry;
+use crate::snapshot::PySnapshot;
+use crate::telemetry::{response_size, sanitize_key, RequestSpan};
+use crate::txn::{PyTxn, PyTxnOp};
 use crate::txn_response::PyTxnResponse;
-use crate::watch::PyWatch;
+use crate::watch::{PyWatch, ReadyEvent};
+use crate::watch_event::PyWatchEventType;
+use crate::writer::PyPutWriter;
 
 #[pyclass(name = "Communicator")]
-pub struct PyCommunicator(pub Arc<Mutex<EtcdClient>>);
+pub struct PyCommunicator {
+    // Connected to the same endpoints over connections of their own, and empty once closed.
+    pub client: Arc<Mutex<Vec<EtcdClient>>>,
+    // Picks the client for the next request, so that requests are spread over them in turn.
+    pub next: Arc<AtomicUsize>,
+    // Counts the replacements of `client`, so that concurrent requests replace it only once.
+    pub generation: Arc<AtomicU64>,
+    // The endpoints connected to, which change when a pinned client fails over.
+    pub endpoints: Arc<std::sync::Mutex<Vec<String>>>,
+    // The endpoints a pinned client fails over to in turn, starting with the connected one.
+    pub failover_endpoints: Vec<String>,
+    pub connect_options: PyConnectOptions,
+    // Shared with the views of the communicator, so that closing any of them closes all.
+    pub closed: Arc<watch::Sender<bool>>,
+    // Set when the health of the connection is checked in the background.
+    pub healthy: Option<watch::Receiver<bool>>,
+    // Updated by the requests and health checks of the communicator and its views.
+    pub state: Arc<watch::Sender<ConnectionState>>,
+    circuit_breaker: Option<Arc<std::sync::Mutex<CircuitBreaker>>>,
+    // Shared with the views of the communicator, whose requests it includes.
+    stats: Arc<std::sync::Mutex<LatencyStats>>,
+    // Set on views with a timeout, which requests, including their retries, must not exceed.
+    timeout: Option<f64>,
+    // Passed to the audit callback along with the changes made through the view.
+    audit_context: Option<PyObject>,
+    // Shared with the views of the communicator, whose requests they are injected into too.
+    faults: Faults,
+    // The process which created the communicator, whose runtime its connections belong to.
+    pid: u32,
+}
 
 #[pymethods]
 impl PyCommunicator {
     // TODO: Implement and use the CRUD response types
-    fn get<'a>(&'a self, py: Python<'a>, key: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    /// Returns the value of `key`, or `default` if it does not exist,
+    /// unless `strict` is set, which raises `NotFoundError` instead.
+    #[pyo3(signature = (key, default=None, *, strict=false))]
+    fn get<'a>(
+        &'a self,
+        py: Python<'a>,
+        key: &PyBytes,
+        default: Option<PyObject>,
+        strict: bool,
+    ) -> PyResult<&'a PyAny> {
         let key = key.as_bytes().to_vec();
+        let serializable = self.connect_options.serializable_reads;
+        let request = self.idempotent_request("get", Some(key.clone()), {
+            let key = key.clone();
+            move |mut client| {
+                let key = key.clone();
+                async move {
+                    let options = serializable.then(|| GetOptions::new().with_serializable());
+                    client.get(key, options).await
+                }
+            }
+        });
+
+        let decoding = self.connect_options.text_decoding;
+        let codec = self.connect_options.codec.clone();
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.get(key, None).await;
-            result
-                .map(|response| {
-                    let kvs = response.kvs();
-                    if !kvs.is_empty() {
-                        Some(kvs[0].value().to_owned())
-                    } else {
-                        None
-                    }
-                })
-                .map_err(|e| PyClientError(e).into())
+            let mut response = request.await?;
+            match response.take_kvs().into_iter().next() {
+                Some(kv) => {
+                    let value = KvBytes::value(kv, decoding, codec.as_ref())?;
+                    Ok(Python::with_gil(|py| value.into_py(py)))
+                }
+                None if strict => Err(not_found_error("get", &key)),
+                None => Ok(default.unwrap_or_else(|| Python::with_gil(|py| py.None()))),
+            }
         })
     }
 
     fn get_prefix<'a>(&'a self, py: Python<'a>, prefix: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
         let prefix = prefix.as_bytes().to_vec();
+        let serializable = self.connect_options.serializable_reads;
+        let request =
+            self.idempotent_request("get_prefix", Some(prefix.clone()), move |mut client| {
+                let prefix = prefix.clone();
+                async move {
+                    let mut options = GetOptions::new().with_prefix();
+                    if serializable {
+                        options = options.with_serializable();
+                    }
+                    client.get(prefix, Some(options)).await
+                }
+            });
 
+        let decoding = self.connect_options.text_decoding;
+        let codec = self.connect_options.codec.clone();
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let options = GetOptions::new().with_prefix();
-            let result = client.get(prefix, Some(options)).await;
-            result
-                .map(|response| {
-                    let mut list = vec![];
-                    let kvs = response.kvs();
-                    for kv in kvs {
-                        list.push((kv.key().to_owned(), kv.value().to_owned()));
-                    }
-                    list
-                })
-                .map_err(|e| PyClientError(e).into())
+            let mut response = request.await?;
+            response
+                .take_kvs()
+                .into_iter()
+                .map(|kv| KvBytes::pair(kv, decoding, codec.as_ref()))
+                .collect::<PyResult<Vec<_>>>()
         })
     }
 
-    fn put<'a>(&'a self, py: Python<'a>, key: &PyBytes, value: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    fn put<'a>(&'a self, py: Python<'a>, key: &PyBytes, value: &PyAny) -> PyResult<&'a PyAny> {
         let key = key.as_bytes().to_vec();
-        let value = value.as_bytes().to_vec();
+        let value = encode_value(py, self.connect_options.codec.as_ref(), value)?;
+        // The audit tells the revision of the previous key.
+        let prev_key = self.connect_options.audit.is_some();
+        let request = self.request("put", Some(key.clone()), move |mut client| {
+            let (key, value) = (key.clone(), value.clone());
+            async move {
+                let options = prev_key.then(|| PutOptions::new().with_prev_key());
+                client.put(key, value, options).await
+            }
+        });
 
-        future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.put(key, value, None).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
-        })
+        future_into_py(py, async move { request.await.map(|_| ()) })
     }
 
     fn delete<'a>(&'a self, py: Python<'a>, key: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
         let key = key.as_bytes().to_vec();
+        let prev_key = self.connect_options.audit.is_some();
+        let request = self.request("delete", Some(key.clone()), move |mut client| {
+            let key = key.clone();
+            async move {
+                let options = prev_key.then(|| DeleteOptions::new().with_prev_key());
+                client.delete(key, options).await
+            }
+        });
 
-        future_into_py(py, async move {
-            let mut client = client.lock().await;
-
-            let result = client.delete(key, None).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
-        })
+        future_into_py(py, async move { request.await.map(|_| ()) })
     }
 
     fn delete_prefix<'a>(&'a self, py: Python<'a>, key: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
         let key = key.as_bytes().to_vec();
+        let request = self.request("delete_prefix", Some(key.clone()), move |mut client| {
+            let key = key.clone();
+            async move {
+                let options = DeleteOptions::new().with_prefix();
+                client.delete(key, Some(options)).await
+            }
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    fn txn<'a>(&'a self, py: Python<'a>, txn: PyTxn) -> PyResult<&'a PyAny> {
+        let request = self.request("txn", None, move |mut client| {
+            let txn = txn.0.clone();
+            async move { client.txn(txn).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyTxnResponse::from) })
+    }
+
+    /// Sets `key` to what `transform` returns given its value, or `None` if it does not exist,
+    /// and returns it. The put only applies if the key was not modified since it was read,
+    /// otherwise it is read and transformed again, up to `max_retries` times,
+    /// after which `ConflictError` is raised.
+    #[pyo3(signature = (key, transform, max_retries=16))]
+    fn update<'a>(
+        slf: Py<Self>,
+        py: Python<'a>,
+        key: &PyBytes,
+        transform: PyObject,
+        max_retries: usize,
+    ) -> PyResult<&'a PyAny> {
+        let key = key.as_bytes().to_vec();
+        let communicator = slf.borrow(py);
+        let decoding = communicator.connect_options.text_decoding;
+        let codec = communicator.connect_options.codec.clone();
+        drop(communicator);
 
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let options = DeleteOptions::new().with_prefix();
-            let result = client.delete(key, Some(options)).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            for _ in 0..=max_retries {
+                let request = Python::with_gil(|py| {
+                    let key = key.clone();
+                    slf.borrow(py).idempotent_request(
+                        "update",
+                        Some(key.clone()),
+                        move |mut client| {
+                            let key = key.clone();
+                            // Not serializable, as the put only applies after reading
+                            // the latest revision.
+                            async move { client.get(key, None).await }
+                        },
+                    )
+                });
+                let mut response = request.await?;
+                let kv = response.take_kvs().into_iter().next();
+                // A key which does not exist compares with a modification revision of 0.
+                let revision = kv.as_ref().map_or(0, |kv| kv.mod_revision());
+                let current = kv
+                    .map(|kv| KvBytes::value(kv, decoding, codec.as_ref()))
+                    .transpose()?;
+
+                let (updated, value) = Python::with_gil(|py| -> PyResult<_> {
+                    let updated = transform.call1(py, (current.into_py(py),))?;
+                    let value = encode_value(py, codec.as_ref(), updated.as_ref(py))?;
+                    Ok((updated, value))
+                })?;
+
+                let txn = Txn::new()
+                    .when([Compare::mod_revision(
+                        key.clone(),
+                        CompareOp::Equal,
+                        revision,
+                    )])
+                    .and_then([TxnOp::put(key.clone(), value, None)]);
+                let request = Python::with_gil(|py| {
+                    slf.borrow(py)
+                        .request("update", Some(key.clone()), move |mut client| {
+                            let txn = txn.clone();
+                            async move { client.txn(txn).await }
+                        })
+                });
+                if request.await?.succeeded() {
+                    return Ok(updated);
+                }
+            }
+            let error = ConflictError::new_err(format!(
+                "the key was modified concurrently on each of {} attempts",
+                max_retries + 1
+            ));
+            Err(add_context(error, "update", Some(&key), None))
         })
     }
 
-    fn txn<'a>(&'a self, py: Python<'a>, txn: PyTxn) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    /// Copies the keys under `src` to `dst`, keeping their leases, `chunk_size` keys per
+    /// transaction which only applies if none of them was modified since the chunk was read.
+    /// Returns the number of keys copied.
+    #[pyo3(signature = (src, dst, chunk_size=64, progress=None))]
+    fn copy_prefix<'a>(
+        slf: Py<Self>,
+        py: Python<'a>,
+        src: &PyBytes,
+        dst: &PyBytes,
+        chunk_size: usize,
+        progress: Option<PyObject>,
+    ) -> PyResult<&'a PyAny> {
+        let (src, dst) = (src.as_bytes().to_vec(), dst.as_bytes().to_vec());
+        check_prefixes(&src, &dst, chunk_size)?;
+        future_into_py(py, copy_prefix(slf, src, dst, chunk_size, progress, false))
+    }
+
+    /// Moves the keys under `src` to `dst` like `copy_prefix()`,
+    /// deleting each chunk from `src` in the transaction copying it.
+    #[pyo3(signature = (src, dst, chunk_size=64, progress=None))]
+    fn move_prefix<'a>(
+        slf: Py<Self>,
+        py: Python<'a>,
+        src: &PyBytes,
+        dst: &PyBytes,
+        chunk_size: usize,
+        progress: Option<PyObject>,
+    ) -> PyResult<&'a PyAny> {
+        let (src, dst) = (src.as_bytes().to_vec(), dst.as_bytes().to_vec());
+        check_prefixes(&src, &dst, chunk_size)?;
+        future_into_py(py, copy_prefix(slf, src, dst, chunk_size, progress, true))
+    }
+
+    /// Writes the keys under `prefix` to the JSON file at `path`, or a JSONL file if it ends
+    /// with `.jsonl`, as they were at a single revision. Returns the number of keys written.
+    #[pyo3(signature = (prefix, path, chunk_size=1000))]
+    fn export_prefix<'a>(
+        slf: Py<Self>,
+        py: Python<'a>,
+        prefix: &PyBytes,
+        path: String,
+        chunk_size: usize,
+    ) -> PyResult<&'a PyAny> {
+        check_chunk_size(chunk_size)?;
+        let prefix = prefix.as_bytes().to_vec();
+        future_into_py(py, export_prefix(slf, prefix, path, chunk_size))
+    }
+
+    /// Puts the keys of a file written by `export_prefix()` under `prefix`, keeping the
+    /// existing keys unless `overwrite` is set. Returns the number of keys put.
+    #[pyo3(signature = (path, prefix, overwrite=false, chunk_size=64))]
+    fn import_prefix<'a>(
+        slf: Py<Self>,
+        py: Python<'a>,
+        path: String,
+        prefix: &PyBytes,
+        overwrite: bool,
+        chunk_size: usize,
+    ) -> PyResult<&'a PyAny> {
+        check_chunk_size(chunk_size)?;
+        let prefix = prefix.as_bytes().to_vec();
+        future_into_py(py, import_prefix(slf, path, prefix, overwrite, chunk_size))
+    }
+
+    /// Runs `ops` with up to `concurrency` of them in flight at once, returning for each op
+    /// what `get()`, `put()`, `delete()` or `txn()` would, or the exception it raised.
+    /// Gets are sent as reads of their own, retried like those of `get()`.
+    #[pyo3(signature = (ops, concurrency=32))]
+    fn bulk<'a>(
+        &'a self,
+        py: Python<'a>,
+        ops: Vec<PyTxnOp>,
+        concurrency: usize,
+    ) -> PyResult<&'a PyAny> {
+        if concurrency == 0 {
+            return Err(InvalidArgsError::new_err("concurrency must be at least 1"));
+        }
+
+        let ops = ops
+            .into_iter()
+            .map(|op| (op.get_key(py), op))
+            .collect::<Vec<_>>();
+        let communicator = Arc::new(self.view(self.connect_options.clone()));
+        let serializable = self.connect_options.serializable_reads;
+        let decoding = self.connect_options.text_decoding;
+        let codec = self.connect_options.codec.clone();
 
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.txn(txn.0).await;
-            result
-                .map(PyTxnResponse)
-                .map_err(|e| PyClientError(e).into())
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+            // Dropped along with this future when the awaiting task is cancelled.
+            let mut tasks = scopeguard::guard(Vec::with_capacity(ops.len()), |tasks| {
+                tasks.iter().for_each(JoinHandle::abort);
+            });
+            for (key, op) in ops {
+                // The semaphore is never closed.
+                let permit = semaphore.clone().acquire_owned().await.unwrap();
+                let communicator = communicator.clone();
+                let codec = codec.clone();
+                tasks.push(tokio::spawn(async move {
+                    // Built once admitted, so that the hooks, spans and latencies of the
+                    // request do not include the time it spent queued.
+                    let response = match key {
+                        Some(key) => {
+                            let request = communicator.idempotent_request(
+                                "bulk",
+                                Some(key.clone()),
+                                move |mut client| {
+                                    let key = key.clone();
+                                    async move {
+                                        let options = serializable
+                                            .then(|| GetOptions::new().with_serializable());
+                                        client.get(key, options).await
+                                    }
+                                },
+                            );
+                            match request.await?.take_kvs().into_iter().next() {
+                                Some(kv) => {
+                                    let value = KvBytes::value(kv, decoding, codec.as_ref())?;
+                                    Ok(Python::with_gil(|py| value.into_py(py)))
+                                }
+                                None => Ok(Python::with_gil(|py| py.None())),
+                            }
+                        }
+                        None => {
+                            let request = communicator.request("bulk", None, move |mut client| {
+                                let txn = Txn::new().and_then([op.0.clone()]);
+                                async move { client.txn(txn).await }
+                            });
+                            let response = request.await?;
+                            Ok(Python::with_gil(|py| match response.op_responses().pop() {
+                                Some(TxnOpResponse::Txn(response)) => {
+                                    PyTxnResponse::from(response).into_py(py)
+                                }
+                                _ => py.None(),
+                            }))
+                        }
+                    };
+                    drop(permit);
+                    response
+                }));
+            }
+
+            let mut responses = Vec::with_capacity(tasks.len());
+            for task in tasks.iter_mut() {
+                responses.push(
+                    task.await
+                        .map_err(|e| ClientError::new_err(e.to_string()))?,
+                );
+            }
+            Ok(Python::with_gil(|py| {
+                responses
+                    .into_iter()
+                    .map(|response: PyResult<PyObject>| match response {
+                        Ok(response) => response,
+                        Err(error) => error.into_py(py),
+                    })
+                    .collect::<Vec<PyObject>>()
+            }))
         })
     }
 
     fn keys_prefix<'a>(&'a self, py: Python<'a>, key: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
         let key = key.as_bytes().to_vec();
+        let serializable = self.connect_options.serializable_reads;
+        let request =
+            self.idempotent_request("keys_prefix", Some(key.clone()), move |mut client| {
+                let key = key.clone();
+                async move {
+                    let mut options = GetOptions::new().with_prefix();
+                    if serializable {
+                        options = options.with_serializable();
+                    }
+                    client.get(key, Some(options)).await
+                }
+            });
 
+        let decoding = self.connect_options.text_decoding;
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let options = GetOptions::new().with_prefix();
-            let result = client.get(key, Some(options)).await;
-            result
-                .map(|response| {
-                    let mut result = Vec::new();
-                    let kvs = response.kvs();
-                    for kv in kvs {
-                        result.push(kv.key().to_owned());
-                    }
-                    result
-                })
-                .map_err(|e| PyClientError(e).into())
+            let mut response = request.await?;
+            response
+                .take_kvs()
+                .into_iter()
+                .map(|kv| KvBytes::key(kv, decoding))
+                .collect::<PyResult<Vec<_>>>()
         })
     }
 
     fn lock<'a>(&'a self, py: Python<'a>, name: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
         let name = name.as_bytes().to_vec();
+        let request = self.request("lock", Some(name.clone()), move |mut client| {
+            let name = name.clone();
+            async move { client.lock(name, None).await }
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    fn unlock<'a>(&'a self, py: Python<'a>, name: &PyBytes) -> PyResult<&'a PyAny> {
+        let name = name.as_bytes().to_vec();
+        let request = self.request("unlock", Some(name.clone()), move |mut client| {
+            let name = name.clone();
+            async move { client.unlock(name).await }
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    // TODO: Implement and use the response types of `lease` type's methods
+    fn lease_grant<'a>(&'a self, py: Python<'a>, ttl: i64) -> PyResult<&'a PyAny> {
+        let request = self.request("lease_grant", None, move |mut client| async move {
+            client.lease_grant(ttl, None).await
+        });
+
+        future_into_py(py, async move { request.await.map(PyLeaseGrantResponse) })
+    }
+
+    fn lease_revoke<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
+        let request = self.request("lease_revoke", None, move |mut client| async move {
+            client.lease_revoke(id).await
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    fn lease_time_to_live<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
+        let request = self.request("lease_time_to_live", None, move |mut client| async move {
+            client.lease_time_to_live(id, None).await
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    fn lease_keep_alive<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
+        let request = self.request("lease_keep_alive", None, move |mut client| async move {
+            client.lease_keep_alive(id).await
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    fn campaign<'a>(
+        &'a self,
+        py: Python<'a>,
+        name: &PyBytes,
+        value: &PyBytes,
+        lease: i64,
+    ) -> PyResult<&'a PyAny> {
+        self.check_process()?;
+        let client = self.client.clone();
+        let name = name.as_bytes().to_vec();
+        let value = value.as_bytes().to_vec();
 
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.lock(name, None).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            // Campaigning blocks until leadership is acquired,
+            // so it must not hold the shared client while waiting.
+            let mut client = connected(&client).await?;
+            let result = client.campaign(name, value, lease).await;
+            result
+                .map(PyCampaignResponse)
+                .map_err(|e| PyClientError(e).into())
         })
     }
 
-    fn unlock<'a>(&'a self, py: Python<'a>, name: &PyBytes) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    fn proclaim<'a>(
+        &'a self,
+        py: Python<'a>,
+        value: &PyBytes,
+        leader: PyLeaderKey,
+    ) -> PyResult<&'a PyAny> {
+        let value = value.as_bytes().to_vec();
+        let request = self.request("proclaim", None, move |mut client| {
+            let value = value.clone();
+            let options = ProclaimOptions::new().with_leader(leader.0.clone());
+            async move { client.proclaim(value, Some(options)).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyProclaimResponse) })
+    }
+
+    fn leader<'a>(&'a self, py: Python<'a>, name: &PyBytes) -> PyResult<&'a PyAny> {
         let name = name.as_bytes().to_vec();
+        let request = self.idempotent_request("leader", Some(name.clone()), move |mut client| {
+            let name = name.clone();
+            async move { client.leader(name).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyLeaderResponse) })
+    }
+
+    fn resign<'a>(&'a self, py: Python<'a>, leader: PyLeaderKey) -> PyResult<&'a PyAny> {
+        let request = self.request("resign", None, move |mut client| {
+            let options = ResignOptions::new().with_leader(leader.0.clone());
+            async move { client.resign(Some(options)).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyResignResponse) })
+    }
+
+    fn authenticate<'a>(
+        &'a self,
+        py: Python<'a>,
+        user: String,
+        password: String,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.idempotent_request("authenticate", None, move |client| {
+            let (user, password) = (user.clone(), password.clone());
+            async move { client.auth_client().authenticate(user, password).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyAuthenticateResponse) })
+    }
+
+    /// Evaluates the roles of `user` on the client side, which needs permission to read
+    /// the user and its roles, e.g. as root.
+    #[pyo3(signature = (user, key, perm_type=None))]
+    fn can<'a>(
+        &'a self,
+        py: Python<'a>,
+        user: String,
+        key: &PyBytes,
+        perm_type: Option<PyPermissionType>,
+    ) -> PyResult<&'a PyAny> {
+        let key = key.as_bytes().to_vec();
+        let perm_type = perm_type.map_or(PermissionType::Read, |perm_type| perm_type.0);
+        let request = self.idempotent_request("can", Some(key.clone()), move |mut client| {
+            let (user, key) = (user.clone(), key.clone());
+            async move {
+                let roles = client.user_get(user).await?.roles().to_vec();
+                if roles.iter().any(|role| role == "root") {
+                    return Ok(true);
+                }
+
+                let mut perms = Vec::new();
+                for role in roles {
+                    perms.extend(client.role_get(role).await?.permissions());
+                }
+                Ok(permits(&perms, &key, perm_type))
+            }
+        });
+
+        future_into_py(py, request)
+    }
+
+    fn user_change_password<'a>(
+        &'a self,
+        py: Python<'a>,
+        name: String,
+        new_password: String,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.request("user_change_password", None, move |mut client| {
+            let (name, new_password) = (name.clone(), new_password.clone());
+            async move { client.user_change_password(name, new_password).await }
+        });
 
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.unlock(name).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            request.await.map(PyUserChangePasswordResponse)
         })
     }
 
-    // TODO: Implement and use the response types of `lease` type's methods
-    fn lease_grant<'a>(&'a self, py: Python<'a>, ttl: i64) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    fn user_grant_role<'a>(
+        &'a self,
+        py: Python<'a>,
+        user: String,
+        role: String,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.request("user_grant_role", None, move |mut client| {
+            let (user, role) = (user.clone(), role.clone());
+            async move { client.user_grant_role(user, role).await }
+        });
+
+        future_into_py(
+            py,
+            async move { request.await.map(PyUserGrantRoleResponse) },
+        )
+    }
+
+    fn user_revoke_role<'a>(
+        &'a self,
+        py: Python<'a>,
+        user: String,
+        role: String,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.request("user_revoke_role", None, move |mut client| {
+            let (user, role) = (user.clone(), role.clone());
+            async move { client.user_revoke_role(user, role).await }
+        });
+
+        future_into_py(
+            py,
+            async move { request.await.map(PyUserRevokeRoleResponse) },
+        )
+    }
+
+    fn role_add<'a>(&'a self, py: Python<'a>, name: String) -> PyResult<&'a PyAny> {
+        let request = self.request("role_add", None, move |mut client| {
+            let name = name.clone();
+            async move { client.role_add(name).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyRoleAddResponse) })
+    }
+
+    fn role_get<'a>(&'a self, py: Python<'a>, name: String) -> PyResult<&'a PyAny> {
+        let request = self.idempotent_request("role_get", None, move |mut client| {
+            let name = name.clone();
+            async move { client.role_get(name).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyRoleGetResponse) })
+    }
+
+    fn role_list<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.idempotent_request("role_list", None, |mut client| async move {
+            client.role_list().await
+        });
+
+        future_into_py(py, async move { request.await.map(PyRoleListResponse) })
+    }
+
+    #[pyo3(signature = (role, key, range_end=None, perm_type=None, prefix=false))]
+    fn role_grant_permission<'a>(
+        &'a self,
+        py: Python<'a>,
+        role: String,
+        key: &PyBytes,
+        range_end: Option<&PyBytes>,
+        perm_type: Option<PyPermissionType>,
+        prefix: bool,
+    ) -> PyResult<&'a PyAny> {
+        let perm_type = perm_type.map_or(PermissionType::Read, |perm_type| perm_type.0);
+        let mut perm = Permission::new(perm_type, key.as_bytes());
+        match (range_end, prefix) {
+            (Some(_), true) => {
+                return Err(InvalidArgsError::new_err(
+                    "range_end and prefix are mutually exclusive",
+                ))
+            }
+            (Some(range_end), false) => perm = perm.with_range_end(range_end.as_bytes()),
+            (None, true) => perm = perm.with_prefix(),
+            (None, false) => {}
+        }
+
+        let request = self.request(
+            "role_grant_permission",
+            Some(key.as_bytes().to_vec()),
+            move |mut client| {
+                let (role, perm) = (role.clone(), perm.clone());
+                async move { client.role_grant_permission(role, perm).await }
+            },
+        );
+
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.lease_grant(ttl, None).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            request.await.map(PyRoleGrantPermissionResponse)
         })
     }
 
-    fn lease_revoke<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    #[pyo3(signature = (role, key, range_end=None, prefix=false))]
+    fn role_revoke_permission<'a>(
+        &'a self,
+        py: Python<'a>,
+        role: String,
+        key: &PyBytes,
+        range_end: Option<&PyBytes>,
+        prefix: bool,
+    ) -> PyResult<&'a PyAny> {
+        let key = key.as_bytes().to_vec();
+        let options = match (range_end, prefix) {
+            (Some(_), true) => {
+                return Err(InvalidArgsError::new_err(
+                    "range_end and prefix are mutually exclusive",
+                ))
+            }
+            (Some(range_end), false) => {
+                Some(RoleRevokePermissionOptions::new().with_range_end(range_end.as_bytes()))
+            }
+            (None, true) => Some(RoleRevokePermissionOptions::new().with_prefix()),
+            (None, false) => None,
+        };
+
+        let request = self.request(
+            "role_revoke_permission",
+            Some(key.clone()),
+            move |mut client| {
+                let (role, key, options) = (role.clone(), key.clone(), options.clone());
+                async move { client.role_revoke_permission(role, key, options).await }
+            },
+        );
+
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.lease_revoke(id).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            request.await.map(PyRoleRevokePermissionResponse)
         })
     }
 
-    fn lease_time_to_live<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    fn role_delete<'a>(&'a self, py: Python<'a>, name: String) -> PyResult<&'a PyAny> {
+        let request = self.request("role_delete", None, move |mut client| {
+            let name = name.clone();
+            async move { client.role_delete(name).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyRoleDeleteResponse) })
+    }
+
+    fn member_list<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.idempotent_request("member_list", None, |mut client| async move {
+            client.member_list().await
+        });
+
+        future_into_py(py, async move { request.await.map(PyMemberListResponse) })
+    }
+
+    #[pyo3(signature = (peer_urls, is_learner=false))]
+    fn member_add<'a>(
+        &'a self,
+        py: Python<'a>,
+        peer_urls: Vec<String>,
+        is_learner: bool,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.request("member_add", None, move |mut client| {
+            let peer_urls = peer_urls.clone();
+            let options = is_learner.then(|| MemberAddOptions::new().with_is_learner());
+            async move { client.member_add(peer_urls, options).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyMemberAddResponse) })
+    }
+
+    fn member_remove<'a>(&'a self, py: Python<'a>, id: u64) -> PyResult<&'a PyAny> {
+        let request = self.request("member_remove", None, move |mut client| async move {
+            client.member_remove(id).await
+        });
+
+        future_into_py(py, async move { request.await.map(PyMemberRemoveResponse) })
+    }
+
+    fn member_update<'a>(
+        &'a self,
+        py: Python<'a>,
+        id: u64,
+        peer_urls: Vec<String>,
+    ) -> PyResult<&'a PyAny> {
+        let request = self.request("member_update", None, move |mut client| {
+            let peer_urls = peer_urls.clone();
+            async move { client.member_update(id, peer_urls).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyMemberUpdateResponse) })
+    }
+
+    fn status<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request =
+            self.idempotent_request(
+                "status",
+                None,
+                |mut client| async move { client.status().await },
+            );
+
+        future_into_py(py, async move { request.await.map(PyStatusResponse) })
+    }
+
+    /// Makes a round trip to the cluster, returning how long it took in seconds.
+    /// Connecting is lazy, so this is how misconfigured endpoints fail right away.
+    fn ping<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.request(
+            "ping",
+            None,
+            |mut client| async move { client.status().await },
+        );
+
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.lease_time_to_live(id, None).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            let started_at = Instant::now();
+            request.await?;
+            Ok(started_at.elapsed().as_secs_f64())
         })
     }
 
-    fn lease_keep_alive<'a>(&'a self, py: Python<'a>, id: i64) -> PyResult<&'a PyAny> {
-        let client = self.0.clone();
+    #[pyo3(signature = (action, alarm_type=None, member_id=None))]
+    fn alarm<'a>(
+        &'a self,
+        py: Python<'a>,
+        action: PyAlarmAction,
+        alarm_type: Option<PyAlarmType>,
+        member_id: Option<u64>,
+    ) -> PyResult<&'a PyAny> {
+        let alarm_type = alarm_type.map_or(AlarmType::None, |alarm_type| alarm_type.0);
+        let request = self.request("alarm", None, move |mut client| {
+            let options = member_id.map(|member_id| {
+                let mut options = AlarmOptions::new();
+                options.with_member(member_id);
+                options
+            });
+            async move { client.alarm(action.0, alarm_type, options).await }
+        });
+
+        future_into_py(py, async move { request.await.map(PyAlarmResponse) })
+    }
+
+    fn defragment<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.request("defragment", None, |mut client| async move {
+            client.defragment().await
+        });
+
+        future_into_py(py, async move { request.await.map(PyDefragmentResponse) })
+    }
+
+    fn hash_kv<'a>(&'a self, py: Python<'a>, revision: i64) -> PyResult<&'a PyAny> {
+        let request = self.idempotent_request("hash_kv", None, move |mut client| async move {
+            client.hash_kv(revision).await
+        });
+
+        future_into_py(py, async move { request.await.map(PyHashKvResponse) })
+    }
+
+    /// Returns a dict-like view of the keys starting with `prefix`, which its keys leave out,
+    /// whose writes are checked against `schema` if given.
+    #[pyo3(signature = (prefix, *, schema=None))]
+    fn as_mapping(
+        slf: Py<Self>,
+        prefix: &PyBytes,
+        schema: Option<Py<PySchema>>,
+    ) -> PyPrefixMapping {
+        PyPrefixMapping::new(slf, prefix.as_bytes().to_vec(), schema)
+    }
+
+    /// Returns a writer queueing puts and writing them in batched transactions,
+    /// for loading many keys faster than awaiting every put.
+    #[pyo3(signature = (batch_size=128, queue_size=4096))]
+    fn writer(
+        slf: Py<Self>,
+        py: Python<'_>,
+        batch_size: usize,
+        queue_size: usize,
+    ) -> PyResult<PyPutWriter> {
+        if batch_size == 0 || queue_size == 0 {
+            return Err(InvalidArgsError::new_err(
+                "batch_size and queue_size must be at least 1",
+            ));
+        }
+        PyPutWriter::new(py, slf, batch_size, queue_size)
+    }
+
+    /// Returns a mirror of the keys under `prefix`, which reads them once entered
+    /// and keeps them up to date with a watch until it is exited.
+    fn mirror(slf: Py<Self>, prefix: &PyBytes) -> PyMirror {
+        PyMirror::new(slf, prefix.as_bytes().to_vec())
+    }
+
+    /// Returns a watcher of the keys under `prefix`, which calls the callbacks registered
+    /// for them with their old and new values once entered, coalescing the changes made
+    /// within `debounce` seconds of each other.
+    #[pyo3(signature = (prefix, debounce=0.0))]
+    fn config_watcher(slf: Py<Self>, prefix: &PyBytes, debounce: f64) -> PyResult<PyConfigWatcher> {
+        if !(debounce >= 0.0 && debounce.is_finite()) {
+            return Err(InvalidArgsError::new_err(
+                "debounce must be a non-negative number of seconds",
+            ));
+        }
+        let prefix = prefix.as_bytes().to_vec();
+        Ok(PyConfigWatcher::new(slf, prefix, debounce))
+    }
+
+    /// Returns a registry of service instances under `prefix`, `b"services/"` by default,
+    /// whose keys are attached to leases kept alive until they are deregistered.
+    #[pyo3(signature = (prefix=None))]
+    fn service_registry(slf: Py<Self>, py: Python, prefix: Option<&PyBytes>) -> PyServiceRegistry {
+        let prefix = prefix.unwrap_or_else(|| PyBytes::new(py, b"services/"));
+        PyServiceRegistry::new(slf, prefix.into())
+    }
+
+    /// Returns a presence of `member_id` in `group`, whose key is attached to a lease of `ttl`
+    /// seconds kept alive while it is entered, with `value` encoded like that of `put()`.
+    #[pyo3(signature = (group, member_id, ttl=10, value=None))]
+    fn presence(
+        slf: Py<Self>,
+        py: Python,
+        group: &PyBytes,
+        member_id: &PyBytes,
+        ttl: i64,
+        value: Option<&PyAny>,
+    ) -> PyResult<PyPresence> {
+        let value = match value {
+            Some(value) => encode_value(py, slf.borrow(py).connect_options.codec.as_ref(), value)?,
+            None => vec![],
+        };
+        PyPresence::new(slf, group, member_id, value, ttl)
+    }
+
+    /// Returns a view of the members present in `group`, kept up to date while entered.
+    fn members(slf: Py<Self>, group: &PyBytes) -> PyResult<PyMembers> {
+        PyMembers::new(slf, group)
+    }
+
+    /// Returns a changefeed delivering the changes under `prefix` to the file at `path`
+    /// or to `sink`, resuming after the revision recorded in the file at `checkpoint`.
+    #[pyo3(signature = (prefix, checkpoint, path=None, sink=None))]
+    fn changefeed(
+        slf: Py<Self>,
+        prefix: &PyBytes,
+        checkpoint: String,
+        path: Option<String>,
+        sink: Option<PyObject>,
+    ) -> PyResult<PyChangefeed> {
+        let prefix = prefix.as_bytes().to_vec();
+        PyChangefeed::new(slf, prefix, checkpoint, path, sink)
+    }
+
+    /// Injects a fault into the requests for `operation`, or for all operations if unset,
+    /// on the keys matching the pattern `key`, where `*` matches any run of bytes and `?` any
+    /// single byte, or on any key if unset. Every `every`-th matching request is delayed by
+    /// `latency` seconds, then fails with the status `error` if set, up to `times` requests.
+    #[pyo3(signature = (operation=None, key=None, *, error=None, latency=0.0, every=1, times=None))]
+    fn inject_fault(
+        &self,
+        operation: Option<String>,
+        key: Option<&PyBytes>,
+        error: Option<PyGRPCStatusCode>,
+        latency: f64,
+        every: u64,
+        times: Option<u64>,
+    ) -> PyResult<PyFault> {
+        self.faults
+            .add(operation, key, error, latency, every, times)
+    }
+
+    /// Removes every fault injected into the requests of the communicator and its views.
+    fn clear_faults(&self) {
+        self.faults.clear();
+    }
+
+    /// Returns a communicator forwarding every call to this one, which records the requests
+    /// and their responses to the file at `path` once exited, for `ReplayCommunicator`.
+    fn record(slf: Py<Self>, path: String) -> PyRecordingCommunicator {
+        PyRecordingCommunicator::new(slf, path)
+    }
+
+    fn snapshot(&self) -> PyResult<PySnapshot> {
+        self.check_process()?;
+        Ok(PySnapshot::new(
+            self.client.clone(),
+            self.closed.subscribe(),
+        ))
+    }
+
+    #[pyo3(signature = (path, progress=None))]
+    fn save_snapshot<'a>(
+        &'a self,
+        py: Python<'a>,
+        path: String,
+        progress: Option<PyObject>,
+    ) -> PyResult<&'a PyAny> {
+        self.check_process()?;
+        let client = self.client.clone();
+
         future_into_py(py, async move {
-            let mut client = client.lock().await;
-            let result = client.lease_keep_alive(id).await;
-            result.map(|_| ()).map_err(|e| PyClientError(e).into())
+            let mut client = connected(&client).await?;
+            let mut stream = client.snapshot().await.map_err(PyClientError)?;
+            let mut file = File::create(&path).await.map_err(io_error)?;
+
+            let mut received_bytes: u64 = 0;
+            while let Some(response) = stream.message().await.map_err(PyClientError)? {
+                file.write_all(response.blob()).await.map_err(io_error)?;
+                received_bytes += response.blob().len() as u64;
+
+                if let Some(ref progress) = progress {
+                    Python::with_gil(|py| {
+                        progress.call1(py, (received_bytes, response.remaining_bytes()))
+                    })?;
+                }
+            }
+
+            file.sync_all().await.map_err(io_error)?;
+            Ok(received_bytes)
         })
     }
 
+    fn observe(&self, name: &PyBytes) -> PyResult<PyObserve> {
+        self.check_process()?;
+        let client = self.client.clone();
+        let name = name.as_bytes().to_vec();
+        Ok(PyObserve::new(client, self.closed.subscribe(), name))
+    }
+
+    /// Watches `key`, or the keys starting with it with `prefix`.
+    /// Events whose type is in `filters` are not sent.
+    #[pyo3(signature = (
+        key,
+        once=None,
+        ready_event=None,
+        cleanup_event=None,
+        *,
+        prefix=false,
+        prev_kv=false,
+        start_revision=None,
+        filters=None,
+        progress_notify=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
     fn watch(
         &self,
         key: &PyBytes,
         once: Option<bool>,
-        ready_event: Option<PyCondVar>,
+        ready_event: Option<ReadyEvent>,
         cleanup_event: Option<PyCondVar>,
-    ) -> PyWatch {
-        let client = self.0.clone();
+        prefix: bool,
+        prev_kv: bool,
+        start_revision: Option<i64>,
+        filters: Option<Vec<PyWatchEventType>>,
+        progress_notify: bool,
+    ) -> PyResult<PyWatch> {
+        self.check_process()?;
+        let client = self.client.clone();
         let key = key.as_bytes().to_vec();
         let once = once.unwrap_or(false);
-        PyWatch::new(client, key, once, None, ready_event, cleanup_event)
+        let mut options = WatchOptions::new();
+        if prefix {
+            options = options.with_prefix();
+        }
+        if prev_kv {
+            options = options.with_prev_key();
+        }
+        if let Some(start_revision) = start_revision {
+            options = options.with_start_revision(start_revision);
+        }
+        if let Some(filters) = filters {
+            let filters: Vec<_> = filters
+                .into_iter()
+                .map(|event_type| match event_type.0 {
+                    EventType::Put => WatchFilterType::NoPut,
+                    EventType::Delete => WatchFilterType::NoDelete,
+                })
+                .collect();
+            options = options.with_filters(filters);
+        }
+        if progress_notify {
+            options = options.with_progress_notify();
+        }
+        let closed = self.closed.subscribe();
+        Ok(PyWatch::new(
+            client,
+            closed,
+            key,
+            once,
+            Some(options),
+            ready_event,
+            cleanup_event,
+        )
+        .with_decoding(
+            self.connect_options.text_decoding,
+            self.connect_options.codec.clone(),
+        )
+        .with_tracer(self.connect_options.tracer.clone()))
     }
 
+    /// Watches the keys starting with `key`, like `watch()` with `prefix`.
     fn watch_prefix(
         &self,
         key: &PyBytes,
         once: Option<bool>,
-        ready_event: Option<PyCondVar>,
+        ready_event: Option<ReadyEvent>,
         cleanup_event: Option<PyCondVar>,
-    ) -> PyWatch {
-        let client = self.0.clone();
-        let key = key.as_bytes().to_vec();
-        let once = once.unwrap_or(false);
-        let options = WatchOptions::new().with_prefix();
-        PyWatch::new(client, key, once, Some(options), ready_event, cleanup_event)
+    ) -> PyResult<PyWatch> {
+        self.watch(
+            key,
+            once,
+            ready_event,
+            cleanup_event,
+            true,
+            false,
+            None,
+            None,
+            false,
+        )
+    }
+
+    /// Returns the latency statistics of the requests made by the communicator and its views,
+    /// by operation: the `count` of requests made and the `p50`, `p95` and `p99` latencies
+    /// of the latest 1024 of them, in seconds.
+    fn stats<'a>(&self, py: Python<'a>) -> PyResult<&'a PyDict> {
+        self.stats.lock().unwrap().to_dict(py)
+    }
+
+    /// The state of the connection, as last seen by the requests and health checks.
+    #[getter]
+    fn connection_state(&self) -> ConnectionState {
+        *self.state.borrow()
+    }
+
+    /// Returns an async iterator over the state of the connection and its changes.
+    fn connection_state_changes(&self) -> PyConnectionStateChanges {
+        PyConnectionStateChanges::new(self.state.subscribe(), self.closed.subscribe())
+    }
+
+    /// Whether the latest background health check succeeded,
+    /// or `None` if the connection is not checked in the background.
+    #[getter]
+    fn connected(&self) -> Option<bool> {
+        self.healthy.as_ref().map(|healthy| *healthy.borrow())
+    }
+
+    /// Replaces the client with a new one connected to the same endpoints with the same options,
+    /// e.g. after a long network partition. Watches and other streams keep their connection.
+    fn reconnect<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        self.check_process()?;
+        let client = self.client.clone();
+        let generation = self.generation.clone();
+        let next = self.next.clone();
+        let endpoints = self.endpoints.lock().unwrap().clone();
+        let connect_options = self.connect_options.clone();
+        let state = self.state.clone();
+
+        future_into_py(py, async move {
+            let connect = || connect_options.connect_channels(&endpoints, connect_options.channels);
+            let used = generation.load(Ordering::SeqCst);
+            replace(&client, &generation, &next, &state, used, connect).await?;
+            Ok(())
+        })
+    }
+
+    /// Returns a view of the communicator which shares its connection, watches and closing,
+    /// but sends requests with the timeout, serializable reads, retry policy, auth retry limit
+    /// and text decoding of `options`. Its other options, which concern the connection,
+    /// are ignored. The timeout covers requests with all their retries,
+    /// and cannot extend the timeout the connection was made with.
+    fn with_options(&self, options: PyConnectOptions) -> Self {
+        let mut connect_options = self.connect_options.clone();
+        connect_options.serializable_reads = options.serializable_reads;
+        connect_options.reconnect = options.reconnect;
+        connect_options.auth_retry_limit = options.auth_retry_limit;
+        connect_options.text_decoding = options.text_decoding;
+        connect_options.codec = options.codec;
+
+        let mut view = self.view(connect_options);
+        view.timeout = options.timeout;
+        view
+    }
+
+    /// Returns a view of the communicator like `with_options()`,
+    /// which only changes the timeout of its requests to `timeout` seconds.
+    fn with_timeout(&self, timeout: f64) -> PyResult<Self> {
+        if !(timeout > 0.0 && timeout.is_finite()) {
+            return Err(InvalidArgsError::new_err(
+                "timeout must be a positive number of seconds",
+            ));
+        }
+
+        let mut view = self.view(self.connect_options.clone());
+        view.timeout = Some(timeout);
+        Ok(view)
+    }
+
+    /// Returns a view of the communicator like `with_options()`, whose changes are passed
+    /// to the audit callback of `ConnectOptions.with_audit()` along with `context`.
+    fn with_audit_context(&self, context: PyObject) -> Self {
+        let mut view = self.view(self.connect_options.clone());
+        view.audit_context = Some(context);
+        view
+    }
+
+    /// Stops the watches, observations and snapshots of this communicator and drops
+    /// its connection once the requests in flight are done.
+    /// Any request made afterwards fails with `ClientError`.
+    fn close<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let client = self.client.clone();
+        let forked = self.check_process().is_err();
+        self.closed.send_replace(true);
+
+        future_into_py(py, async move {
+            // The connections of a communicator created before forking are left to the parent.
+            if !forked {
+                client.lock().await.clear();
+            }
+            Ok(())
+        })
     }
 }
 
 impl PyCommunicator {
-    pub fn new(client: EtcdClient) -> PyCommunicator {
-        PyCommunicator(Arc::new(Mutex::new(client)))
+    pub fn new(
+        clients: Vec<EtcdClient>,
+        endpoints: Vec<String>,
+        connect_options: PyConnectOptions,
+    ) -> PyCommunicator {
+        let circuit_breaker = connect_options
+            .circuit_breaker
+            .map(|policy| Arc::new(std::sync::Mutex::new(CircuitBreaker::new(policy))));
+        let (endpoints, failover_endpoints) = match connect_options.load_balancing {
+            LoadBalancing::RoundRobin => (endpoints, vec![]),
+            LoadBalancing::Pinned => (endpoints[..1].to_vec(), endpoints),
+        };
+        PyCommunicator {
+            client: Arc::new(Mutex::new(clients)),
+            next: Arc::new(AtomicUsize::new(0)),
+            generation: Arc::new(AtomicU64::new(0)),
+            endpoints: Arc::new(std::sync::Mutex::new(endpoints)),
+            failover_endpoints,
+            connect_options,
+            closed: Arc::new(watch::channel(false).0),
+            healthy: None,
+            state: Arc::new(watch::channel(ConnectionState::Connecting).0),
+            circuit_breaker,
+            stats: Arc::default(),
+            timeout: None,
+            audit_context: None,
+            faults: Faults::default(),
+            pid: std::process::id(),
+        }
+    }
+
+    fn view(&self, connect_options: PyConnectOptions) -> Self {
+        PyCommunicator {
+            client: self.client.clone(),
+            next: self.next.clone(),
+            generation: self.generation.clone(),
+            endpoints: self.endpoints.clone(),
+            failover_endpoints: self.failover_endpoints.clone(),
+            connect_options,
+            closed: self.closed.clone(),
+            healthy: self.healthy.clone(),
+            state: self.state.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            stats: self.stats.clone(),
+            timeout: self.timeout,
+            audit_context: self.audit_context.clone(),
+            faults: self.faults.clone(),
+            pid: self.pid,
+        }
+    }
+
+    /// Raises `ClientError` in a process forked after the communicator was created, as its
+    /// connections belong to the runtime of the parent, whose threads were not forked.
+    pub fn check_process(&self) -> PyResult<()> {
+        if self.pid != std::process::id() {
+            return Err(ClientError::new_err(
+                "the communicator was created before os.fork(), \
+                 connect again in the forked process instead",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Runs `request` with the shared client.
+    ///
+    /// etcd-client only authenticates when connecting, so once the auth token expires
+    /// every request fails until the client is recreated. Such failures are retried
+    /// up to `auth_retry_limit` times, each after reconnecting with the stored credentials.
+    pub fn request<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        key: Option<Vec<u8>>,
+        request: F,
+    ) -> impl Future<Output = PyResult<T>>
+    where
+        F: FnMut(EtcdClient) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, etcd_client::Error>> + Send,
+        T: Send + 'static,
+    {
+        self.send(false, operation, key, request)
+    }
+
+    /// Runs `request` like `request()`, and also retries it after reconnecting
+    /// when it failed on the transport, as it can safely be run more than once.
+    pub fn idempotent_request<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        key: Option<Vec<u8>>,
+        request: F,
+    ) -> impl Future<Output = PyResult<T>>
+    where
+        F: FnMut(EtcdClient) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, etcd_client::Error>> + Send,
+        T: Send + 'static,
+    {
+        self.send(true, operation, key, request)
+    }
+
+    /// Runs `request` for `operation` on `key`, which the errors it raises tell.
+    fn send<T, F, Fut>(
+        &self,
+        idempotent: bool,
+        operation: &'static str,
+        key: Option<Vec<u8>>,
+        mut request: F,
+    ) -> impl Future<Output = PyResult<T>>
+    where
+        F: FnMut(EtcdClient) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, etcd_client::Error>> + Send,
+        T: Send + 'static,
+    {
+        let forked = self.check_process();
+        let client = self.client.clone();
+        let generation = self.generation.clone();
+        let next = self.next.clone();
+        let endpoints = self.endpoints.clone();
+        let failover_endpoints = self.failover_endpoints.clone();
+        let connect_options = self.connect_options.clone();
+        let circuit_breaker = self.circuit_breaker.clone();
+        let state = self.state.clone();
+        let faults = self.faults.clone();
+        let fault_key = key.clone();
+
+        let send = async move {
+            forked?;
+            if let Some(ref circuit_breaker) = circuit_breaker {
+                circuit_breaker.lock().unwrap().acquire()?;
+            }
+            let connect = |endpoints: Vec<String>| {
+                let connect_options = &connect_options;
+                async move {
+                    connect_options
+                        .connect_channels(&endpoints, connect_options.channels)
+                        .await
+                }
+            };
+            let reconnect = connect_options.reconnect;
+
+            // Every request runs on its own handle of the client, so that requests run
+            // concurrently. The shared client is only locked to be replaced.
+            let (mut current, mut used) = checkout(&client, &generation, &next).await?;
+            let (mut auth_retries, mut reconnects, mut failovers) = (0, 0, 0);
+            loop {
+                // Injected faults go through the handling of actual errors, retries included.
+                let result = match faults.inject(operation, fault_key.as_deref()).await {
+                    Ok(()) => request(current.clone()).await,
+                    Err(e) => Err(e),
+                };
+                let transport_failed = matches!(result, Err(ref e) if is_transport_error(e));
+                if let Some(ref circuit_breaker) = circuit_breaker {
+                    circuit_breaker.lock().unwrap().record(transport_failed);
+                }
+                if transport_failed {
+                    set_state(&state, ConnectionState::TransientFailure);
+                } else {
+                    set_state(&state, ConnectionState::Ready);
+                }
+                let error = match result {
+                    Ok(response) => return Ok(response),
+                    Err(e) => e,
+                };
+
+                let current_endpoints = endpoints.lock().unwrap().clone();
+                if is_invalid_auth_token(&error) && auth_retries < connect_options.auth_retry_limit
+                {
+                    auth_retries += 1;
+                    log::debug!("reconnecting after the auth token was rejected: {error}");
+                    let reauthenticate = || connect(current_endpoints);
+                    (current, used) =
+                        replace(&client, &generation, &next, &state, used, reauthenticate).await?;
+                } else if is_transport_error(&error) && failovers + 1 < failover_endpoints.len() {
+                    // Moves on to the endpoint after the failed one, which is then kept.
+                    failovers += 1;
+                    let position = failover_endpoints
+                        .iter()
+                        .position(|endpoint| current_endpoints.contains(endpoint))
+                        .unwrap_or(0);
+                    let next_endpoint =
+                        &failover_endpoints[(position + 1) % failover_endpoints.len()];
+                    log::warn!("failing over to {next_endpoint} after a transport error: {error}");
+                    let fail_over = || {
+                        *endpoints.lock().unwrap() = vec![next_endpoint.clone()];
+                        connect(vec![next_endpoint.clone()])
+                    };
+                    if let Ok(replaced) =
+                        replace(&client, &generation, &next, &state, used, fail_over).await
+                    {
+                        (current, used) = replaced;
+                    } else {
+                        log::warn!("failed to connect to {next_endpoint}");
+                    }
+                    if !idempotent {
+                        return Err(PyClientError(error).into_pyerr(idempotent));
+                    }
+                } else if is_transport_error(&error) && reconnects < reconnect.max_attempts {
+                    // Others are not kept waiting, as they fail anyway.
+                    if idempotent {
+                        sleep(reconnect.backoff(reconnects)).await;
+                    }
+                    reconnects += 1;
+                    log::warn!(
+                        "reconnecting after a transport error (attempt {reconnects}/{}): {error}",
+                        reconnect.max_attempts
+                    );
+                    // A failed attempt leaves the client as is, for the next attempt to replace.
+                    let connect_again = || connect(current_endpoints);
+                    if let Ok(replaced) =
+                        replace(&client, &generation, &next, &state, used, connect_again).await
+                    {
+                        (current, used) = replaced;
+                    } else {
+                        log::warn!("failed to reconnect");
+                    }
+                    if !idempotent {
+                        return Err(PyClientError(error).into_pyerr(idempotent));
+                    }
+                } else {
+                    return Err(PyClientError(error).into_pyerr(idempotent));
+                }
+            }
+        };
+
+        let span = RequestSpan::start(
+            self.connect_options.tracer.as_ref(),
+            operation,
+            key.as_deref(),
+        );
+        let hooks = self.connect_options.hooks.clone();
+        hooks.before(operation, key.as_deref());
+        let slow_threshold = self.connect_options.slow_threshold;
+        let debug_logging = self.connect_options.debug_logging;
+        let serializable_reads = self.connect_options.serializable_reads;
+        let endpoints = self.endpoints.clone();
+        let stats = self.stats.clone();
+        let timeout = self.timeout;
+        let audit = self.connect_options.audit.clone();
+        let audit_context = self.audit_context.clone();
+        async move {
+            let started = Instant::now();
+            let result = match timeout {
+                Some(timeout) => {
+                    let duration = Duration::from_secs_f64(timeout);
+                    match tokio::time::timeout(duration, send).await {
+                        Ok(result) => result,
+                        Err(_) => Err(timeout_error(timeout, idempotent)),
+                    }
+                }
+                None => send.await,
+            };
+            let result = result.map_err(|error| {
+                // Requests balanced over several endpoints cannot tell which one failed.
+                let endpoint = match endpoints.lock().unwrap().as_slice() {
+                    [endpoint] => Some(endpoint.clone()),
+                    _ => None,
+                };
+                add_context(error, operation, key.as_deref(), endpoint.as_deref())
+            });
+            let elapsed = started.elapsed();
+            let status = metrics::status(result.as_ref().err());
+            if slow_threshold.is_some_and(|threshold| elapsed.as_secs_f64() >= threshold) {
+                log::warn!(
+                    "slow {operation} of \"{}\": took {:.3}s ({status})",
+                    sanitize_key(key.as_deref()),
+                    elapsed.as_secs_f64()
+                );
+            }
+            if debug_logging {
+                let size = result
+                    .as_ref()
+                    .ok()
+                    .and_then(|response| response_size(response));
+                log::debug!(
+                    "{operation} of \"{}\" (idempotent={idempotent}, timeout={timeout:?}, \
+                     serializable_reads={serializable_reads}): {status}, {} in {:.3}s",
+                    sanitize_key(key.as_deref()),
+                    size.map_or_else(|| "no items".to_owned(), |size| format!("{size} items")),
+                    elapsed.as_secs_f64()
+                );
+            }
+            hooks.after(operation, key.as_deref(), elapsed, &status);
+            if let (Some(audit), Ok(response)) = (&audit, &result) {
+                let context = audit_context.as_ref();
+                audit::audit(audit, context, operation, key.as_deref(), response);
+            }
+            stats.lock().unwrap().record(operation, elapsed);
+            metrics::record_request(operation, elapsed, status);
+            span.end(result.as_ref().err());
+            result
+        }
+    }
+}
+
+fn closed_error() -> PyErr {
+    ClientError::new_err("the communicator is closed")
+}
+
+fn timeout_error(timeout: f64, idempotent: bool) -> PyErr {
+    let error = RequestTimeoutError::new_err(format!(
+        "RequestTimeoutError(err=the request took longer than {timeout}s)"
+    ));
+    Python::with_gil(|py| {
+        let _ = error.value(py).setattr("retryable", idempotent);
+    });
+    error
+}
+
+/// Returns a handle of the next of the shared clients in turn.
+fn pick(clients: &[EtcdClient], next: &AtomicUsize) -> PyResult<EtcdClient> {
+    if clients.is_empty() {
+        return Err(closed_error());
+    }
+    Ok(clients[next.fetch_add(1, Ordering::Relaxed) % clients.len()].clone())
+}
+
+/// Returns a handle of the next of the shared clients along with their generation.
+async fn checkout(
+    client: &Mutex<Vec<EtcdClient>>,
+    generation: &AtomicU64,
+    next: &AtomicUsize,
+) -> PyResult<(EtcdClient, u64)> {
+    let clients = client.lock().await;
+    Ok((pick(&clients, next)?, generation.load(Ordering::SeqCst)))
+}
+
+/// Replaces the shared clients with the ones made by `connect`, unless the clients
+/// of generation `used` have already been replaced by a concurrent request,
+/// and returns a handle of the next of the shared clients along with their generation.
+/// The connection is `CONNECTING` meanwhile, and in `TRANSIENT_FAILURE` if it failed.
+async fn replace<C, Fut>(
+    client: &Mutex<Vec<EtcdClient>>,
+    generation: &AtomicU64,
+    next: &AtomicUsize,
+    state: &watch::Sender<ConnectionState>,
+    used: u64,
+    connect: C,
+) -> PyResult<(EtcdClient, u64)>
+where
+    C: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Vec<EtcdClient>, etcd_client::Error>>,
+{
+    let mut clients = client.lock().await;
+    if clients.is_empty() {
+        return Err(closed_error());
+    }
+    if generation.load(Ordering::SeqCst) == used {
+        set_state(state, ConnectionState::Connecting);
+        *clients = connect().await.map_err(|error| {
+            set_state(state, ConnectionState::TransientFailure);
+            PyClientError(error)
+        })?;
+        generation.fetch_add(1, Ordering::SeqCst);
+        metrics::record_reconnect();
+    }
+    Ok((pick(&clients, next)?, generation.load(Ordering::SeqCst)))
+}
+
+/// Returns a handle of the first of the shared clients for a stream,
+/// failing once the communicator is closed.
+pub async fn connected(client: &Mutex<Vec<EtcdClient>>) -> PyResult<EtcdClient> {
+    client
+        .lock()
+        .await
+        .first()
+        .cloned()
+        .ok_or_else(closed_error)
+}
+
+/// Resolves once the communicator is closed or the interpreter exits. A communicator dropped
+/// without being closed leaves its streams running until the interpreter exits.
+pub async fn wait_closed(mut closed: watch::Receiver<bool>) {
+    tokio::select! {
+        Ok(_) = closed.wait_for(|closed| *closed) => {}
+        _ = wait_shutdown() => {}
+    }
+}
+
+fn is_invalid_auth_token(error: &etcd_client::Error) -> bool {
+    match error {
+        etcd_client::Error::GRpcStatus(status) => {
+            status.code() == tonic::Code::Unauthenticated
+                && status.message().contains("invalid auth token")
+        }
+        _ => false,
     }
 }
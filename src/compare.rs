@@ -1,84 +1,100 @@
 use etcd_client::{Compare, CompareOp};
 use pyo3::prelude::*;
-use pyo3::pyclass::CompareOp as PyO3CompareOp;
 use pyo3::types::PyBytes;
 
+use crate::py_enum::PyEnum;
+use crate::recipe::Recipe;
+
+pub static COMPARE_OP: PyEnum = PyEnum::new(
+    "IntEnum",
+    "CompareOp",
+    &[("EQUAL", 0), ("GREATER", 1), ("LESS", 2), ("NOT_EQUAL", 3)],
+);
+
+/// Converts to and from the members of `CompareOp`, an `enum.IntEnum` whose values
+/// are those of etcd's protocol.
 #[derive(Clone)]
-#[pyclass(name = "CompareOp")]
 pub struct PyCompareOp(CompareOp);
 
-#[pymethods]
-impl PyCompareOp {
-    #[classattr]
-    const EQUAL: Self = Self(CompareOp::Equal);
-    #[classattr]
-    const GREATER: Self = Self(CompareOp::Greater);
-    #[classattr]
-    const LESS: Self = Self(CompareOp::Less);
-    #[classattr]
-    const NOT_EQUAL: Self = Self(CompareOp::NotEqual);
-
-    pub fn __repr__(&self) -> String {
-        match self.0 {
-            CompareOp::Equal => "CompareOp.EQUAL".to_owned(),
-            CompareOp::Greater => "CompareOp.GREATER".to_owned(),
-            CompareOp::Less => "CompareOp.LESS".to_owned(),
-            CompareOp::NotEqual => "CompareOp.NOT_EQUAL".to_owned(),
-        }
+impl IntoPy<PyObject> for PyCompareOp {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        COMPARE_OP
+            .member(py, self.0 as i32)
+            .expect("CompareOp has a member for every operator")
     }
+}
 
-    pub fn __richcmp__(&self, py: Python, rhs: &PyCompareOp, op: PyO3CompareOp) -> PyObject {
-        match op {
-            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
-            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
-            _ => py.NotImplemented(),
+impl<'a> FromPyObject<'a> for PyCompareOp {
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        match COMPARE_OP.value(ob)? {
+            0 => Ok(Self(CompareOp::Equal)),
+            1 => Ok(Self(CompareOp::Greater)),
+            2 => Ok(Self(CompareOp::Less)),
+            _ => Ok(Self(CompareOp::NotEqual)),
         }
     }
 }
 
 #[derive(Clone)]
 #[pyclass(name = "Compare")]
-pub struct PyCompare(pub Compare);
+pub struct PyCompare(pub Compare, Recipe);
 
 #[pymethods]
 impl PyCompare {
     #[staticmethod]
     fn version(key: &PyBytes, cmp: PyCompareOp, version: i64) -> PyResult<Self> {
+        let recipe = Recipe::new("version", (key, cmp.clone(), version));
         let key = key.as_bytes().to_vec();
-        Ok(PyCompare(Compare::version(key, cmp.0, version)))
+        Ok(PyCompare(Compare::version(key, cmp.0, version), recipe))
     }
 
     #[staticmethod]
     fn create_revision(key: &PyBytes, cmp: PyCompareOp, revision: i64) -> PyResult<Self> {
+        let recipe = Recipe::new("create_revision", (key, cmp.clone(), revision));
         let key = key.as_bytes().to_vec();
-        Ok(PyCompare(Compare::create_revision(key, cmp.0, revision)))
+        Ok(PyCompare(
+            Compare::create_revision(key, cmp.0, revision),
+            recipe,
+        ))
     }
 
     #[staticmethod]
     fn mod_revision(key: &PyBytes, cmp: PyCompareOp, revision: i64) -> PyResult<Self> {
+        let recipe = Recipe::new("mod_revision", (key, cmp.clone(), revision));
         let key = key.as_bytes().to_vec();
-        Ok(PyCompare(Compare::mod_revision(key, cmp.0, revision)))
+        Ok(PyCompare(
+            Compare::mod_revision(key, cmp.0, revision),
+            recipe,
+        ))
     }
 
     #[staticmethod]
     fn value(key: &PyBytes, cmp: PyCompareOp, value: &PyBytes) -> PyResult<Self> {
+        let recipe = Recipe::new("value", (key, cmp.clone(), value));
         let key = key.as_bytes().to_vec();
         let value = value.as_bytes().to_vec();
-        Ok(PyCompare(Compare::value(key, cmp.0, value)))
+        Ok(PyCompare(Compare::value(key, cmp.0, value), recipe))
     }
 
     #[staticmethod]
     fn lease(key: &PyBytes, cmp: PyCompareOp, lease: i64) -> PyResult<Self> {
+        let recipe = Recipe::new("lease", (key, cmp.clone(), lease));
         let key = key.as_bytes().to_vec();
-        Ok(PyCompare(Compare::lease(key, cmp.0, lease)))
+        Ok(PyCompare(Compare::lease(key, cmp.0, lease), recipe))
     }
 
     fn with_range(&self, end: &PyBytes) -> PyResult<Self> {
+        let recipe = self.1.then("with_range", (end,));
         let end = end.as_bytes().to_vec();
-        Ok(PyCompare(self.0.clone().with_range(end)))
+        Ok(PyCompare(self.0.clone().with_range(end), recipe))
     }
 
     fn with_prefix(&self) -> PyResult<Self> {
-        Ok(PyCompare(self.0.clone().with_prefix()))
+        let recipe = self.1.then("with_prefix", ());
+        Ok(PyCompare(self.0.clone().with_prefix(), recipe))
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        self.1.reduce(py, py.get_type::<Self>())
     }
 }
@@ -1,8 +1,9 @@
 use pyo3::{pyclass, *};
-use pyo3_asyncio::tokio::future_into_py;
 use std::sync::Arc;
 use tokio::sync::{Mutex, Notify};
 
+use crate::runtime::future_into_py;
+
 #[pyclass(name = "CondVar")]
 #[derive(Clone)]
 pub struct PyCondVar {
@@ -13,7 +14,7 @@ pub struct PyCondVar {
 #[pymethods]
 impl PyCondVar {
     #[new]
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             inner: Arc::new(Notify::new()),
             condition: Arc::new(Mutex::new(false)),
@@ -31,6 +32,34 @@ impl PyCondVar {
         })
     }
 
+    /// Waits until `predicate` returns a true value, which it then returns, like
+    /// `asyncio.Condition.wait_for()`. `predicate` is called under the internal lock
+    /// right away and again on each notification.
+    pub fn wait_for<'a>(&'a self, py: Python<'a>, predicate: PyObject) -> PyResult<&'a PyAny> {
+        let inner = self.inner.clone();
+        let condition = self.condition.clone();
+        future_into_py(py, async move {
+            loop {
+                // Registered before calling the predicate, so that a notification
+                // sent right after it was called is not missed.
+                let notified = inner.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let guard = condition.lock().await;
+                let result = Python::with_gil(|py| -> PyResult<Option<PyObject>> {
+                    let result = predicate.call0(py)?;
+                    Ok(result.as_ref(py).is_true()?.then_some(result))
+                })?;
+                if let Some(result) = result {
+                    return Ok(result);
+                }
+                drop(guard);
+                notified.await;
+            }
+        })
+    }
+
     pub fn notify_waiters<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
         let inner = self.inner.clone();
         let condition = self.condition.clone();
@@ -43,6 +72,21 @@ impl PyCondVar {
 }
 
 impl PyCondVar {
+    /// Waits until notified like `wait()`, from Rust.
+    pub async fn _wait(&self) {
+        loop {
+            // Registered before checking the condition, so that a notification
+            // sent right after it was checked is not missed.
+            let notified = self.inner.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if *self.condition.lock().await {
+                return;
+            }
+            notified.await;
+        }
+    }
+
     pub async fn _notify_waiters(&self) {
         let inner = self.inner.clone();
         let condition = self.condition.clone();
@@ -0,0 +1,199 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::communicator::{wait_closed, PyCommunicator};
+use crate::mirror::{Change, Mirroring};
+use crate::runtime::future_into_py;
+use crate::telemetry::sanitize_key;
+
+/// Whether `key` matches `pattern`, where `*` matches any run of bytes, including `/`,
+/// and `?` any single byte.
+pub fn matches(pattern: &[u8], key: &[u8]) -> bool {
+    let (mut p, mut k) = (0, 0);
+    // Where the latest `*` is in the pattern, and from where in the key it matches so far.
+    let mut star = None;
+    while k < key.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == key[k]) {
+            p += 1;
+            k += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, k));
+            p += 1;
+        } else if let Some((star_p, star_k)) = star {
+            // Makes the latest `*` match one more byte.
+            star = Some((star_p, star_k + 1));
+            p = star_p + 1;
+            k = star_k + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+/// A callback registered for the keys matching `pattern`.
+struct Registration {
+    pattern: Vec<u8>,
+    callback: PyObject,
+    decoder: Option<PyObject>,
+}
+
+impl Registration {
+    /// Calls the callback with `key` and its `old` and `new` values, passed through the decoder
+    /// if any, logging the errors raised by either.
+    fn call(&self, py: Python, key: &[u8], old: Option<&PyObject>, new: Option<&PyObject>) {
+        let decode = |value: Option<&PyObject>| match (value, &self.decoder) {
+            (Some(value), Some(decoder)) => decoder.call1(py, (value,)),
+            (Some(value), None) => Ok(value.clone_ref(py)),
+            (None, _) => Ok(py.None()),
+        };
+        let result = (|| -> PyResult<()> {
+            let args = (PyBytes::new(py, key), decode(old)?, decode(new)?);
+            self.callback.call1(py, args)?;
+            Ok(())
+        })();
+        if let Err(e) = result {
+            let key = sanitize_key(Some(key));
+            log::warn!("the reload callback failed for \"{key}\": {e}");
+        }
+    }
+}
+
+type Registrations = Arc<Mutex<Vec<Arc<Registration>>>>;
+
+/// Calls the callbacks registered for the keys of `changes`.
+fn dispatch(py: Python, registrations: &Registrations, changes: Vec<Change>) {
+    let registrations = registrations.lock().unwrap().clone();
+    for (key, old, new) in changes {
+        for registration in registrations.iter() {
+            if matches(&registration.pattern, &key) {
+                registration.call(py, &key, old.as_ref(), new.as_ref());
+            }
+        }
+    }
+}
+
+/// Dispatches the changes received as they come, or those received within `debounce`
+/// of each other at once, with each key changed from its first old value to its last
+/// new value, and left out if it ends up unchanged.
+async fn deliver(
+    mut changes: mpsc::UnboundedReceiver<Change>,
+    debounce: Duration,
+    registrations: Registrations,
+) {
+    while let Some(change) = changes.recv().await {
+        let mut pending = vec![change];
+        if !debounce.is_zero() {
+            while let Ok(Some((key, old, new))) = timeout(debounce, changes.recv()).await {
+                match pending
+                    .iter_mut()
+                    .find(|(pending_key, _, _)| *pending_key == key)
+                {
+                    Some(change) => change.2 = new,
+                    None => pending.push((key, old, new)),
+                }
+            }
+        }
+
+        Python::with_gil(|py| {
+            pending.retain(|(_, old, new)| match (old, new) {
+                (Some(old), Some(new)) => !old.as_ref(py).eq(new).unwrap_or(false),
+                (None, None) => false,
+                _ => true,
+            });
+            dispatch(py, &registrations, pending);
+        });
+    }
+}
+
+/// Calls the callbacks registered for keys under a prefix with their old and new values
+/// as they change, reading them from a mirror of the prefix.
+#[pyclass(name = "ConfigWatcher")]
+pub struct PyConfigWatcher {
+    mirroring: Mirroring,
+    debounce: Duration,
+    registrations: Registrations,
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl PyConfigWatcher {
+    pub fn new(communicator: Py<PyCommunicator>, prefix: Vec<u8>, debounce: f64) -> Self {
+        Self {
+            mirroring: Mirroring::new(communicator, prefix),
+            debounce: Duration::from_secs_f64(debounce),
+            registrations: Arc::default(),
+            tasks: Arc::default(),
+        }
+    }
+
+    fn abort_tasks(&self) {
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+#[pymethods]
+impl PyConfigWatcher {
+    /// Calls `callback(key, old, new)` whenever a key under the prefix matching `pattern`,
+    /// which leaves out the prefix, changes, with its values passed through `decoder` if set.
+    #[pyo3(signature = (pattern, callback, decoder=None))]
+    fn register(&self, pattern: &PyBytes, callback: PyObject, decoder: Option<PyObject>) {
+        self.registrations
+            .lock()
+            .unwrap()
+            .push(Arc::new(Registration {
+                pattern: pattern.as_bytes().to_vec(),
+                callback,
+                decoder,
+            }));
+    }
+
+    /// Reads the keys under the prefix, calling the callbacks registered so far with the keys
+    /// which exist, then calls them as the keys change in the background.
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let watcher = slf.borrow(py);
+        let mirroring = watcher.mirroring.clone();
+        let debounce = watcher.debounce;
+        let registrations = watcher.registrations.clone();
+        let tasks = watcher.tasks.clone();
+        watcher.abort_tasks();
+        drop(watcher);
+        let closed = mirroring.communicator().borrow(py).closed.subscribe();
+
+        future_into_py(py, async move {
+            mirroring.load(false).await?;
+            Python::with_gil(|py| {
+                let initial = mirroring
+                    .values(py)
+                    .into_iter()
+                    .map(|(key, value)| (key, None, Some(value)))
+                    .collect();
+                dispatch(py, &registrations, initial);
+            });
+
+            let changes = mirroring.subscribe();
+            let follow = mirroring.spawn(closed.clone());
+            let delivery = tokio::spawn(async move {
+                tokio::select! {
+                    _ = deliver(changes, debounce, registrations) => {}
+                    _ = wait_closed(closed) => {}
+                }
+            });
+            tasks.lock().unwrap().extend([follow, delivery]);
+            Ok(slf)
+        })
+    }
+
+    /// Stops calling the callbacks.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        self.abort_tasks();
+        future_into_py(py, async move { Ok(()) })
+    }
+}
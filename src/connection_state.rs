@@ -0,0 +1,96 @@
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+
+use crate::communicator::wait_closed;
+use crate::py_enum::PyEnum;
+use crate::runtime::future_into_py;
+
+/// The state of the connection of a communicator, as last seen by its requests
+/// and background health checks, named after the connectivity states of gRPC channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    // Until the first request, as the channel connects lazily, and while reconnecting.
+    Connecting = 0,
+    Ready = 1,
+    TransientFailure = 2,
+}
+
+pub static CONNECTION_STATE: PyEnum = PyEnum::new(
+    "Enum",
+    "ConnectionState",
+    &[("CONNECTING", 0), ("READY", 1), ("TRANSIENT_FAILURE", 2)],
+);
+
+impl IntoPy<PyObject> for ConnectionState {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        CONNECTION_STATE
+            .member(py, self as i32)
+            .expect("ConnectionState has a member for every state")
+    }
+}
+
+/// Moves `sender` to `state`, notifying the receivers only if it changed.
+pub fn set_state(sender: &watch::Sender<ConnectionState>, state: ConnectionState) {
+    sender.send_if_modified(|current| {
+        if *current == state {
+            return false;
+        }
+        log::debug!("connection state changed from {current:?} to {state:?}");
+        *current = state;
+        true
+    });
+}
+
+/// Yields the state of the connection of a communicator, then every state it moves to,
+/// until the communicator is closed. A state which changed again before being yielded
+/// is skipped, so that a slow consumer only ever sees the latest one.
+#[pyclass(name = "ConnectionStateChanges")]
+#[derive(Clone)]
+pub struct PyConnectionStateChanges {
+    receiver: Arc<Mutex<watch::Receiver<ConnectionState>>>,
+    closed: watch::Receiver<bool>,
+    last: Arc<Mutex<Option<ConnectionState>>>,
+}
+
+impl PyConnectionStateChanges {
+    pub fn new(receiver: watch::Receiver<ConnectionState>, closed: watch::Receiver<bool>) -> Self {
+        Self {
+            receiver: Arc::new(Mutex::new(receiver)),
+            closed,
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+#[pymethods]
+impl PyConnectionStateChanges {
+    fn __aiter__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __anext__<'a>(&'a self, py: Python<'a>) -> PyResult<Option<PyObject>> {
+        let receiver = self.receiver.clone();
+        let closed = self.closed.clone();
+        let last = self.last.clone();
+
+        Ok(Some(
+            future_into_py(py, async move {
+                let mut receiver = receiver.lock().await;
+                let mut last = last.lock().await;
+                let state = tokio::select! {
+                    state = receiver.wait_for(|state| Some(*state) != *last) => match state {
+                        Ok(state) => *state,
+                        // The communicator was dropped.
+                        Err(_) => return Err(PyStopAsyncIteration::new_err(())),
+                    },
+                    _ = wait_closed(closed) => return Err(PyStopAsyncIteration::new_err(())),
+                };
+                *last = Some(state);
+                Ok(state)
+            })?
+            .into(),
+        ))
+    }
+}
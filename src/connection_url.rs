@@ -0,0 +1,116 @@
+use pyo3::prelude::*;
+
+use crate::client::PyConnectOptions;
+use crate::error::{EndpointError, InvalidUriError};
+
+fn percent_decode(text: &str) -> PyResult<String> {
+    let invalid = || InvalidUriError::new_err(format!("invalid percent-encoding in {text:?}"));
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).ok_or_else(invalid)?;
+            let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+fn parse_seconds(name: &str, value: &str) -> PyResult<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .ok_or_else(|| InvalidUriError::new_err(format!("invalid {name}: {value:?}")))
+}
+
+/// Parses a connection URL of the form
+/// `etcd[s]://[user:password@]host1:port1[,host2:port2...][/][?option=value&...]`
+/// into the endpoints to connect to and `connect_options` updated with the options it sets.
+pub fn parse_url(
+    url: &str,
+    connect_options: PyConnectOptions,
+) -> PyResult<(Vec<String>, PyConnectOptions)> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| InvalidUriError::new_err(format!("missing scheme in {url:?}")))?;
+    let scheme = match scheme {
+        "etcd" | "http" => "http",
+        "etcds" | "https" => "https",
+        _ => {
+            return Err(InvalidUriError::new_err(format!(
+                "unsupported scheme {scheme:?}, expected \"etcd\" or \"etcds\""
+            )))
+        }
+    };
+
+    let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let authority = rest.strip_suffix('/').unwrap_or(rest);
+    if authority.contains('/') {
+        return Err(InvalidUriError::new_err(format!(
+            "unexpected path in {url:?}"
+        )));
+    }
+
+    let mut connect_options = connect_options;
+    let hosts = match authority.rsplit_once('@') {
+        Some((user_info, hosts)) => {
+            let (name, password) = user_info.split_once(':').ok_or_else(|| {
+                InvalidUriError::new_err("credentials must be given as user:password")
+            })?;
+            connect_options =
+                connect_options.with_user(percent_decode(name)?, percent_decode(password)?);
+            hosts
+        }
+        None => authority,
+    };
+
+    let endpoints = hosts
+        .split(',')
+        .filter(|host| !host.is_empty())
+        .map(|host| format!("{scheme}://{host}"))
+        .collect::<Vec<_>>();
+    if endpoints.is_empty() {
+        return Err(EndpointError::new_err(format!("no hosts in {url:?}")));
+    }
+
+    let (mut ca_path, mut cert_path, mut key_path) = (None, None, None);
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value)?;
+        match name {
+            "timeout" => {
+                connect_options = connect_options.with_timeout(parse_seconds(name, &value)?);
+            }
+            "connect_timeout" => {
+                connect_options =
+                    connect_options.with_connect_timeout(parse_seconds(name, &value)?);
+            }
+            "cacert" => ca_path = Some(value),
+            "cert" => cert_path = Some(value),
+            "key" => key_path = Some(value),
+            _ => {
+                return Err(InvalidUriError::new_err(format!(
+                    "unknown option {name:?} in {url:?}"
+                )))
+            }
+        }
+    }
+    match ca_path {
+        Some(ca_path) => {
+            connect_options = connect_options.with_tls(ca_path, cert_path, key_path)?
+        }
+        None if cert_path.is_some() || key_path.is_some() => {
+            return Err(InvalidUriError::new_err("cert and key require cacert"));
+        }
+        None => {}
+    }
+
+    Ok((endpoints, connect_options))
+}
@@ -0,0 +1,86 @@
+use etcd_client::Client as EtcdClient;
+use hickory_resolver::TokioAsyncResolver;
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::sync::Weak;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+use crate::error::EndpointError;
+
+/// Resolves the client endpoints of the cluster announced under `domain`
+/// through the same SRV records as etcdctl's `--discovery-srv`.
+pub async fn resolve_srv(domain: &str) -> PyResult<Vec<String>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()
+        .map_err(|e| EndpointError::new_err(e.to_string()))?;
+
+    let mut endpoints = Vec::new();
+    for (service, scheme) in [("_etcd-client-ssl", "https"), ("_etcd-client", "http")] {
+        // A cluster usually announces only one of both services.
+        if let Ok(lookup) = resolver
+            .srv_lookup(format!("{service}._tcp.{domain}."))
+            .await
+        {
+            for srv in lookup.iter() {
+                let target = srv.target().to_utf8();
+                let host = target.trim_end_matches('.');
+                endpoints.push(format!("{scheme}://{host}:{}", srv.port()));
+            }
+        }
+    }
+
+    if endpoints.is_empty() {
+        return Err(EndpointError::new_err(format!(
+            "no etcd SRV records found for {domain}"
+        )));
+    }
+    endpoints.sort();
+    endpoints.dedup();
+    Ok(endpoints)
+}
+
+/// Re-resolves `domain` every `interval` and applies the changes to the endpoints of `client`,
+/// until the client is dropped or closed. Failed lookups keep the current endpoints.
+pub fn spawn_srv_refresh(
+    client: Weak<Mutex<Vec<EtcdClient>>>,
+    domain: String,
+    interval: f64,
+    endpoints: Vec<String>,
+) {
+    tokio::spawn(async move {
+        let mut current = endpoints.into_iter().collect::<HashSet<_>>();
+        loop {
+            sleep(Duration::from_secs_f64(interval)).await;
+
+            let resolved = match resolve_srv(&domain).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    log::warn!("failed to refresh the endpoints of {domain}: {e}");
+                    continue;
+                }
+            };
+            let resolved = resolved.into_iter().collect::<HashSet<_>>();
+
+            let Some(client) = client.upgrade() else {
+                break;
+            };
+            let clients = client.lock().await;
+            if clients.is_empty() {
+                break;
+            }
+            if resolved != current {
+                log::info!("endpoints of {domain} changed to {resolved:?}");
+            }
+            for client in clients.iter() {
+                for endpoint in resolved.difference(&current) {
+                    let _ = client.add_endpoint(endpoint).await;
+                }
+                for endpoint in current.difference(&resolved) {
+                    let _ = client.remove_endpoint(endpoint).await;
+                }
+            }
+            current = resolved;
+        }
+    });
+}
@@ -0,0 +1,40 @@
+use etcd_client::LeaderKey;
+use pyo3::{prelude::*, types::PyBytes};
+
+#[derive(Debug, Clone)]
+#[pyclass(name = "LeaderKey")]
+pub struct PyLeaderKey(pub LeaderKey);
+
+#[pymethods]
+impl PyLeaderKey {
+    #[new]
+    fn new(name: &PyBytes, key: &PyBytes, rev: i64, lease: i64) -> Self {
+        Self(
+            LeaderKey::new()
+                .with_name(name.as_bytes())
+                .with_key(key.as_bytes())
+                .with_rev(rev)
+                .with_lease(lease),
+        )
+    }
+
+    pub fn name(&self) -> Vec<u8> {
+        self.0.name().to_owned()
+    }
+
+    pub fn key(&self) -> Vec<u8> {
+        self.0.key().to_owned()
+    }
+
+    pub fn rev(&self) -> i64 {
+        self.0.rev()
+    }
+
+    pub fn lease(&self) -> i64 {
+        self.0.lease()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
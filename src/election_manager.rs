@@ -0,0 +1,272 @@
+use crate::runtime::future_into_py;
+use crate::{
+    client::PyClient,
+    condvar::PyCondVar,
+    election::PyLeaderKey,
+    election_response::PyProclaimResponse,
+    error::{GRPCStatusError, PyClientError},
+    keepalive::{keep_lease_alive, KeepAliveTask},
+};
+use etcd_client::{
+    Client as EtcdClient, EventType, LeaderKey, ProclaimOptions, ResignOptions, WatchOptions,
+};
+
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyTuple},
+};
+use scopeguard::ScopeGuard;
+use std::{sync::Arc, time::Duration};
+use tokio::{sync::Mutex, task::JoinHandle};
+
+#[pyclass(name = "Leadership")]
+#[derive(Clone)]
+pub struct PyLeadership {
+    pub client: EtcdClient,
+    pub leader_key: LeaderKey,
+    pub lost: PyCondVar,
+}
+
+#[pymethods]
+impl PyLeadership {
+    fn leader_key(&self) -> PyLeaderKey {
+        PyLeaderKey(self.leader_key.clone())
+    }
+
+    fn proclaim<'a>(&'a self, py: Python<'a>, value: &PyBytes) -> PyResult<&'a PyAny> {
+        let mut client = self.client.clone();
+        let leader_key = self.leader_key.clone();
+        let value = value.as_bytes().to_vec();
+
+        future_into_py(py, async move {
+            let options = ProclaimOptions::new().with_leader(leader_key);
+            let result = client.proclaim(value, Some(options)).await;
+            result
+                .map(PyProclaimResponse)
+                .map_err(|e| PyClientError(e).into())
+        })
+    }
+
+    fn lost<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let lost = self.lost.clone();
+        future_into_py(py, async move {
+            // Unlike `CondVar.wait()`, registers before checking, so that a loss signalled
+            // in between is not missed.
+            lost._wait().await;
+            Ok(())
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Leadership(leader_key={:?})", self.leader_key)
+    }
+}
+
+#[pyclass(name = "Election")]
+#[derive(Clone)]
+pub struct PyElection(pub Arc<Mutex<EtcdElectionManager>>);
+
+#[pymethods]
+impl PyElection {
+    fn __aenter__<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let election_manager = self.0.clone();
+        future_into_py(py, async move {
+            election_manager.lock().await.handle_aenter().await
+        })
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        let election_manager = self.0.clone();
+        future_into_py(py, async move {
+            election_manager.lock().await.handle_aexit().await
+        })
+    }
+}
+
+pub struct EtcdElectionManager {
+    pub client: PyClient,
+    pub name: Vec<u8>,
+    pub value: Vec<u8>,
+    pub ttl: i64,
+    pub shared_lease_id: Option<i64>,
+    pub lease_id: Option<i64>,
+    pub leadership: Option<PyLeadership>,
+    pub lease_keepalive_task: Option<KeepAliveTask>,
+    pub leader_watch_task: Option<JoinHandle<()>>,
+}
+
+impl EtcdElectionManager {
+    pub fn new(
+        client: PyClient,
+        name: Vec<u8>,
+        value: Vec<u8>,
+        ttl: i64,
+        shared_lease_id: Option<i64>,
+    ) -> Self {
+        Self {
+            client,
+            name,
+            value,
+            ttl,
+            shared_lease_id,
+            lease_id: None,
+            leadership: None,
+            lease_keepalive_task: None,
+            leader_watch_task: None,
+        }
+    }
+
+    fn abort_tasks(&mut self) {
+        if let Some(ref lease_keepalive_task) = self.lease_keepalive_task {
+            lease_keepalive_task.abort();
+        }
+        if let Some(ref leader_watch_task) = self.leader_watch_task {
+            leader_watch_task.abort();
+        }
+    }
+
+    async fn revoke_lease(&mut self, client: &mut EtcdClient) -> PyResult<()> {
+        if let Some(lease_id) = self.lease_id.take() {
+            if let Err(etcd_client::Error::GRpcStatus(status)) = client.lease_revoke(lease_id).await
+            {
+                if status.code() != tonic::Code::NotFound {
+                    return Err(GRPCStatusError::new_err(status.to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn handle_aenter(&mut self) -> PyResult<PyLeadership> {
+        let (mut client, _) = self.client.connect_etcd_client().await?;
+
+        // Cancelling the campaign drops this future, which must not leave the lease kept alive,
+        // as a leader key created meanwhile would then never be deleted.
+        let mut self_ = scopeguard::guard(self, {
+            let client = client.clone();
+            move |self_| {
+                self_.abort_tasks();
+                if let (Some(lease_id), Ok(runtime)) =
+                    (self_.lease_id.take(), tokio::runtime::Handle::try_current())
+                {
+                    let mut client = client;
+                    runtime.spawn(async move {
+                        let _ = client.lease_revoke(lease_id).await;
+                    });
+                }
+            }
+        });
+
+        let lost = PyCondVar::new();
+
+        // A shared lease is kept alive and revoked by its owner,
+        // so only a lease granted here gets a keepalive task.
+        let lease_id = match self_.shared_lease_id {
+            Some(lease_id) => lease_id,
+            None => {
+                let ttl = self_.ttl;
+                let lease_id = client
+                    .lease_grant(ttl, None)
+                    .await
+                    .map_err(PyClientError)?
+                    .id();
+                self_.lease_id = Some(lease_id);
+
+                let interval = Duration::from_secs_f64((ttl as f64) / 3.0);
+                let keep_alive = keep_lease_alive(client.clone(), lease_id, interval);
+                let lost_to_move = lost.clone();
+                self_.lease_keepalive_task = Some(KeepAliveTask::spawn(
+                    "election",
+                    self_.name.clone(),
+                    lease_id,
+                    self_.client.connect_options.keepalive_callback.clone(),
+                    async move {
+                        let result = keep_alive.await;
+                        lost_to_move._notify_waiters().await;
+                        result
+                    },
+                ));
+
+                lease_id
+            }
+        };
+
+        let campaign_result = client
+            .campaign(self_.name.clone(), self_.value.clone(), lease_id)
+            .await;
+
+        let leader_key = match campaign_result.map(|mut res| res.take_leader()) {
+            Ok(Some(leader_key)) => leader_key,
+            Ok(None) => {
+                self_.abort_tasks();
+                self_.revoke_lease(&mut client).await?;
+                return Err(PyClientError(etcd_client::Error::ElectError(
+                    "campaign response has no leader key".to_owned(),
+                ))
+                .into());
+            }
+            Err(e) => {
+                self_.abort_tasks();
+                self_.revoke_lease(&mut client).await?;
+                return Err(PyClientError(e).into());
+            }
+        };
+
+        // Leadership is gone as soon as the leader key is deleted,
+        // so watch it from its creation to not miss an early deletion.
+        let options = WatchOptions::new().with_start_revision(leader_key.rev());
+        let (watcher, mut stream) = match client.watch(leader_key.key(), Some(options)).await {
+            Ok(watch) => watch,
+            Err(e) => {
+                self_.abort_tasks();
+                self_.revoke_lease(&mut client).await?;
+                return Err(PyClientError(e).into());
+            }
+        };
+
+        let lost_to_move = lost.clone();
+        self_.leader_watch_task = Some(tokio::spawn(async move {
+            let _watcher = watcher;
+            while let Ok(Some(res)) = stream.message().await {
+                if res
+                    .events()
+                    .iter()
+                    .any(|event| event.event_type() == EventType::Delete)
+                {
+                    break;
+                }
+            }
+            lost_to_move._notify_waiters().await;
+        }));
+
+        let leadership = PyLeadership {
+            client,
+            leader_key,
+            lost,
+        };
+        let self_ = ScopeGuard::into_inner(self_);
+        self_.leadership = Some(leadership.clone());
+        Ok(leadership)
+    }
+
+    pub async fn handle_aexit(&mut self) -> PyResult<()> {
+        let (mut client, _) = self.client.connect_etcd_client().await?;
+
+        self.abort_tasks();
+        self.lease_keepalive_task = None;
+        self.leader_watch_task = None;
+
+        let leadership = self.leadership.take();
+        if self.lease_id.is_some() {
+            // Revoking the lease deletes the leader key, which resigns from the election.
+            self.revoke_lease(&mut client).await
+        } else {
+            if let Some(leadership) = leadership {
+                let options = ResignOptions::new().with_leader(leadership.leader_key);
+                client.resign(Some(options)).await.map_err(PyClientError)?;
+            }
+            Ok(())
+        }
+    }
+}
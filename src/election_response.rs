@@ -0,0 +1,74 @@
+use etcd_client::{CampaignResponse, LeaderResponse, ProclaimResponse, ResignResponse};
+use pyo3::prelude::*;
+
+use crate::election::PyLeaderKey;
+use crate::key_value::PyKeyValue;
+use crate::response_header::PyResponseHeader;
+
+#[derive(Clone)]
+#[pyclass(name = "CampaignResponse")]
+pub struct PyCampaignResponse(pub CampaignResponse);
+
+#[pymethods]
+impl PyCampaignResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn leader(&self) -> Option<PyLeaderKey> {
+        self.0.leader().cloned().map(PyLeaderKey)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "ProclaimResponse")]
+pub struct PyProclaimResponse(pub ProclaimResponse);
+
+#[pymethods]
+impl PyProclaimResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "LeaderResponse")]
+pub struct PyLeaderResponse(pub LeaderResponse);
+
+#[pymethods]
+impl PyLeaderResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn kv(&self) -> Option<PyKeyValue> {
+        self.0.kv().cloned().map(PyKeyValue)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "ResignResponse")]
+pub struct PyResignResponse(pub ResignResponse);
+
+#[pymethods]
+impl PyResignResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
@@ -1,5 +1,8 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyBytes, PyTuple};
 use pyo3::{create_exception, exceptions::PyException, types::PyDict, PyErr, Python, *};
 use std::fmt::Debug;
+use tonic::metadata::KeyAndValueRef;
 
 create_exception!(etcd_client, ClientError, PyException);
 create_exception!(etcd_client, GRPCStatusError, ClientError);
@@ -8,14 +11,27 @@ create_exception!(etcd_client, InvalidUriError, ClientError);
 create_exception!(etcd_client, IoError, ClientError);
 create_exception!(etcd_client, TransportError, ClientError);
 create_exception!(etcd_client, WatchError, ClientError);
+create_exception!(etcd_client, ConnectionLostError, WatchError);
 create_exception!(etcd_client, Utf8Error, ClientError);
 create_exception!(etcd_client, LeaseKeepAliveError, ClientError);
 create_exception!(etcd_client, ElectError, ClientError);
 create_exception!(etcd_client, InvalidHeaderValueError, ClientError);
 create_exception!(etcd_client, EndpointError, ClientError);
 create_exception!(etcd_client, LockError, ClientError);
+create_exception!(etcd_client, CircuitOpenError, ClientError);
+create_exception!(etcd_client, NotFoundError, GRPCStatusError);
+create_exception!(etcd_client, AlreadyExistsError, GRPCStatusError);
+create_exception!(etcd_client, PermissionDeniedError, GRPCStatusError);
+create_exception!(etcd_client, UnavailableError, GRPCStatusError);
+create_exception!(etcd_client, DeadlineExceededError, GRPCStatusError);
+create_exception!(etcd_client, CompactedError, GRPCStatusError);
+create_exception!(etcd_client, RequestTimeoutError, ClientError);
+create_exception!(etcd_client, ConflictError, ClientError);
+create_exception!(etcd_client, ReplayMismatchError, ClientError);
+create_exception!(etcd_client, SchemaError, InvalidArgsError);
 
 #[pyclass(name = "GRPCStatusCode")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PyGRPCStatusCode {
     Ok = 0,
     Cancelled = 1,
@@ -36,13 +52,190 @@ pub enum PyGRPCStatusCode {
     Unauthenticated = 16,
 }
 
+// Besides the `int()` conversion and the equality with `int`s generated for enums,
+// behaves like an `IntEnum` for hashing and conversions from `int`s.
+#[pymethods]
+impl PyGRPCStatusCode {
+    #[staticmethod]
+    fn from_int(code: i32) -> PyResult<Self> {
+        if !(0..=16).contains(&code) {
+            return Err(PyValueError::new_err(format!(
+                "{code} is not a valid GRPCStatusCode"
+            )));
+        }
+        Ok(tonic::Code::from_i32(code).into())
+    }
+
+    fn __index__(&self) -> isize {
+        *self as isize
+    }
+
+    // Hashes like the `int` it equals.
+    fn __hash__(&self) -> u64 {
+        *self as u64
+    }
+}
+
+impl From<tonic::Code> for PyGRPCStatusCode {
+    fn from(code: tonic::Code) -> Self {
+        match code {
+            tonic::Code::Ok => Self::Ok,
+            tonic::Code::Cancelled => Self::Cancelled,
+            tonic::Code::Unknown => Self::Unknown,
+            tonic::Code::InvalidArgument => Self::InvalidArgument,
+            tonic::Code::DeadlineExceeded => Self::DeadlineExceeded,
+            tonic::Code::NotFound => Self::NotFound,
+            tonic::Code::AlreadyExists => Self::AlreadyExists,
+            tonic::Code::PermissionDenied => Self::PermissionDenied,
+            tonic::Code::ResourceExhausted => Self::ResourceExhausted,
+            tonic::Code::FailedPrecondition => Self::FailedPrecondition,
+            tonic::Code::Aborted => Self::Aborted,
+            tonic::Code::OutOfRange => Self::OutOfRange,
+            tonic::Code::Unimplemented => Self::Unimplemented,
+            tonic::Code::Internal => Self::Internal,
+            tonic::Code::Unavailable => Self::Unavailable,
+            tonic::Code::DataLoss => Self::DataLoss,
+            tonic::Code::Unauthenticated => Self::Unauthenticated,
+        }
+    }
+}
+
 #[derive(Debug)]
 #[pyclass(name = "ClientError")]
 pub struct PyClientError(pub etcd_client::Error);
 
 impl From<PyClientError> for PyErr {
     fn from(error: PyClientError) -> Self {
-        match &error.0 {
+        error.into_pyerr(false)
+    }
+}
+
+/// Returns `IoError` for a failed local I/O operation, formatted like the client's own `IoError`.
+pub fn io_error(e: impl std::fmt::Display) -> PyErr {
+    IoError::new_err(format!("IoError(err={})", e))
+}
+
+// The message etcd rejects reads and watches below the compaction point with.
+const COMPACTED: &str = "mvcc: required revision has been compacted";
+
+/// Returns `CompactedError` for a watch which etcd canceled as it fell below the compaction
+/// point, setting its `compact_revision` attribute like the status it would have answered with.
+pub fn compacted_error(compact_revision: i64) -> PyErr {
+    let status = tonic::Status::out_of_range(format!("etcdserver: {COMPACTED}"));
+    let error = PyErr::from(PyClientError(etcd_client::Error::GRpcStatus(status)));
+    Python::with_gil(|py| {
+        let _ = error
+            .value(py)
+            .setattr("compact_revision", compact_revision);
+    });
+    error
+}
+
+/// Returns `NotFoundError` for `key`, which `operation` found missing, like the status
+/// etcd answers with for a missing lease or member, as etcd returns no error for missing keys.
+pub fn not_found_error(operation: &str, key: &[u8]) -> PyErr {
+    let status = tonic::Status::not_found("etcdserver: key not found");
+    let error = PyErr::from(PyClientError(etcd_client::Error::GRpcStatus(status)));
+    add_context(error, operation, Some(key), None)
+}
+
+/// Whether a request which failed with `error` can safely be sent again as is.
+/// A request timing out or losing its connection may have been applied, so it is only
+/// retryable if `idempotent`, unless the connection was refused before it was sent.
+pub fn is_retryable(error: &etcd_client::Error, idempotent: bool) -> bool {
+    match error {
+        etcd_client::Error::TransportError(_) => idempotent || is_connection_refused(error),
+        etcd_client::Error::GRpcStatus(status) => match status.code() {
+            tonic::Code::Unavailable => true,
+            tonic::Code::DeadlineExceeded => idempotent,
+            _ => idempotent && is_local_timeout(error),
+        },
+        _ => false,
+    }
+}
+
+/// Whether `error` is the refusal of a connection, which no request was sent over.
+fn is_connection_refused(error: &etcd_client::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = match error {
+        etcd_client::Error::TransportError(e) => Some(e),
+        _ => None,
+    };
+    while let Some(error) = source {
+        if error
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::ConnectionRefused)
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+// The message of `tonic::transport::TimeoutExpired`, which is lost when it becomes a status.
+const TIMEOUT_EXPIRED: &str = "Timeout expired";
+
+/// Whether `error` is a timeout enforced by the client rather than by etcd, i.e. the timeout of
+/// `ConnectOptions.with_timeout()`, which tonic cancels the request with, or of connecting.
+fn is_local_timeout(error: &etcd_client::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = match error {
+        etcd_client::Error::GRpcStatus(status) => {
+            if status.code() == tonic::Code::Cancelled && status.message() == TIMEOUT_EXPIRED {
+                return true;
+            }
+            std::error::Error::source(status)
+        }
+        etcd_client::Error::TransportError(e) => Some(e),
+        _ => None,
+    };
+    while let Some(error) = source {
+        if error.is::<tonic::transport::TimeoutExpired>()
+            || error
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::TimedOut)
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl PyClientError {
+    /// Converts the error like `PyErr::from()`, which assumes the failed request
+    /// was not `idempotent`, setting its `retryable` attribute.
+    pub fn into_pyerr(self, idempotent: bool) -> PyErr {
+        let retryable = is_retryable(&self.0, idempotent);
+        let error = self.to_pyerr();
+        Python::with_gil(|py| {
+            // Only fails for exceptions rejecting attributes, which these are not.
+            let _ = error.value(py).setattr("retryable", retryable);
+            if let Some(wrapped) = self.wrapped() {
+                error.set_cause(py, Some(cause(py, wrapped)));
+            }
+        });
+        error
+    }
+
+    /// The error which this one wraps, if any, and which its message only formats.
+    fn wrapped(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.0 {
+            etcd_client::Error::InvalidUri(e) => Some(e),
+            etcd_client::Error::IoError(e) => Some(e),
+            etcd_client::Error::TransportError(e) => Some(e),
+            // The status is the exception itself.
+            etcd_client::Error::GRpcStatus(e) => std::error::Error::source(e),
+            etcd_client::Error::Utf8Error(e) => Some(e),
+            etcd_client::Error::InvalidHeaderValue(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn to_pyerr(&self) -> PyErr {
+        if is_local_timeout(&self.0) {
+            return RequestTimeoutError::new_err(format!("RequestTimeoutError(err={})", self.0));
+        }
+        match &self.0 {
             etcd_client::Error::GRpcStatus(e) => Python::with_gil(|py| {
                 let error_details = PyDict::new(py);
                 error_details.set_item("code", e.code() as u64).unwrap();
@@ -54,7 +247,21 @@ impl From<PyClientError> for PyErr {
                     .unwrap();
 
                 let kv_args: PyObject = error_details.into_py(py);
-                GRPCStatusError::new_err(kv_args)
+                let error = match e.code() {
+                    tonic::Code::NotFound => NotFoundError::new_err(kv_args),
+                    tonic::Code::AlreadyExists => AlreadyExistsError::new_err(kv_args),
+                    tonic::Code::PermissionDenied => PermissionDeniedError::new_err(kv_args),
+                    tonic::Code::Unavailable => UnavailableError::new_err(kv_args),
+                    tonic::Code::DeadlineExceeded => DeadlineExceededError::new_err(kv_args),
+                    tonic::Code::OutOfRange if e.message().ends_with(COMPACTED) => {
+                        CompactedError::new_err(kv_args)
+                    }
+                    _ => GRPCStatusError::new_err(kv_args),
+                };
+                let _ = error
+                    .value(py)
+                    .setattr("metadata", metadata(py, e.metadata()));
+                error
             }),
             etcd_client::Error::InvalidArgs(e) => {
                 InvalidArgsError::new_err(format!("InvalidArgsError(err={})", e))
@@ -62,7 +269,7 @@ impl From<PyClientError> for PyErr {
             etcd_client::Error::InvalidUri(e) => {
                 InvalidUriError::new_err(format!("InvalidUriError(err={})", e))
             }
-            etcd_client::Error::IoError(e) => IoError::new_err(format!("IoError(err={})", e)),
+            etcd_client::Error::IoError(e) => io_error(e),
             etcd_client::Error::TransportError(e) => {
                 TransportError::new_err(format!("TransportError(err={})", e))
             }
@@ -85,3 +292,78 @@ impl From<PyClientError> for PyErr {
         }
     }
 }
+
+/// Converts `error` and its sources to a chain of exceptions linked by `__cause__`, as `OSError`
+/// for I/O errors, so that the root cause of a failure can be told apart from the failure itself.
+fn cause(py: Python<'_>, error: &(dyn std::error::Error + 'static)) -> PyErr {
+    let cause = match error.downcast_ref::<std::io::Error>() {
+        Some(e) => match e.raw_os_error() {
+            Some(code) => std::io::Error::from_raw_os_error(code).into(),
+            None => std::io::Error::new(e.kind(), e.to_string()).into(),
+        },
+        None => PyException::new_err(error.to_string()),
+    };
+    if let Some(source) = error.source() {
+        cause.set_cause(py, Some(self::cause(py, source)));
+    }
+    cause
+}
+
+/// Converts the metadata of a status, e.g. its trailers, to `(key, value)` pairs in the order
+/// received, with the values of binary (`-bin`) keys decoded to `bytes` and the others as `str`.
+fn metadata<'a>(py: Python<'a>, metadata: &tonic::metadata::MetadataMap) -> &'a PyTuple {
+    let pairs: Vec<PyObject> = metadata
+        .iter()
+        .map(|pair| {
+            let (key, value): (&str, PyObject) = match pair {
+                KeyAndValueRef::Ascii(key, value) => match value.to_str() {
+                    Ok(value) => (key.as_str(), value.into_py(py)),
+                    Err(_) => (
+                        key.as_str(),
+                        PyBytes::new(py, value.as_encoded_bytes()).into(),
+                    ),
+                },
+                KeyAndValueRef::Binary(key, value) => match value.to_bytes() {
+                    Ok(bytes) => (key.as_str(), PyBytes::new(py, &bytes).into()),
+                    Err(_) => (
+                        key.as_str(),
+                        PyBytes::new(py, value.as_encoded_bytes()).into(),
+                    ),
+                },
+            };
+            (key, value).into_py(py)
+        })
+        .collect();
+    PyTuple::new(py, pairs)
+}
+
+/// Sets the `operation`, `key` and `endpoint` attributes of `error` raised by a request,
+/// and notes them in its traceback, as the error alone rarely tells which request failed.
+pub fn add_context(
+    error: PyErr,
+    operation: &str,
+    key: Option<&[u8]>,
+    endpoint: Option<&str>,
+) -> PyErr {
+    Python::with_gil(|py| {
+        let key = key.map(|key| PyBytes::new(py, key));
+        let mut note = format!("raised by {operation}");
+        if let Some(key) = key {
+            note += &format!(" of {key:?}");
+        }
+        if let Some(endpoint) = endpoint {
+            note += &format!(" at {endpoint}");
+        }
+
+        // Setting attributes only fails for exceptions rejecting them, which these are not.
+        let value = error.value(py);
+        let _ = value.setattr("operation", operation);
+        let _ = value.setattr("key", key);
+        let _ = value.setattr("endpoint", endpoint);
+        // `add_note()` is only available from Python 3.11.
+        if value.hasattr("add_note").unwrap_or(false) {
+            let _ = value.call_method1("add_note", (note,));
+        }
+    });
+    error
+}
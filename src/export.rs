@@ -0,0 +1,239 @@
+use etcd_client::{Compare, CompareOp, GetOptions, Txn, TxnOp, TxnOpResponse};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
+
+use crate::communicator::PyCommunicator;
+use crate::error::{io_error, InvalidArgsError};
+use crate::migration::{get, prefix_end};
+
+/// Checks the arguments of `export_prefix()` and `import_prefix()`.
+pub fn check_chunk_size(chunk_size: usize) -> PyResult<()> {
+    if chunk_size == 0 {
+        return Err(InvalidArgsError::new_err("chunk_size must be at least 1"));
+    }
+    Ok(())
+}
+
+/// Files ending with `.jsonl` hold a record per line, others a single JSON document.
+fn is_jsonl(path: &str) -> bool {
+    path.ends_with(".jsonl")
+}
+
+/// Sets `name` in `record` to `bytes` as a string, or to their base64 as `{name}_base64`
+/// if they are not UTF-8.
+pub fn set_field(py: Python, record: &PyDict, name: &str, bytes: &[u8]) -> PyResult<()> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => record.set_item(name, text),
+        Err(_) => {
+            let encoded = py
+                .import("base64")?
+                .call_method1("b64encode", (PyBytes::new(py, bytes),))?
+                .call_method1("decode", ("ascii",))?;
+            record.set_item(format!("{name}_base64"), encoded)
+        }
+    }
+}
+
+/// Reads the field set by `set_field()`, if any.
+fn field(py: Python, record: &PyDict, name: &str) -> PyResult<Option<Vec<u8>>> {
+    if let Some(text) = record.get_item(name)? {
+        return Ok(Some(text.extract::<String>()?.into_bytes()));
+    }
+    match record.get_item(format!("{name}_base64"))? {
+        Some(encoded) => {
+            let bytes = py.import("base64")?.call_method1("b64decode", (encoded,))?;
+            Ok(Some(bytes.downcast::<PyBytes>()?.as_bytes().to_vec()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Serializes the key, relative to the prefix, and the value of a record.
+fn dump_record(py: Python, key: &[u8], value: &[u8]) -> PyResult<String> {
+    let record = PyDict::new(py);
+    set_field(py, record, "key", key)?;
+    set_field(py, record, "value", value)?;
+    py.import("json")?
+        .call_method1("dumps", (record,))?
+        .extract()
+}
+
+/// Returns the key and the value of `record`, or `None` if it is not a key, like the header
+/// line of a JSONL file.
+fn load_record(py: Python, record: &PyAny) -> PyResult<Option<(Vec<u8>, Vec<u8>)>> {
+    let record = record.downcast::<PyDict>()?;
+    let Some(key) = field(py, record, "key")? else {
+        return Ok(None);
+    };
+    match field(py, record, "value")? {
+        Some(value) => Ok(Some((key, value))),
+        None => Err(InvalidArgsError::new_err(format!(
+            "the record of {:?} has no value",
+            String::from_utf8_lossy(&key)
+        ))),
+    }
+}
+
+/// Writes the keys under `prefix` to `path`, relative to the prefix, reading them `chunk_size`
+/// at a time at the revision of the first chunk, so that the file is a consistent copy of the
+/// prefix at that revision. Returns the number of keys written.
+pub async fn export_prefix(
+    communicator: Py<PyCommunicator>,
+    prefix: Vec<u8>,
+    path: String,
+    chunk_size: usize,
+) -> PyResult<i64> {
+    let jsonl = is_jsonl(&path);
+    let mut file = BufWriter::new(File::create(&path).await.map_err(io_error)?);
+    let end = prefix_end(&prefix);
+
+    let mut revision = 0;
+    let mut exported = 0;
+    // The range of every key starts at `\0` when the prefix is empty.
+    let mut start = if prefix.is_empty() {
+        vec![0]
+    } else {
+        prefix.clone()
+    };
+    loop {
+        let mut options = GetOptions::new()
+            .with_range(end.clone())
+            .with_limit(chunk_size as i64);
+        if revision != 0 {
+            options = options.with_revision(revision);
+        }
+        let mut response = get(&communicator, "export_prefix", &start, options).await?;
+        if revision == 0 {
+            revision = response.header().map_or(0, |header| header.revision());
+            let header = if jsonl {
+                format!("{{\"revision\": {revision}}}\n")
+            } else {
+                format!("{{\"revision\": {revision}, \"kvs\": [")
+            };
+            file.write_all(header.as_bytes()).await.map_err(io_error)?;
+        }
+
+        let more = response.more();
+        let kvs = response.take_kvs();
+        let records = Python::with_gil(|py| {
+            kvs.iter()
+                .map(|kv| dump_record(py, &kv.key()[prefix.len()..], kv.value()))
+                .collect::<PyResult<Vec<_>>>()
+        })?;
+        for record in records {
+            let line = match (jsonl, exported) {
+                (true, _) => format!("{record}\n"),
+                (false, 0) => format!("\n  {record}"),
+                (false, _) => format!(",\n  {record}"),
+            };
+            file.write_all(line.as_bytes()).await.map_err(io_error)?;
+            exported += 1;
+        }
+
+        match kvs.last() {
+            Some(kv) if more => start = [kv.key(), &[0]].concat(),
+            _ => break,
+        }
+    }
+
+    if !jsonl {
+        file.write_all(b"\n]}\n").await.map_err(io_error)?;
+    }
+    file.flush().await.map_err(io_error)?;
+    file.get_ref().sync_all().await.map_err(io_error)?;
+    Ok(exported)
+}
+
+/// Puts the keys of `kvs` under `prefix` in a transaction, leaving the existing keys alone
+/// unless `overwrite` is set. Returns the number of keys put.
+async fn import_chunk(
+    communicator: &Py<PyCommunicator>,
+    prefix: &[u8],
+    kvs: &[(Vec<u8>, Vec<u8>)],
+    overwrite: bool,
+) -> PyResult<i64> {
+    if kvs.is_empty() {
+        return Ok(0);
+    }
+    let ops = kvs.iter().map(|(key, value)| {
+        let key = [prefix, key].concat();
+        let put = TxnOp::put(key.clone(), value.clone(), None);
+        if overwrite {
+            return put;
+        }
+        let absent = Compare::create_revision(key, CompareOp::Equal, 0);
+        TxnOp::txn(Txn::new().when([absent]).and_then([put]))
+    });
+    let txn = Txn::new().and_then(ops.collect::<Vec<_>>());
+
+    let request = Python::with_gil(|py| {
+        communicator.borrow(py).request(
+            "import_prefix",
+            Some(prefix.to_vec()),
+            move |mut client| {
+                let txn = txn.clone();
+                async move { client.txn(txn).await }
+            },
+        )
+    });
+    let response = request.await?;
+    if overwrite {
+        return Ok(kvs.len() as i64);
+    }
+    let put = response.op_responses().into_iter().filter(|op| match op {
+        TxnOpResponse::Txn(response) => response.succeeded(),
+        _ => false,
+    });
+    Ok(put.count() as i64)
+}
+
+/// Puts the keys written to `path` by `export_prefix()` under `prefix`, `chunk_size` keys per
+/// transaction. Returns the number of keys put.
+pub async fn import_prefix(
+    communicator: Py<PyCommunicator>,
+    path: String,
+    prefix: Vec<u8>,
+    overwrite: bool,
+    chunk_size: usize,
+) -> PyResult<i64> {
+    let mut file = File::open(&path).await.map_err(io_error)?;
+    let mut imported = 0;
+
+    if is_jsonl(&path) {
+        // Read a line at a time, so that large files are not held in memory at once.
+        let mut lines = BufReader::new(file).lines();
+        let mut chunk = Vec::with_capacity(chunk_size);
+        while let Some(line) = lines.next_line().await.map_err(io_error)? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = Python::with_gil(|py| {
+                let record = py.import("json")?.call_method1("loads", (line,))?;
+                load_record(py, record)
+            })?;
+            chunk.extend(record);
+            if chunk.len() == chunk_size {
+                imported += import_chunk(&communicator, &prefix, &chunk, overwrite).await?;
+                chunk.clear();
+            }
+        }
+        imported += import_chunk(&communicator, &prefix, &chunk, overwrite).await?;
+    } else {
+        let mut text = String::new();
+        file.read_to_string(&mut text).await.map_err(io_error)?;
+        let kvs = Python::with_gil(|py| {
+            let document = py.import("json")?.call_method1("loads", (text,))?;
+            let mut kvs = Vec::new();
+            for record in document.get_item("kvs")?.iter()? {
+                kvs.extend(load_record(py, record?)?);
+            }
+            PyResult::Ok(kvs)
+        })?;
+        for chunk in kvs.chunks(chunk_size) {
+            imported += import_chunk(&communicator, &prefix, chunk, overwrite).await?;
+        }
+    }
+    Ok(imported)
+}
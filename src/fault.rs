@@ -0,0 +1,173 @@
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::config_watcher::matches;
+use crate::error::{InvalidArgsError, PyClientError, PyGRPCStatusCode};
+
+/// A synthetic failure or latency injected into the requests for `operation` on the keys
+/// matching `pattern`, or into all of them if unset.
+struct Fault {
+    operation: Option<String>,
+    pattern: Option<Vec<u8>>,
+    error: Option<PyGRPCStatusCode>,
+    latency: Duration,
+    every: u64,
+    times: Option<u64>,
+    // The number of requests matched and of those injected into.
+    counts: Mutex<(u64, u64)>,
+}
+
+impl Fault {
+    /// Counts a request for `operation` on `key`, returning whether it is injected into.
+    fn hit(&self, operation: &str, key: Option<&[u8]>) -> bool {
+        if self
+            .operation
+            .as_deref()
+            .is_some_and(|name| name != operation)
+        {
+            return false;
+        }
+        if let Some(ref pattern) = self.pattern {
+            if !key.is_some_and(|key| matches(pattern, key)) {
+                return false;
+            }
+        }
+        let mut counts = self.counts.lock().unwrap();
+        let (matched, injected) = &mut *counts;
+        if self.times.is_some_and(|times| *injected >= times) {
+            return false;
+        }
+        *matched += 1;
+        if *matched % self.every != 0 {
+            return false;
+        }
+        *injected += 1;
+        true
+    }
+}
+
+/// The faults injected into the requests of a communicator, shared with its views.
+#[derive(Clone, Default)]
+pub struct Faults(Arc<Mutex<Vec<Arc<Fault>>>>);
+
+impl Faults {
+    /// Returns the latency to add to a request for `operation` on `key` and the error to fail
+    /// it with, if any, out of the faults injected into it.
+    pub fn hit(&self, operation: &str, key: Option<&[u8]>) -> (Duration, Option<tonic::Status>) {
+        let faults = self.0.lock().unwrap().clone();
+        let mut latency = Duration::ZERO;
+        let mut error = None;
+        for fault in faults.iter().filter(|fault| fault.hit(operation, key)) {
+            latency += fault.latency;
+            if let (None, Some(code)) = (&error, fault.error) {
+                let code = tonic::Code::from_i32(code as i32);
+                error = Some(tonic::Status::new(code, "injected fault"));
+            }
+        }
+        (latency, error)
+    }
+
+    /// Waits for the latency injected into a request for `operation` on `key`,
+    /// then fails it with the error injected into it, if any.
+    pub async fn inject(
+        &self,
+        operation: &str,
+        key: Option<&[u8]>,
+    ) -> Result<(), etcd_client::Error> {
+        let (latency, error) = self.hit(operation, key);
+        if !latency.is_zero() {
+            sleep(latency).await;
+        }
+        match error {
+            Some(status) => Err(etcd_client::Error::GRpcStatus(status)),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds a fault, returning it for Python.
+    pub fn add(
+        &self,
+        operation: Option<String>,
+        key: Option<&PyBytes>,
+        error: Option<PyGRPCStatusCode>,
+        latency: f64,
+        every: u64,
+        times: Option<u64>,
+    ) -> PyResult<PyFault> {
+        if !latency.is_finite() || latency < 0.0 {
+            return Err(InvalidArgsError::new_err(
+                "latency must be a non-negative number of seconds",
+            ));
+        }
+        if every == 0 {
+            return Err(InvalidArgsError::new_err("every must be at least 1"));
+        }
+        if error.is_none() && latency == 0.0 {
+            return Err(InvalidArgsError::new_err(
+                "an error or a latency must be given",
+            ));
+        }
+        let fault = Arc::new(Fault {
+            operation,
+            pattern: key.map(|key| key.as_bytes().to_vec()),
+            error,
+            latency: Duration::from_secs_f64(latency),
+            every,
+            times,
+            counts: Mutex::default(),
+        });
+        self.0.lock().unwrap().push(fault.clone());
+        Ok(PyFault {
+            fault,
+            faults: self.clone(),
+        })
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// A fault injected into the requests of a communicator, which stays until removed.
+#[pyclass(name = "Fault")]
+pub struct PyFault {
+    fault: Arc<Fault>,
+    faults: Faults,
+}
+
+#[pymethods]
+impl PyFault {
+    /// The number of requests which the fault was injected into.
+    #[getter]
+    fn injected(&self) -> u64 {
+        self.fault.counts.lock().unwrap().1
+    }
+
+    /// Stops injecting the fault.
+    fn remove(&self) {
+        let mut faults = self.faults.0.lock().unwrap();
+        faults.retain(|fault| !Arc::ptr_eq(fault, &self.fault));
+    }
+
+    pub fn __repr__(&self, py: Python) -> String {
+        let fault = &self.fault;
+        format!(
+            "Fault(operation={:?}, key={:?}, error={:?}, latency={}, every={}, times={:?}, injected={})",
+            fault.operation,
+            fault.pattern.as_deref().map(|pattern| PyBytes::new(py, pattern)),
+            fault.error,
+            fault.latency.as_secs_f64(),
+            fault.every,
+            fault.times,
+            self.injected()
+        )
+    }
+}
+
+/// Converts the error injected into a request into the exception it raises.
+pub fn injected_error(status: tonic::Status) -> PyErr {
+    PyClientError(etcd_client::Error::GRpcStatus(status)).into()
+}
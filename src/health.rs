@@ -0,0 +1,118 @@
+use etcd_client::Client as EtcdClient;
+use pyo3::prelude::*;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+use tokio::time::{sleep, timeout};
+
+use crate::client::PyConnectOptions;
+use crate::communicator::{connected, wait_closed};
+use crate::connection_state::{set_state, ConnectionState};
+use crate::error::InvalidArgsError;
+
+#[pyclass(get_all, name = "EndpointHealth")]
+#[derive(Clone, Debug)]
+pub struct PyEndpointHealth {
+    endpoint: String,
+    healthy: bool,
+    latency: f64,
+    error: Option<String>,
+}
+
+#[pymethods]
+impl PyEndpointHealth {
+    pub fn __repr__(&self) -> String {
+        format!(
+            "EndpointHealth(endpoint={:?}, healthy={}, latency={}, error={:?})",
+            self.endpoint, self.healthy, self.latency, self.error
+        )
+    }
+}
+
+/// Converts the `timeout` of a check in seconds, which must be positive.
+pub fn check_timeout(timeout: f64) -> PyResult<Duration> {
+    if !(timeout > 0.0 && timeout.is_finite()) {
+        return Err(InvalidArgsError::new_err(
+            "timeout must be a positive number of seconds",
+        ));
+    }
+    Ok(Duration::from_secs_f64(timeout))
+}
+
+impl PyEndpointHealth {
+    /// Connects to `endpoint` alone and requests its status, so that the result
+    /// reflects that single member instead of whichever one the balancer picks.
+    /// A member which does not answer within `limit` is reported as unhealthy,
+    /// as the options of the client may not bound the request.
+    pub async fn check(
+        endpoint: String,
+        connect_options: PyConnectOptions,
+        limit: Duration,
+    ) -> Self {
+        let started_at = Instant::now();
+        let status = async {
+            let mut client =
+                EtcdClient::connect([endpoint.clone()], Some(connect_options.inner)).await?;
+            client.status().await.map(|_| ())
+        };
+        let result = match timeout(limit, status).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => Err(format!("timed out after {limit:?}")),
+        };
+        let latency = started_at.elapsed().as_secs_f64();
+
+        Self {
+            endpoint,
+            healthy: result.is_ok(),
+            latency,
+            error: result.err(),
+        }
+    }
+}
+
+/// Requests the status of the cluster every `interval` through `client`, publishing
+/// whether it succeeded within `interval` to `healthy` and `state`,
+/// until the client is closed or dropped.
+pub fn spawn_health_check(
+    client: Weak<Mutex<Vec<EtcdClient>>>,
+    closed: watch::Receiver<bool>,
+    interval: f64,
+    healthy: watch::Sender<bool>,
+    state: Arc<watch::Sender<ConnectionState>>,
+) {
+    let interval = Duration::from_secs_f64(interval);
+    let check = async move {
+        loop {
+            // The client is not held in between, so that dropping it ends the checks.
+            let status = {
+                let Some(client) = client.upgrade() else {
+                    break;
+                };
+                let Ok(mut client) = connected(&client).await else {
+                    break;
+                };
+                timeout(interval, client.status()).await
+            };
+            let now_healthy = matches!(status, Ok(Ok(_)));
+            let was_healthy = healthy.send_replace(now_healthy);
+            if now_healthy {
+                set_state(&state, ConnectionState::Ready);
+            } else {
+                set_state(&state, ConnectionState::TransientFailure);
+            }
+            match status {
+                Ok(Ok(_)) if !was_healthy => log::info!("health check succeeded"),
+                Ok(Err(e)) if was_healthy => log::warn!("health check failed: {e}"),
+                Err(_) if was_healthy => log::warn!("health check timed out"),
+                _ => {}
+            }
+            sleep(interval).await;
+        }
+    };
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = check => {}
+            _ = wait_closed(closed) => {}
+        }
+    });
+}
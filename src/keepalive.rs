@@ -0,0 +1,164 @@
+use etcd_client::Client as EtcdClient;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use scopeguard::ScopeGuard;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::telemetry::sanitize_key;
+
+/// Keeps the lease `lease_id` alive every `interval` until it expires or a request to do so fails.
+pub async fn keep_lease_alive(
+    mut client: EtcdClient,
+    lease_id: i64,
+    interval: Duration,
+) -> Result<(), etcd_client::Error> {
+    let (mut lease_keeper, mut lease_stream) = client.lease_keep_alive(lease_id).await?;
+
+    loop {
+        sleep(interval).await;
+        lease_keeper.keep_alive().await?;
+        match lease_stream.message().await? {
+            Some(res) if res.ttl() > 0 => continue,
+            Some(_) => {
+                return Err(etcd_client::Error::LeaseKeepAliveError(
+                    "the lease expired".to_owned(),
+                ))
+            }
+            None => {
+                return Err(etcd_client::Error::LeaseKeepAliveError(
+                    "the keepalive stream ended".to_owned(),
+                ))
+            }
+        }
+    }
+}
+
+/// What `ConnectOptions.with_keepalive_callback()` is called with when a task keeping the lease
+/// of a lock, an election or a service instance alive in the background stops before its release.
+#[pyclass(get_all, name = "KeepAliveEvent")]
+#[derive(Clone, Debug)]
+pub struct PyKeepAliveEvent {
+    // "lock", "election", "service" or "presence".
+    kind: &'static str,
+    name: Py<PyBytes>,
+    lease_id: i64,
+    // Whether the task was dropped, as the runtime shut down, rather than failed.
+    aborted: bool,
+    error: Option<String>,
+}
+
+#[pymethods]
+impl PyKeepAliveEvent {
+    pub fn __repr__(&self, py: Python) -> String {
+        format!(
+            "KeepAliveEvent(kind={:?}, name={:?}, lease_id={}, aborted={}, error={:?})",
+            self.kind,
+            self.name.as_ref(py),
+            self.lease_id,
+            self.aborted,
+            self.error
+        )
+    }
+}
+
+/// What a keepalive task reports about, as no one awaits it to see it stop.
+struct KeepAliveReporter {
+    kind: &'static str,
+    name: Vec<u8>,
+    lease_id: i64,
+    callback: Option<PyObject>,
+    // Set as the lease is given up, after which the task stopping is expected.
+    released: Arc<AtomicBool>,
+}
+
+impl KeepAliveReporter {
+    fn report(&self, error: Option<String>) {
+        if self.released.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let name = sanitize_key(Some(&self.name));
+        match error {
+            Some(ref error) => log::error!(
+                "failed to keep alive the lease {} of the {} {name}: {error}",
+                self.lease_id,
+                self.kind
+            ),
+            None => log::error!(
+                "the keepalive of the lease {} of the {} {name} was aborted",
+                self.lease_id,
+                self.kind
+            ),
+        }
+
+        if let Some(ref callback) = self.callback {
+            Python::with_gil(|py| {
+                let event = PyKeepAliveEvent {
+                    kind: self.kind,
+                    name: PyBytes::new(py, &self.name).into(),
+                    lease_id: self.lease_id,
+                    aborted: error.is_none(),
+                    error,
+                };
+                if let Err(e) = callback.call1(py, (event,)) {
+                    log::warn!(
+                        "the keepalive callback failed for the {} {name}: {e}",
+                        self.kind
+                    );
+                }
+            });
+        }
+    }
+}
+
+/// A task keeping the lease of a lock, an election or a service instance alive in the background,
+/// which reports to the logger and the keepalive callback, if any, when it stops before `abort()`.
+pub struct KeepAliveTask {
+    handle: JoinHandle<()>,
+    released: Arc<AtomicBool>,
+}
+
+impl KeepAliveTask {
+    pub fn spawn<F>(
+        kind: &'static str,
+        name: Vec<u8>,
+        lease_id: i64,
+        callback: Option<PyObject>,
+        keep_alive: F,
+    ) -> Self
+    where
+        F: Future<Output = Result<(), etcd_client::Error>> + Send + 'static,
+    {
+        let released = Arc::new(AtomicBool::new(false));
+        let reporter = KeepAliveReporter {
+            kind,
+            name,
+            lease_id,
+            callback,
+            released: released.clone(),
+        };
+
+        let handle = tokio::spawn(async move {
+            // Dropping the task before it completes means it was aborted.
+            let reporter = scopeguard::guard(reporter, |reporter| reporter.report(None));
+            let result = keep_alive.await;
+            let reporter = ScopeGuard::into_inner(reporter);
+            if let Err(e) = result {
+                reporter.report(Some(e.to_string()));
+            }
+        });
+
+        Self { handle, released }
+    }
+
+    /// Stops the task as its lease is given up, which is not reported.
+    pub fn abort(&self) {
+        self.released.store(true, Ordering::Relaxed);
+        self.handle.abort();
+    }
+}
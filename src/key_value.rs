@@ -0,0 +1,95 @@
+use etcd_client::KeyValue;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::codec::Codec;
+use crate::text_decoding::TextDecoding;
+
+/// Selects what a key-value pair of a response converts to. The `bytes` or `str` are built
+/// straight from the response buffer when the result is handed over to Python, which holds
+/// the GIL anyway, rather than through an intermediate buffer built beforehand.
+pub enum KvBytes {
+    Key(KeyValue, TextDecoding),
+    Value(KeyValue, TextDecoding),
+    Pair(KeyValue, TextDecoding),
+    // Converted by a codec, which needs the GIL already.
+    Decoded(PyObject),
+}
+
+impl KvBytes {
+    pub fn key(kv: KeyValue, decoding: TextDecoding) -> PyResult<Self> {
+        decoding.check(kv.key())?;
+        Ok(KvBytes::Key(kv, decoding))
+    }
+
+    pub fn value(kv: KeyValue, decoding: TextDecoding, codec: Option<&Codec>) -> PyResult<Self> {
+        if let Some(codec) = codec {
+            return Python::with_gil(|py| Ok(KvBytes::Decoded(codec.decode(py, kv.value())?)));
+        }
+        decoding.check(kv.value())?;
+        Ok(KvBytes::Value(kv, decoding))
+    }
+
+    pub fn pair(kv: KeyValue, decoding: TextDecoding, codec: Option<&Codec>) -> PyResult<Self> {
+        decoding.check(kv.key())?;
+        if let Some(codec) = codec {
+            return Python::with_gil(|py| {
+                let key = decoding.decode(py, kv.key());
+                let value = codec.decode(py, kv.value())?;
+                Ok(KvBytes::Decoded((key, value).into_py(py)))
+            });
+        }
+        decoding.check(kv.value())?;
+        Ok(KvBytes::Pair(kv, decoding))
+    }
+}
+
+impl IntoPy<PyObject> for KvBytes {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            KvBytes::Key(kv, decoding) => decoding.decode(py, kv.key()),
+            KvBytes::Value(kv, decoding) => decoding.decode(py, kv.value()),
+            KvBytes::Pair(kv, decoding) => (
+                decoding.decode(py, kv.key()),
+                decoding.decode(py, kv.value()),
+            )
+                .into_py(py),
+            KvBytes::Decoded(object) => object,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "KeyValue")]
+pub struct PyKeyValue(pub KeyValue);
+
+#[pymethods]
+impl PyKeyValue {
+    pub fn key<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, self.0.key())
+    }
+
+    pub fn value<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, self.0.value())
+    }
+
+    pub fn create_revision(&self) -> i64 {
+        self.0.create_revision()
+    }
+
+    pub fn mod_revision(&self) -> i64 {
+        self.0.mod_revision()
+    }
+
+    pub fn version(&self) -> i64 {
+        self.0.version()
+    }
+
+    pub fn lease(&self) -> i64 {
+        self.0.lease()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
@@ -0,0 +1,162 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
+use scopeguard::ScopeGuard;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use tokio::time::sleep;
+
+use crate::client::PyClient;
+use crate::election_manager::EtcdElectionManager;
+use crate::error::TransportError;
+use crate::telemetry::sanitize_key;
+
+/// The coroutine run while leading, scheduled on the event loop of the caller,
+/// which is cancelled when dropped.
+struct LeaderTask {
+    // The `concurrent.futures.Future` of the coroutine.
+    future: PyObject,
+    done: oneshot::Receiver<()>,
+}
+
+impl LeaderTask {
+    fn start(py: Python, factory: &PyObject, event_loop: &PyObject) -> PyResult<Self> {
+        let coroutine = factory.call0(py)?;
+        let future = py
+            .import("asyncio")?
+            .call_method1("run_coroutine_threadsafe", (coroutine, event_loop))?;
+
+        let (sender, done) = oneshot::channel();
+        let sender = Mutex::new(Some(sender));
+        let on_done = PyCFunction::new_closure(
+            py,
+            None,
+            None,
+            move |_args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                if let Some(sender) = sender.lock().unwrap().take() {
+                    let _ = sender.send(());
+                }
+                Ok(())
+            },
+        )?;
+        future.call_method1("add_done_callback", (on_done,))?;
+        Ok(Self {
+            future: future.into(),
+            done,
+        })
+    }
+
+    /// Waits until the coroutine completes, returning its result or raising its error.
+    async fn wait(&mut self) -> PyResult<PyObject> {
+        let _ = (&mut self.done).await;
+        Python::with_gil(|py| self.future.call_method0(py, "result"))
+    }
+
+    fn cancel(&self) {
+        Python::with_gil(|py| {
+            let _ = self.future.call_method0(py, "cancel");
+        });
+    }
+}
+
+impl Drop for LeaderTask {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Cancels `task` and waits until it stops, so that it does not run alongside the one started
+/// once the leadership is won again.
+async fn stop(mut task: LeaderTask, election: &str) {
+    task.cancel();
+    let Err(e) = task.wait().await else {
+        return;
+    };
+    Python::with_gil(|py| {
+        let cancelled = py
+            .import("concurrent.futures")
+            .and_then(|futures| futures.getattr("CancelledError"));
+        if !cancelled.is_ok_and(|cancelled| e.is_instance(py, cancelled)) {
+            log::warn!(
+                "the task of the election {election} failed as its leadership was lost: {e}"
+            );
+        }
+    });
+}
+
+/// Whether campaigning failed with `error` for a reason which may pass, such as a lost
+/// connection, rather than e.g. for missing permissions, which campaigning again would not fix.
+fn can_campaign_again(error: &PyErr) -> bool {
+    Python::with_gil(|py| {
+        error.is_instance_of::<TransportError>(py)
+            || error
+                .value(py)
+                .getattr("retryable")
+                .and_then(|retryable| retryable.is_true())
+                .unwrap_or(false)
+    })
+}
+
+/// Campaigns on the election `name` with `value`, runs the coroutine returned by `factory` on
+/// `event_loop` while leading, and campaigns again after cancelling it if the leadership is
+/// lost, until it completes. Resigns and returns its result or raises its error once it does.
+/// Campaigning is retried with backoff only after retryable errors, and others are raised.
+pub async fn run_as_leader(
+    client: PyClient,
+    name: Vec<u8>,
+    value: Vec<u8>,
+    ttl: i64,
+    factory: PyObject,
+    event_loop: PyObject,
+) -> PyResult<PyObject> {
+    let reconnect = client.connect_options.reconnect;
+    let election = sanitize_key(Some(&name));
+    let mut attempts = 0;
+    loop {
+        let mut manager =
+            EtcdElectionManager::new(client.clone(), name.clone(), value.clone(), ttl, None);
+        let leadership = match manager.handle_aenter().await {
+            Ok(leadership) => leadership,
+            Err(e) if can_campaign_again(&e) => {
+                let backoff = reconnect.backoff(attempts);
+                attempts += 1;
+                log::warn!("campaigning on the election {election} again in {backoff:?}: {e}");
+                sleep(backoff).await;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        attempts = 0;
+
+        // Cancelling the runner while leading drops this future, which must resign.
+        let mut manager = scopeguard::guard(manager, |mut manager| {
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                runtime.spawn(async move {
+                    let _ = manager.handle_aexit().await;
+                });
+            }
+        });
+
+        let result = match Python::with_gil(|py| LeaderTask::start(py, &factory, &event_loop)) {
+            Ok(mut task) => tokio::select! {
+                result = task.wait() => Some(result),
+                _ = leadership.lost._wait() => {
+                    stop(task, &election).await;
+                    None
+                }
+            },
+            Err(e) => Some(Err(e)),
+        };
+
+        let resigned = manager.handle_aexit().await;
+        ScopeGuard::into_inner(manager);
+        match result {
+            Some(result) => {
+                if let Err(e) = resigned {
+                    log::warn!("failed to resign from the election {election}: {e}");
+                }
+                return result;
+            }
+            None => log::warn!("lost the leadership of the election {election}, campaigning again"),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use etcd_client::LeaseGrantResponse;
+use pyo3::prelude::*;
+
+use crate::response_header::PyResponseHeader;
+
+#[derive(Clone)]
+#[pyclass(name = "LeaseGrantResponse")]
+pub struct PyLeaseGrantResponse(pub LeaseGrantResponse);
+
+#[pymethods]
+impl PyLeaseGrantResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn id(&self) -> i64 {
+        self.0.id()
+    }
+
+    pub fn ttl(&self) -> i64 {
+        self.0.ttl()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
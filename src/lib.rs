@@ -1,52 +1,243 @@
+#![allow(non_local_definitions)]
+
+mod audit;
+mod auth;
+mod auth_response;
+mod bench;
+mod changefeed;
+mod circuit_breaker;
 mod client;
+mod cluster_info;
+mod cluster_response;
+mod codec;
 mod communicator;
 mod compare;
 mod condvar;
+mod config_watcher;
+mod connection_state;
+mod connection_url;
+mod discovery;
+mod election;
+mod election_manager;
+mod election_response;
 mod error;
+mod export;
+mod fault;
+mod health;
+mod keepalive;
+mod key_value;
+mod leader_task;
+mod lease_response;
+mod load_balancing;
 mod lock_manager;
+mod maintenance;
+mod maintenance_response;
+mod mapping;
+mod member;
+mod metrics;
+mod migration;
+mod mirror;
+mod observe;
+mod presence;
+mod py_enum;
+mod recipe;
+mod reconnect;
+mod recording;
+mod response_header;
+mod runtime;
+mod schema;
+mod services;
+mod snapshot;
+mod telemetry;
+mod testing;
+mod text_decoding;
 mod txn;
 mod txn_response;
 mod watch;
 mod watch_event;
 mod watch_event_stream;
+mod writer;
 
+use audit::PyAuditEvent;
+use auth::{PyPermission, PyPermissionType};
+use auth_response::{
+    PyAuthenticateResponse, PyRoleAddResponse, PyRoleDeleteResponse, PyRoleGetResponse,
+    PyRoleGrantPermissionResponse, PyRoleListResponse, PyRoleRevokePermissionResponse,
+    PyUserChangePasswordResponse, PyUserGrantRoleResponse, PyUserRevokeRoleResponse,
+};
+use bench::{PyBenchResult, PyWorkload};
+use changefeed::{PyChangefeed, PyChangefeedEvent};
 use client::{PyClient, PyConnectOptions};
+use cluster_info::{PyClusterInfo, PyMemberInfo};
+use cluster_response::{
+    PyMemberAddResponse, PyMemberListResponse, PyMemberRemoveResponse, PyMemberUpdateResponse,
+};
 use communicator::PyCommunicator;
-use compare::{PyCompare, PyCompareOp};
+use compare::{PyCompare, COMPARE_OP};
 use condvar::PyCondVar;
+use config_watcher::PyConfigWatcher;
+use connection_state::{PyConnectionStateChanges, CONNECTION_STATE};
+use election::PyLeaderKey;
+use election_manager::{PyElection, PyLeadership};
+use election_response::{
+    PyCampaignResponse, PyLeaderResponse, PyProclaimResponse, PyResignResponse,
+};
 use error::{
-    ClientError, ElectError, EndpointError, GRPCStatusError, InvalidArgsError,
-    InvalidHeaderValueError, InvalidUriError, IoError, LeaseKeepAliveError, PyGRPCStatusCode,
-    TransportError, Utf8Error, WatchError,
+    AlreadyExistsError, CircuitOpenError, ClientError, CompactedError, ConflictError,
+    ConnectionLostError, DeadlineExceededError, ElectError, EndpointError, GRPCStatusError,
+    InvalidArgsError, InvalidHeaderValueError, InvalidUriError, IoError, LeaseKeepAliveError,
+    LockError, NotFoundError, PermissionDeniedError, PyGRPCStatusCode, ReplayMismatchError,
+    RequestTimeoutError, SchemaError, TransportError, UnavailableError, Utf8Error, WatchError,
 };
+use fault::PyFault;
+use health::PyEndpointHealth;
+use keepalive::PyKeepAliveEvent;
+use key_value::PyKeyValue;
+use lease_response::PyLeaseGrantResponse;
+use load_balancing::PyLoadBalancing;
 use lock_manager::PyEtcdLockOption;
+use maintenance::{PyAlarmAction, PyAlarmType};
+use maintenance_response::{
+    PyAlarmMember, PyAlarmResponse, PyDefragmentResponse, PyHashKvResponse, PySnapshotResponse,
+    PyStatusResponse,
+};
+use mapping::PyPrefixMapping;
+use member::PyMember;
+use mirror::PyMirror;
+use observe::PyObserve;
+use presence::{PyMembers, PyPresence};
 use pyo3::prelude::*;
+use recording::{PyRecordingCommunicator, PyReplayCommunicator};
+use response_header::PyResponseHeader;
+use schema::PySchema;
+use services::{PyServiceDiscovery, PyServiceRegistry};
+use snapshot::PySnapshot;
+use testing::PyEtcdServer;
+use text_decoding::PyTextDecoding;
 use txn::{PyTxn, PyTxnOp};
 use txn_response::PyTxnResponse;
 use watch::PyWatch;
-use watch_event::{PyWatchEvent, PyWatchEventType};
+use watch_event::{PyWatchEvent, WATCH_EVENT_TYPE};
+use writer::PyPutWriter;
 
+// TODO: Declare support for free-threaded CPython 3.13t with `#[pymodule(gil_used = false)]`,
+// which needs PyO3 0.23 or later. State shared across threads is already kept in `Arc`s with
+// Rust locks rather than relying on the GIL, but the `&PyAny` GIL refs used throughout
+// have to be ported to `Bound` first.
+// TODO: Support sub-interpreters once PyO3 supports per-interpreter module state.
+// Until then, PyO3 raises `ImportError` when another interpreter imports the module,
+// which keeps the runtime, the logger and the shutdown state here to the first one.
 #[pymodule]
 fn etcd_client(py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(runtime::cleanup_runtime, module)?)?;
+    module.add_function(wrap_pyfunction!(runtime::configure_shutdown, module)?)?;
+    module.add_function(wrap_pyfunction!(recipe::replay, module)?)?;
+    module.add_function(wrap_pyfunction!(metrics::metrics_snapshot, module)?)?;
+    module.add_function(wrap_pyfunction!(bench::bench, module)?)?;
+    // Cheap, unlike starting the runtime, and done here for `atexit.unregister()` to opt out.
+    py.import("atexit")?
+        .call_method1("register", (module.getattr("_cleanup_runtime")?,))?;
+
     module.add_class::<PyClient>()?;
     module.add_class::<PyConnectOptions>()?;
+    module.add_class::<PyLoadBalancing>()?;
+    module.add_class::<PyTextDecoding>()?;
     module.add_class::<PyCommunicator>()?;
+    module.add_class::<PyEndpointHealth>()?;
+    module.add_class::<PyAuditEvent>()?;
+    module.add("ConnectionState", CONNECTION_STATE.class(py)?)?;
+    module.add_class::<PyConnectionStateChanges>()?;
 
     module.add_class::<PyWatch>()?;
     module.add_class::<PyWatchEvent>()?;
-    module.add_class::<PyWatchEventType>()?;
+    module.add("WatchEventType", WATCH_EVENT_TYPE.class(py)?)?;
 
     module.add_class::<PyCondVar>()?;
     module.add_class::<PyCompare>()?;
-    module.add_class::<PyCompareOp>()?;
+    module.add("CompareOp", COMPARE_OP.class(py)?)?;
 
     module.add_class::<PyTxn>()?;
     module.add_class::<PyTxnOp>()?;
     module.add_class::<PyTxnResponse>()?;
+    module.add_class::<PyPutWriter>()?;
+    module.add_class::<PyPrefixMapping>()?;
+    module.add_class::<PySchema>()?;
+    module.add_class::<PyMirror>()?;
+    module.add_class::<PyConfigWatcher>()?;
+    module.add_class::<PyServiceRegistry>()?;
+    module.add_class::<PyServiceDiscovery>()?;
+    module.add_class::<PyPresence>()?;
+    module.add_class::<PyMembers>()?;
+    module.add_class::<PyChangefeed>()?;
+    module.add_class::<PyChangefeedEvent>()?;
+    module.add_class::<PyRecordingCommunicator>()?;
+    module.add_class::<PyFault>()?;
+    module.add_class::<PyReplayCommunicator>()?;
+    module.add_class::<PyWorkload>()?;
+    module.add_class::<PyBenchResult>()?;
     module.add_class::<PyEtcdLockOption>()?;
 
+    module.add_class::<PyResponseHeader>()?;
+    module.add_class::<PyKeyValue>()?;
+    module.add_class::<PyLeaseGrantResponse>()?;
+
+    module.add_class::<PyLeaderKey>()?;
+    module.add_class::<PyCampaignResponse>()?;
+    module.add_class::<PyProclaimResponse>()?;
+    module.add_class::<PyLeaderResponse>()?;
+    module.add_class::<PyResignResponse>()?;
+    module.add_class::<PyObserve>()?;
+    module.add_class::<PyElection>()?;
+    module.add_class::<PyLeadership>()?;
+    module.add_class::<PyKeepAliveEvent>()?;
+
+    module.add_class::<PyAuthenticateResponse>()?;
+    module.add_class::<PyUserChangePasswordResponse>()?;
+    module.add_class::<PyUserGrantRoleResponse>()?;
+    module.add_class::<PyUserRevokeRoleResponse>()?;
+    module.add_class::<PyPermissionType>()?;
+    module.add_class::<PyPermission>()?;
+    module.add_class::<PyRoleAddResponse>()?;
+    module.add_class::<PyRoleGetResponse>()?;
+    module.add_class::<PyRoleListResponse>()?;
+    module.add_class::<PyRoleGrantPermissionResponse>()?;
+    module.add_class::<PyRoleRevokePermissionResponse>()?;
+    module.add_class::<PyRoleDeleteResponse>()?;
+
+    module.add_class::<PyMember>()?;
+    module.add_class::<PyMemberListResponse>()?;
+    module.add_class::<PyMemberAddResponse>()?;
+    module.add_class::<PyMemberRemoveResponse>()?;
+    module.add_class::<PyMemberUpdateResponse>()?;
+    module.add_class::<PyMemberInfo>()?;
+    module.add_class::<PyClusterInfo>()?;
+
+    module.add_class::<PyStatusResponse>()?;
+    module.add_class::<PyAlarmAction>()?;
+    module.add_class::<PyAlarmType>()?;
+    module.add_class::<PyAlarmMember>()?;
+    module.add_class::<PyAlarmResponse>()?;
+    module.add_class::<PyDefragmentResponse>()?;
+    module.add_class::<PyHashKvResponse>()?;
+    module.add_class::<PySnapshot>()?;
+    module.add_class::<PySnapshotResponse>()?;
+
     module.add_class::<PyGRPCStatusCode>()?;
 
+    let testing = PyModule::new(py, "testing")?;
+    testing.add_class::<PyEtcdServer>()?;
+    module.add_submodule(testing)?;
+    // `import etcd_client.testing` looks the submodule up there, as the extension is no package.
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item("etcd_client.testing", testing)?;
+
+    // Overridden by the errors of requests which can be retried.
+    py.get_type::<ClientError>().setattr("retryable", false)?;
+    // Overridden by the errors of requests, which tell what failed.
+    for context in ["operation", "key", "endpoint"] {
+        py.get_type::<ClientError>().setattr(context, py.None())?;
+    }
     module.add("ClientError", py.get_type::<ClientError>())?;
     module.add("GRPCStatusError", py.get_type::<GRPCStatusError>())?;
     module.add("InvalidArgsError", py.get_type::<InvalidArgsError>())?;
@@ -54,6 +245,7 @@ fn etcd_client(py: Python, module: &PyModule) -> PyResult<()> {
     module.add("InvalidUriError", py.get_type::<InvalidUriError>())?;
     module.add("TransportError", py.get_type::<TransportError>())?;
     module.add("WatchError", py.get_type::<WatchError>())?;
+    module.add("ConnectionLostError", py.get_type::<ConnectionLostError>())?;
     module.add("Utf8Error", py.get_type::<Utf8Error>())?;
     module.add("LeaseKeepAliveError", py.get_type::<LeaseKeepAliveError>())?;
     module.add("ElectError", py.get_type::<ElectError>())?;
@@ -62,5 +254,28 @@ fn etcd_client(py: Python, module: &PyModule) -> PyResult<()> {
         py.get_type::<InvalidHeaderValueError>(),
     )?;
     module.add("EndpointError", py.get_type::<EndpointError>())?;
+    module.add("LockError", py.get_type::<LockError>())?;
+    module.add("CircuitOpenError", py.get_type::<CircuitOpenError>())?;
+    module.add("NotFoundError", py.get_type::<NotFoundError>())?;
+    module.add("AlreadyExistsError", py.get_type::<AlreadyExistsError>())?;
+    module.add(
+        "PermissionDeniedError",
+        py.get_type::<PermissionDeniedError>(),
+    )?;
+    module.add("UnavailableError", py.get_type::<UnavailableError>())?;
+    module.add(
+        "DeadlineExceededError",
+        py.get_type::<DeadlineExceededError>(),
+    )?;
+    // Overridden by watches canceled by etcd, which tell the compaction point.
+    py.get_type::<CompactedError>()
+        .setattr("compact_revision", py.None())?;
+    module.add("CompactedError", py.get_type::<CompactedError>())?;
+    module.add("RequestTimeoutError", py.get_type::<RequestTimeoutError>())?;
+    module.add("ConflictError", py.get_type::<ConflictError>())?;
+    module.add("ReplayMismatchError", py.get_type::<ReplayMismatchError>())?;
+    // Overridden by the errors of writes, which tell the key rejected.
+    py.get_type::<SchemaError>().setattr("path", py.None())?;
+    module.add("SchemaError", py.get_type::<SchemaError>())?;
     Ok(())
 }
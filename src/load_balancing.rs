@@ -0,0 +1,45 @@
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp as PyO3CompareOp;
+
+use crate::recipe::reduce_classattr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadBalancing {
+    RoundRobin,
+    Pinned,
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(name = "LoadBalancing")]
+pub struct PyLoadBalancing(pub LoadBalancing);
+
+#[pymethods]
+impl PyLoadBalancing {
+    #[classattr]
+    const ROUND_ROBIN: Self = Self(LoadBalancing::RoundRobin);
+    #[classattr]
+    const PINNED: Self = Self(LoadBalancing::Pinned);
+
+    pub fn __repr__(&self) -> String {
+        match self.0 {
+            LoadBalancing::RoundRobin => "LoadBalancing.ROUND_ROBIN".to_owned(),
+            LoadBalancing::Pinned => "LoadBalancing.PINNED".to_owned(),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, rhs: &PyLoadBalancing, op: PyO3CompareOp) -> PyObject {
+        match op {
+            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
+            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        let name = match self.0 {
+            LoadBalancing::RoundRobin => "ROUND_ROBIN",
+            LoadBalancing::Pinned => "PINNED",
+        };
+        reduce_classattr(py, py.get_type::<Self>(), name)
+    }
+}
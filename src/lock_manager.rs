@@ -2,12 +2,15 @@ use crate::{
     client::PyClient,
     communicator::PyCommunicator,
     error::{GRPCStatusError, LockError, PyClientError},
+    keepalive::{keep_lease_alive, KeepAliveTask},
 };
-use etcd_client::{Client as EtcdClient, LockOptions};
+use etcd_client::{Client as EtcdClient, LockOptions, LockResponse};
 
 use pyo3::{prelude::*, types::PyBytes};
-use std::{future::ready, time::Duration};
-use tokio::time::{sleep, timeout};
+use scopeguard::ScopeGuard;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::{task::JoinHandle, time::timeout};
 
 #[derive(Debug, Clone)]
 #[pyclass(get_all, set_all, name = "EtcdLockOption")]
@@ -35,6 +38,45 @@ impl PyEtcdLockOption {
             self.lock_name, self.timeout, self.ttl
         ))
     }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        let args = (PyBytes::new(py, &self.lock_name), self.timeout, self.ttl);
+        Ok((py.get_type::<Self>().into(), args.into_py(py)))
+    }
+}
+
+/// Releases the lock which the pending `lock_task` may still acquire, without waiting for it.
+fn release_in_background(
+    mut client: EtcdClient,
+    lock_task: JoinHandle<Result<LockResponse, etcd_client::Error>>,
+    lease_id: Option<i64>,
+) {
+    // Outside of the runtime, there is nothing left to release the lock with.
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    runtime.spawn(async move {
+        match lease_id {
+            // Revoking the lease deletes any lock key attached to it.
+            Some(lease_id) => {
+                lock_task.abort();
+                let _ = client.lease_revoke(lease_id).await;
+            }
+            None => {
+                if let Ok(Ok(lock_res)) = lock_task.await {
+                    let _ = client.unlock(lock_res.key().to_vec()).await;
+                }
+            }
+        }
+    });
+}
+
+/// What is known of the lock while it is held, readable without waiting for the lock manager,
+/// which stays locked while acquiring or releasing the lock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HeldLock {
+    pub fencing_token: Option<i64>,
+    pub lease_id: Option<i64>,
 }
 
 pub struct EtcdLockManager {
@@ -43,8 +85,9 @@ pub struct EtcdLockManager {
     pub ttl: Option<i64>,
     pub timeout_seconds: Option<f64>,
     pub lock_id: Option<Vec<u8>>,
+    pub held: Arc<Mutex<HeldLock>>,
     pub lease_id: Option<i64>,
-    pub lease_keepalive_task: Option<tokio::task::JoinHandle<Result<(), PyClientError>>>,
+    pub lease_keepalive_task: Option<KeepAliveTask>,
 }
 
 impl EtcdLockManager {
@@ -55,30 +98,72 @@ impl EtcdLockManager {
             ttl: lock_opt.ttl,
             timeout_seconds: lock_opt.timeout,
             lock_id: None,
+            held: Arc::default(),
             lease_id: None,
             lease_keepalive_task: None,
         }
     }
 
-    async fn try_lock(&mut self, client: &mut EtcdClient) -> Result<(), PyClientError> {
+    fn spawn_lock_request(
+        &self,
+        client: &EtcdClient,
+    ) -> JoinHandle<Result<LockResponse, etcd_client::Error>> {
+        let mut client = client.clone();
+        let lock_name = self.lock_name.clone();
         let lock_req_options = self
             .lease_id
             .map(|lease_id| LockOptions::new().with_lease(lease_id));
 
-        let lock_res = client
-            .lock(self.lock_name.clone(), lock_req_options)
-            .await
-            .map_err(PyClientError)?;
+        // The request runs in its own task so that it can outlive an acquisition timeout,
+        // and the lock it may still grab on the server side can be released afterwards.
+        tokio::spawn(async move { client.lock(lock_name, lock_req_options).await })
+    }
+
+    async fn set_acquired(
+        &mut self,
+        client: &mut EtcdClient,
+        lock_res: LockResponse,
+    ) -> Result<(), PyClientError> {
+        let lock_id = lock_res.key().to_vec();
+        self.lock_id = Some(lock_id.clone());
+
+        // The create revision of the lock key only ever grows across lock holders,
+        // so it can be handed out as a fencing token. The revision of the lock response
+        // cannot, as etcd answers a contended lock with the revision it found it free at.
+        let get_res = client.get(lock_id, None).await.map_err(PyClientError)?;
+        *self.held.lock().unwrap() = HeldLock {
+            fencing_token: get_res.kvs().first().map(|kv| kv.create_revision()),
+            lease_id: self.lease_id,
+        };
+        Ok(())
+    }
+
+    /// Releases the lock, revoking its lease if it has one, which deletes the lock key,
+    /// or else deleting the lock key.
+    async fn release(&mut self, client: &mut EtcdClient, lock_id: Vec<u8>) -> PyResult<()> {
+        if let Some(ref lease_keepalive_task) = self.lease_keepalive_task {
+            lease_keepalive_task.abort();
+        }
 
-        self.lock_id = Some(lock_res.key().to_vec());
+        if let Some(lease_id) = self.lease_id {
+            if let Err(etcd_client::Error::GRpcStatus(status)) = client.lease_revoke(lease_id).await
+            {
+                if status.code() != tonic::Code::NotFound {
+                    return Err(GRPCStatusError::new_err(status.to_string()));
+                }
+            }
+        } else {
+            client.unlock(lock_id).await.map_err(PyClientError)?;
+        }
+
+        self.lock_id = None;
+        *self.held.lock().unwrap() = HeldLock::default();
+        self.lease_id = None;
         Ok(())
     }
 
     pub async fn handle_aenter(&mut self) -> PyResult<PyCommunicator> {
-        let client = self.client.clone();
-        let mut client = EtcdClient::connect(client.endpoints, Some(client.connect_options.0))
-            .await
-            .map_err(PyClientError)?;
+        let (mut client, endpoints) = self.client.connect_etcd_client().await?;
 
         let mut self_ = scopeguard::guard(self, |self_| {
             if let Some(ref lease_keepalive_task) = self_.lease_keepalive_task {
@@ -91,49 +176,75 @@ impl EtcdLockManager {
                 let lease_grant_res = client.lease_grant(ttl, None).await.map_err(PyClientError)?;
                 let lease_id = lease_grant_res.id();
 
-                let mut client_to_move = client.clone();
-
-                self_.lease_keepalive_task = Some(tokio::spawn(async move {
-                    let (mut lease_keeper, _lease_stream) = client_to_move
-                        .lease_keep_alive(lease_id)
-                        .await
-                        .map_err(PyClientError)?;
-
-                    loop {
-                        sleep(Duration::from_secs_f64((ttl as f64) / 10.0)).await;
-                        lease_keeper.keep_alive().await.map_err(PyClientError)?;
-                    }
-                }));
+                self_.lease_keepalive_task = Some(KeepAliveTask::spawn(
+                    "lock",
+                    self_.lock_name.clone(),
+                    lease_id,
+                    self_.client.connect_options.keepalive_callback.clone(),
+                    keep_lease_alive(
+                        client.clone(),
+                        lease_id,
+                        Duration::from_secs_f64((ttl as f64) / 10.0),
+                    ),
+                ));
 
                 Some(lease_id)
             }
             None => None,
         };
 
-        let timeout_result: Result<Result<(), PyClientError>, tokio::time::error::Elapsed> =
-            match self_.timeout_seconds {
-                Some(seconds) => {
-                    timeout(
-                        Duration::from_secs_f64(seconds),
-                        self_.try_lock(&mut client),
-                    )
-                    .await
-                }
-                None => ready(Ok(self_.try_lock(&mut client).await)).await,
-            };
+        // Cancelling the acquisition drops this future, which must not leave the request
+        // running on its own, as the lock it would get would never be released.
+        let mut lock_task = scopeguard::guard(self_.spawn_lock_request(&client), {
+            let client = client.clone();
+            let lease_id = self_.lease_id;
+            move |lock_task| release_in_background(client, lock_task, lease_id)
+        });
+
+        let timeout_result = match self_.timeout_seconds {
+            Some(seconds) => timeout(Duration::from_secs_f64(seconds), &mut *lock_task).await,
+            None => Ok((&mut *lock_task).await),
+        };
+        let lock_task = ScopeGuard::into_inner(lock_task);
 
         match timeout_result {
-            Ok(Ok(_)) => Ok(PyCommunicator::new(client)),
-            Ok(Err(try_lock_err)) => Err(try_lock_err.into()),
+            Ok(join_result) => {
+                let lock_res = join_result
+                    .map_err(|join_err| LockError::new_err(join_err.to_string()))?
+                    .map_err(PyClientError)?;
+                let lock_id = lock_res.key().to_vec();
+                let acquired = match self_.set_acquired(&mut client, lock_res).await {
+                    Ok(()) => self_.client.communicator(client.clone(), endpoints).await,
+                    Err(e) => Err(e.into()),
+                };
+                match acquired {
+                    Ok(communicator) => Ok(communicator),
+                    Err(e) => {
+                        // `__aexit__` is not called when `__aenter__` raises,
+                        // so the lock would otherwise stay held.
+                        if let Err(release_err) = self_.release(&mut client, lock_id).await {
+                            log::warn!("failed to release the lock after {e}: {release_err}");
+                        }
+                        Err(e)
+                    }
+                }
+            }
             Err(timedout_err) => {
-                if let Some(lease_id) = self_.lease_id {
-                    if let Err(etcd_client::Error::GRpcStatus(status)) =
-                        client.lease_revoke(lease_id).await
-                    {
-                        if status.code() != tonic::Code::NotFound {
-                            return Err(GRPCStatusError::new_err(status.to_string()));
+                match self_.lease_id {
+                    // Revoking the lease deletes any lock key attached to it.
+                    Some(lease_id) => {
+                        lock_task.abort();
+                        if let Err(etcd_client::Error::GRpcStatus(status)) =
+                            client.lease_revoke(lease_id).await
+                        {
+                            if status.code() != tonic::Code::NotFound {
+                                return Err(GRPCStatusError::new_err(status.to_string()));
+                            }
                         }
                     }
+                    // Without a lease there is nothing to revoke, so wait for the pending
+                    // request in the background and release the lock if it gets acquired.
+                    None => release_in_background(client.clone(), lock_task, None),
                 }
                 Err(LockError::new_err(timedout_err.to_string()))
             }
@@ -141,42 +252,13 @@ impl EtcdLockManager {
     }
 
     pub async fn handle_aexit(&mut self) -> PyResult<()> {
-        let client = self.client.clone();
-        let mut client = EtcdClient::connect(client.endpoints, Some(client.connect_options.0))
-            .await
-            .map_err(PyClientError)?;
-
-        match self.lock_id {
-            None => {
-                return Err(LockError::new_err(
-                    "Attempting to release EtcdLock before it has been acquired!".to_string(),
-                ));
-            }
-            Some(ref lock_id) => {
-                if let Some(ref lease_keepalive_task) = self.lease_keepalive_task {
-                    lease_keepalive_task.abort();
-                }
+        let (mut client, _) = self.client.connect_etcd_client().await?;
 
-                if let Some(lease_id) = self.lease_id {
-                    if let Err(etcd_client::Error::GRpcStatus(status)) =
-                        client.lease_revoke(lease_id).await
-                    {
-                        if status.code() != tonic::Code::NotFound {
-                            return Err(GRPCStatusError::new_err(status.to_string()));
-                        }
-                    }
-                } else {
-                    client
-                        .unlock(lock_id.to_owned())
-                        .await
-                        .map_err(PyClientError)?;
-                }
-            }
+        match self.lock_id.clone() {
+            None => Err(LockError::new_err(
+                "Attempting to release EtcdLock before it has been acquired!".to_string(),
+            )),
+            Some(lock_id) => self.release(&mut client, lock_id).await,
         }
-
-        self.lock_id = None;
-        self.lease_id = None;
-
-        Ok(())
     }
 }
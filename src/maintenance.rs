@@ -0,0 +1,63 @@
+use etcd_client::{AlarmAction, AlarmType};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp as PyO3CompareOp;
+
+#[derive(Clone)]
+#[pyclass(name = "AlarmAction")]
+pub struct PyAlarmAction(pub AlarmAction);
+
+#[pymethods]
+impl PyAlarmAction {
+    #[classattr]
+    const GET: Self = Self(AlarmAction::Get);
+    #[classattr]
+    const ACTIVATE: Self = Self(AlarmAction::Activate);
+    #[classattr]
+    const DEACTIVATE: Self = Self(AlarmAction::Deactivate);
+
+    pub fn __repr__(&self) -> String {
+        match self.0 {
+            AlarmAction::Get => "AlarmAction.GET".to_owned(),
+            AlarmAction::Activate => "AlarmAction.ACTIVATE".to_owned(),
+            AlarmAction::Deactivate => "AlarmAction.DEACTIVATE".to_owned(),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, rhs: &PyAlarmAction, op: PyO3CompareOp) -> PyObject {
+        match op {
+            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
+            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "AlarmType")]
+pub struct PyAlarmType(pub AlarmType);
+
+#[pymethods]
+impl PyAlarmType {
+    #[classattr]
+    const NONE: Self = Self(AlarmType::None);
+    #[classattr]
+    const NOSPACE: Self = Self(AlarmType::Nospace);
+    #[classattr]
+    const CORRUPT: Self = Self(AlarmType::Corrupt);
+
+    pub fn __repr__(&self) -> String {
+        match self.0 {
+            AlarmType::None => "AlarmType.NONE".to_owned(),
+            AlarmType::Nospace => "AlarmType.NOSPACE".to_owned(),
+            AlarmType::Corrupt => "AlarmType.CORRUPT".to_owned(),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, rhs: &PyAlarmType, op: PyO3CompareOp) -> PyObject {
+        match op {
+            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
+            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
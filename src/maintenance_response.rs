@@ -0,0 +1,171 @@
+use etcd_client::{
+    AlarmMember, AlarmResponse, DefragmentResponse, HashKvResponse, SnapshotResponse,
+    StatusResponse,
+};
+use pyo3::{prelude::*, types::PyBytes};
+
+use crate::maintenance::PyAlarmType;
+use crate::response_header::PyResponseHeader;
+
+#[derive(Clone)]
+#[pyclass(name = "AlarmMember")]
+pub struct PyAlarmMember(pub AlarmMember);
+
+#[pymethods]
+impl PyAlarmMember {
+    pub fn member_id(&self) -> u64 {
+        self.0.member_id()
+    }
+
+    pub fn alarm(&self) -> PyAlarmType {
+        PyAlarmType(self.0.alarm())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "AlarmMember(member_id={}, alarm={})",
+            self.0.member_id(),
+            self.alarm().__repr__()
+        )
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "AlarmResponse")]
+pub struct PyAlarmResponse(pub AlarmResponse);
+
+#[pymethods]
+impl PyAlarmResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn alarms(&self) -> Vec<PyAlarmMember> {
+        self.0.alarms().iter().cloned().map(PyAlarmMember).collect()
+    }
+
+    pub fn __repr__(&self) -> String {
+        let alarms = self
+            .alarms()
+            .iter()
+            .map(|alarm| alarm.__repr__())
+            .collect::<Vec<_>>();
+        format!("AlarmResponse(alarms=[{}])", alarms.join(", "))
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "StatusResponse")]
+pub struct PyStatusResponse(pub StatusResponse);
+
+#[pymethods]
+impl PyStatusResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn version(&self) -> String {
+        self.0.version().to_owned()
+    }
+
+    pub fn db_size(&self) -> i64 {
+        self.0.db_size()
+    }
+
+    pub fn leader(&self) -> u64 {
+        self.0.leader()
+    }
+
+    pub fn raft_index(&self) -> u64 {
+        self.0.raft_index()
+    }
+
+    pub fn raft_term(&self) -> u64 {
+        self.0.raft_term()
+    }
+
+    pub fn raft_applied_index(&self) -> u64 {
+        self.0.raft_applied_index()
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        self.0.errors().to_vec()
+    }
+
+    pub fn raft_used_db_size(&self) -> i64 {
+        self.0.raft_used_db_size()
+    }
+
+    pub fn is_learner(&self) -> bool {
+        self.0.is_learner()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "DefragmentResponse")]
+pub struct PyDefragmentResponse(pub DefragmentResponse);
+
+#[pymethods]
+impl PyDefragmentResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "HashKvResponse")]
+pub struct PyHashKvResponse(pub HashKvResponse);
+
+#[pymethods]
+impl PyHashKvResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn hash(&self) -> u32 {
+        self.0.hash()
+    }
+
+    pub fn compact_revision(&self) -> i64 {
+        self.0.compact_version()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass(name = "SnapshotResponse")]
+pub struct PySnapshotResponse(pub SnapshotResponse);
+
+#[pymethods]
+impl PySnapshotResponse {
+    pub fn header(&self) -> Option<PyResponseHeader> {
+        self.0.header().cloned().map(PyResponseHeader)
+    }
+
+    pub fn remaining_bytes(&self) -> u64 {
+        self.0.remaining_bytes()
+    }
+
+    pub fn blob<'a>(&self, py: Python<'a>) -> &'a PyBytes {
+        PyBytes::new(py, self.0.blob())
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "SnapshotResponse(remaining_bytes={}, blob=<{} bytes>)",
+            self.0.remaining_bytes(),
+            self.0.blob().len()
+        )
+    }
+}
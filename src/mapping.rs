@@ -0,0 +1,226 @@
+use etcd_client::{DeleteOptions, GetOptions, PutOptions, Txn, TxnOp};
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::codec::encode_value;
+use crate::communicator::PyCommunicator;
+use crate::key_value::KvBytes;
+use crate::runtime::future_into_py;
+use crate::schema::{flatten, PySchema};
+use crate::text_decoding::TextDecoding;
+
+/// Reads and writes the keys under `prefix` like a dict whose keys leave out the prefix,
+/// with the text decoding and codec of the communicator.
+/// Writes are checked against the schema if any.
+#[pyclass(name = "PrefixMapping")]
+pub struct PyPrefixMapping {
+    communicator: Py<PyCommunicator>,
+    prefix: Vec<u8>,
+    schema: Option<Py<PySchema>>,
+}
+
+#[pymethods]
+impl PyPrefixMapping {
+    #[getter]
+    fn prefix<'a>(&self, py: Python<'a>) -> &'a PyBytes {
+        PyBytes::new(py, &self.prefix)
+    }
+
+    /// Returns the value of `key`, or `default` if it does not exist.
+    #[pyo3(signature = (key, default=None))]
+    fn aget<'a>(
+        &self,
+        py: Python<'a>,
+        key: &PyBytes,
+        default: Option<PyObject>,
+    ) -> PyResult<&'a PyAny> {
+        let communicator = self.communicator.borrow(py);
+        let key = self.key(key);
+        let serializable = communicator.connect_options.serializable_reads;
+        let request =
+            communicator.idempotent_request("get", Some(key.clone()), move |mut client| {
+                let key = key.clone();
+                async move {
+                    let options = serializable.then(|| GetOptions::new().with_serializable());
+                    client.get(key, options).await
+                }
+            });
+
+        let decoding = communicator.connect_options.text_decoding;
+        let codec = communicator.connect_options.codec.clone();
+        future_into_py(py, async move {
+            let mut response = request.await?;
+            match response.take_kvs().into_iter().next() {
+                Some(kv) => {
+                    let value = KvBytes::value(kv, decoding, codec.as_ref())?;
+                    Ok(Python::with_gil(|py| value.into_py(py)))
+                }
+                None => Ok(default.unwrap_or_else(|| Python::with_gil(|py| py.None()))),
+            }
+        })
+    }
+
+    /// Sets `key` to `value`.
+    fn aset<'a>(&self, py: Python<'a>, key: &PyBytes, value: &PyAny) -> PyResult<&'a PyAny> {
+        self.check(py, key.as_bytes(), value)?;
+        let communicator = self.communicator.borrow(py);
+        let key = self.key(key);
+        let value = encode_value(py, communicator.connect_options.codec.as_ref(), value)?;
+        let prev_key = communicator.connect_options.audit.is_some();
+        let request = communicator.request("put", Some(key.clone()), move |mut client| {
+            let (key, value) = (key.clone(), value.clone());
+            async move {
+                let options = prev_key.then(|| PutOptions::new().with_prev_key());
+                client.put(key, value, options).await
+            }
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    /// Sets the keys of `items`, whose values which are dicts hold the keys under theirs,
+    /// joined with `/`, in a single transaction once all of them are checked.
+    fn put_dict<'a>(&self, py: Python<'a>, items: &PyDict) -> PyResult<&'a PyAny> {
+        let mut flattened = Vec::new();
+        flatten(items, b"", &mut flattened)?;
+        for (key, value) in &flattened {
+            self.check(py, key, value)?;
+        }
+
+        let communicator = self.communicator.borrow(py);
+        let codec = communicator.connect_options.codec.as_ref();
+        let prev_key = communicator.connect_options.audit.is_some();
+        let ops = flattened
+            .into_iter()
+            .map(|(key, value)| {
+                let key = [self.prefix.as_slice(), &key].concat();
+                let value = encode_value(py, codec, value)?;
+                let options = prev_key.then(|| PutOptions::new().with_prev_key());
+                Ok(TxnOp::put(key, value, options))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let prefix = self.prefix.clone();
+        let request = communicator.request("put_dict", Some(prefix), move |mut client| {
+            let txn = Txn::new().and_then(ops.clone());
+            async move { client.txn(txn).await }
+        });
+
+        future_into_py(py, async move { request.await.map(|_| ()) })
+    }
+
+    /// Deletes `key`, raising `KeyError` if it does not exist.
+    fn adel<'a>(&self, py: Python<'a>, key: &PyBytes) -> PyResult<&'a PyAny> {
+        let communicator = self.communicator.borrow(py);
+        let missing: PyObject = key.into();
+        let key = self.key(key);
+        let prev_key = communicator.connect_options.audit.is_some();
+        let request = communicator.request("delete", Some(key.clone()), move |mut client| {
+            let key = key.clone();
+            async move {
+                let options = prev_key.then(|| DeleteOptions::new().with_prev_key());
+                client.delete(key, options).await
+            }
+        });
+
+        future_into_py(py, async move {
+            match request.await?.deleted() {
+                0 => Err(PyKeyError::new_err(missing)),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Returns the keys under the prefix, without it.
+    fn akeys<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.get_prefix(py, true);
+        let decoding = self.communicator.borrow(py).connect_options.text_decoding;
+        let length = self.prefix.len();
+        future_into_py(py, async move {
+            let mut response = request.await?;
+            Python::with_gil(|py| {
+                response
+                    .take_kvs()
+                    .iter()
+                    .map(|kv| decode_key(py, decoding, &kv.key()[length..]))
+                    .collect::<PyResult<Vec<_>>>()
+            })
+        })
+    }
+
+    /// Returns the keys under the prefix, without it, with their values.
+    fn aitems<'a>(&self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let request = self.get_prefix(py, false);
+        let communicator = self.communicator.borrow(py);
+        let decoding = communicator.connect_options.text_decoding;
+        let codec = communicator.connect_options.codec.clone();
+        let length = self.prefix.len();
+        future_into_py(py, async move {
+            let mut response = request.await?;
+            let kvs = response.take_kvs();
+            Python::with_gil(|py| {
+                kvs.into_iter()
+                    .map(|kv| {
+                        let key = decode_key(py, decoding, &kv.key()[length..])?;
+                        let value = KvBytes::value(kv, decoding, codec.as_ref())?;
+                        Ok((key, value.into_py(py)))
+                    })
+                    .collect::<PyResult<Vec<_>>>()
+            })
+        })
+    }
+}
+
+impl PyPrefixMapping {
+    pub fn new(
+        communicator: Py<PyCommunicator>,
+        prefix: Vec<u8>,
+        schema: Option<Py<PySchema>>,
+    ) -> Self {
+        Self {
+            communicator,
+            prefix,
+            schema,
+        }
+    }
+
+    /// Checks `value` written to `key`, relative to the prefix, against the schema if any.
+    fn check(&self, py: Python, key: &[u8], value: &PyAny) -> PyResult<()> {
+        match self.schema {
+            Some(ref schema) => schema.borrow(py).check(py, key, value),
+            None => Ok(()),
+        }
+    }
+
+    fn key(&self, key: &PyBytes) -> Vec<u8> {
+        [self.prefix.as_slice(), key.as_bytes()].concat()
+    }
+
+    fn get_prefix(
+        &self,
+        py: Python<'_>,
+        keys_only: bool,
+    ) -> impl std::future::Future<Output = PyResult<etcd_client::GetResponse>> {
+        let communicator = self.communicator.borrow(py);
+        let prefix = self.prefix.clone();
+        let serializable = communicator.connect_options.serializable_reads;
+        communicator.idempotent_request("get_prefix", Some(prefix.clone()), move |mut client| {
+            let prefix = prefix.clone();
+            async move {
+                let mut options = GetOptions::new().with_prefix();
+                if keys_only {
+                    options = options.with_keys_only();
+                }
+                if serializable {
+                    options = options.with_serializable();
+                }
+                client.get(prefix, Some(options)).await
+            }
+        })
+    }
+}
+
+fn decode_key(py: Python<'_>, decoding: TextDecoding, key: &[u8]) -> PyResult<PyObject> {
+    decoding.check(key)?;
+    Ok(decoding.decode(py, key))
+}
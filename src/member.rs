@@ -0,0 +1,33 @@
+use etcd_client::Member;
+use pyo3::prelude::*;
+
+#[derive(Clone)]
+#[pyclass(name = "Member")]
+pub struct PyMember(pub Member);
+
+#[pymethods]
+impl PyMember {
+    pub fn id(&self) -> u64 {
+        self.0.id()
+    }
+
+    pub fn name(&self) -> String {
+        self.0.name().to_owned()
+    }
+
+    pub fn peer_urls(&self) -> Vec<String> {
+        self.0.peer_urls().to_vec()
+    }
+
+    pub fn client_urls(&self) -> Vec<String> {
+        self.0.client_urls().to_vec()
+    }
+
+    pub fn is_learner(&self) -> bool {
+        self.0.is_learner()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
@@ -0,0 +1,164 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// The upper bounds of the latency buckets, in seconds, which are those of prometheus_client.
+const BUCKETS: [f64; 14] = [
+    0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.25, 0.5, 0.75, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+#[derive(Default)]
+struct Histogram {
+    // The requests which took at most the bound of each bucket, not counting the smaller ones.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    /// Returns the cumulative buckets like Prometheus, ending with the `+Inf` one.
+    fn cumulative(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        let mut buckets: Vec<_> = BUCKETS
+            .iter()
+            .zip(self.buckets)
+            .map(|(bound, count)| {
+                total += count;
+                (*bound, total)
+            })
+            .collect();
+        buckets.push((f64::INFINITY, self.count));
+        buckets
+    }
+}
+
+// How many of the latest requests of each operation `LatencyStats` keeps.
+const WINDOW: usize = 1024;
+
+/// The latencies of the latest `WINDOW` requests of each operation made by a communicator,
+/// along with the number of requests made in total.
+#[derive(Default)]
+pub struct LatencyStats(HashMap<&'static str, (VecDeque<f64>, u64)>);
+
+impl LatencyStats {
+    pub fn record(&mut self, operation: &'static str, elapsed: Duration) {
+        let (window, count) = self.0.entry(operation).or_default();
+        if window.len() == WINDOW {
+            window.pop_front();
+        }
+        window.push_back(elapsed.as_secs_f64());
+        *count += 1;
+    }
+
+    /// Returns the `count` of requests of each operation and the `p50`, `p95` and `p99`
+    /// of the latencies in its window, in seconds.
+    pub fn to_dict<'a>(&self, py: Python<'a>) -> PyResult<&'a PyDict> {
+        let stats = PyDict::new(py);
+        for (operation, (window, count)) in &self.0 {
+            let mut sorted: Vec<f64> = window.iter().copied().collect();
+            sorted.sort_by(f64::total_cmp);
+            let item = PyDict::new(py);
+            item.set_item("count", count)?;
+            item.set_item("p50", percentile(&sorted, 0.50))?;
+            item.set_item("p95", percentile(&sorted, 0.95))?;
+            item.set_item("p99", percentile(&sorted, 0.99))?;
+            stats.set_item(*operation, item)?;
+        }
+        Ok(stats)
+    }
+}
+
+/// Returns the nearest-rank percentile `p` of `sorted`, which is one of the values in it,
+/// or 0 if it is empty.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Default)]
+struct Requests {
+    // By operation and status, which is "ok" or the name of the exception raised.
+    counts: HashMap<(&'static str, String), u64>,
+    latencies: HashMap<&'static str, Histogram>,
+}
+
+static REQUESTS: Mutex<Option<Requests>> = Mutex::new(None);
+static WATCH_EVENTS: AtomicU64 = AtomicU64::new(0);
+static RECONNECTS: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the status of a request which failed with `error`, if any: "ok" or the name of
+/// the exception raised.
+pub fn status(error: Option<&PyErr>) -> String {
+    match error {
+        Some(error) => Python::with_gil(|py| {
+            error
+                .get_type(py)
+                .name()
+                .map_or_else(|_| "Exception".to_owned(), str::to_owned)
+        }),
+        None => "ok".to_owned(),
+    }
+}
+
+/// Records a request for `operation` which completed after `elapsed` with `status`.
+pub fn record_request(operation: &'static str, elapsed: Duration, status: String) {
+    let mut requests = REQUESTS.lock().unwrap();
+    let requests = requests.get_or_insert_with(Requests::default);
+    *requests.counts.entry((operation, status)).or_default() += 1;
+    requests
+        .latencies
+        .entry(operation)
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn record_watch_event() {
+    WATCH_EVENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_reconnect() {
+    RECONNECTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the metrics of all communicators since the module was imported: the `requests`
+/// by `(operation, status)`, the `latency` of each operation as the cumulative `buckets`,
+/// `count` and `sum` of a Prometheus histogram, and the `watch_events` delivered and
+/// `reconnects` made.
+#[pyfunction]
+pub fn metrics_snapshot(py: Python<'_>) -> PyResult<&PyDict> {
+    let requests = PyDict::new(py);
+    let latency = PyDict::new(py);
+    if let Some(ref state) = *REQUESTS.lock().unwrap() {
+        for ((operation, status), count) in &state.counts {
+            requests.set_item((*operation, status), count)?;
+        }
+        for (operation, histogram) in &state.latencies {
+            let item = PyDict::new(py);
+            item.set_item("buckets", histogram.cumulative())?;
+            item.set_item("count", histogram.count)?;
+            item.set_item("sum", histogram.sum)?;
+            latency.set_item(*operation, item)?;
+        }
+    }
+
+    let snapshot = PyDict::new(py);
+    snapshot.set_item("requests", requests)?;
+    snapshot.set_item("latency", latency)?;
+    snapshot.set_item("watch_events", WATCH_EVENTS.load(Ordering::Relaxed))?;
+    snapshot.set_item("reconnects", RECONNECTS.load(Ordering::Relaxed))?;
+    Ok(snapshot)
+}
@@ -0,0 +1,140 @@
+use etcd_client::{Compare, CompareOp, GetOptions, KeyValue, PutOptions, Txn, TxnOp};
+use pyo3::prelude::*;
+
+use crate::communicator::PyCommunicator;
+use crate::error::{add_context, ConflictError, InvalidArgsError};
+
+// How many times a chunk is read again after its keys were modified concurrently.
+const CHUNK_RETRIES: usize = 16;
+
+/// Checks the arguments of `copy_prefix()` and `move_prefix()`. Overlapping prefixes would
+/// have the keys written come up again as keys to copy.
+pub fn check_prefixes(src: &[u8], dst: &[u8], chunk_size: usize) -> PyResult<()> {
+    if src.is_empty() || dst.starts_with(src) || src.starts_with(dst) {
+        return Err(InvalidArgsError::new_err(
+            "src and dst must be non-empty prefixes which do not contain each other",
+        ));
+    }
+    if chunk_size == 0 {
+        return Err(InvalidArgsError::new_err("chunk_size must be at least 1"));
+    }
+    Ok(())
+}
+
+/// Copies the keys under `src` to `dst`, deleting them from `src` if `delete` is set,
+/// `chunk_size` keys per transaction, which only applies if none of them was modified since
+/// the chunk was read. Returns the number of keys copied.
+pub async fn copy_prefix(
+    communicator: Py<PyCommunicator>,
+    src: Vec<u8>,
+    dst: Vec<u8>,
+    chunk_size: usize,
+    progress: Option<PyObject>,
+    delete: bool,
+) -> PyResult<i64> {
+    let operation = if delete { "move_prefix" } else { "copy_prefix" };
+    let end = prefix_end(&src);
+    let total = match progress {
+        Some(_) => {
+            let options = GetOptions::new().with_range(end.clone()).with_count_only();
+            get(&communicator, operation, &src, options).await?.count()
+        }
+        None => 0,
+    };
+
+    let mut copied = 0;
+    let mut start = src.clone();
+    loop {
+        let mut attempts = 0;
+        let kvs = loop {
+            let options = GetOptions::new()
+                .with_range(end.clone())
+                .with_limit(chunk_size as i64);
+            let mut response = get(&communicator, operation, &start, options).await?;
+            let kvs = response.take_kvs();
+            if kvs.is_empty() {
+                return Ok(copied);
+            }
+
+            let txn = chunk_txn(&kvs, &src, &dst, delete);
+            let request = Python::with_gil(|py| {
+                communicator
+                    .borrow(py)
+                    .request(operation, Some(src.clone()), move |mut client| {
+                        let txn = txn.clone();
+                        async move { client.txn(txn).await }
+                    })
+            });
+            if request.await?.succeeded() {
+                break kvs;
+            }
+
+            attempts += 1;
+            if attempts > CHUNK_RETRIES {
+                let error = ConflictError::new_err(format!(
+                    "the keys were modified concurrently on each of {attempts} attempts"
+                ));
+                return Err(add_context(error, operation, Some(&start), None));
+            }
+        };
+
+        copied += kvs.len() as i64;
+        if let Some(ref progress) = progress {
+            Python::with_gil(|py| progress.call1(py, (copied, total)))?;
+        }
+        // Moved keys are gone, but the next chunk can start after the last one either way.
+        start = [kvs[kvs.len() - 1].key(), &[0]].concat();
+    }
+}
+
+/// Returns the transaction copying `kvs` from `src` to `dst` with their leases, if they
+/// were not modified since they were read.
+fn chunk_txn(kvs: &[KeyValue], src: &[u8], dst: &[u8], delete: bool) -> Txn {
+    let compares = kvs
+        .iter()
+        .map(|kv| Compare::mod_revision(kv.key(), CompareOp::Equal, kv.mod_revision()));
+    let mut ops = Vec::with_capacity(kvs.len() * 2);
+    for kv in kvs {
+        let key = [dst, &kv.key()[src.len()..]].concat();
+        let options = (kv.lease() != 0).then(|| PutOptions::new().with_lease(kv.lease()));
+        ops.push(TxnOp::put(key, kv.value(), options));
+        if delete {
+            ops.push(TxnOp::delete(kv.key(), None));
+        }
+    }
+    Txn::new().when(compares.collect::<Vec<_>>()).and_then(ops)
+}
+
+pub async fn get(
+    communicator: &Py<PyCommunicator>,
+    operation: &'static str,
+    key: &[u8],
+    options: GetOptions,
+) -> PyResult<etcd_client::GetResponse> {
+    let key = key.to_vec();
+    let request = Python::with_gil(|py| {
+        communicator.borrow(py).idempotent_request(
+            operation,
+            Some(key.clone()),
+            move |mut client| {
+                let (key, options) = (key.clone(), options.clone());
+                async move { client.get(key, Some(options)).await }
+            },
+        )
+    });
+    request.await
+}
+
+/// Returns the end of the range of keys starting with `prefix`, like etcd's `--prefix`.
+pub fn prefix_end(prefix: &[u8]) -> Vec<u8> {
+    let mut end = prefix.to_vec();
+    while let Some(&last) = end.last() {
+        if last < 0xff {
+            *end.last_mut().unwrap() += 1;
+            return end;
+        }
+        end.pop();
+    }
+    // Every byte is 0xff, so the range extends to the end of the keyspace.
+    vec![0]
+}
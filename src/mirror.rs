@@ -0,0 +1,384 @@
+use etcd_client::{EventType, GetOptions, KeyValue, WatchOptions};
+use pyo3::exceptions::PyKeyError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyTuple};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use crate::communicator::{connected, wait_closed, PyCommunicator};
+use crate::error::{ConnectionLostError, PyClientError, WatchError};
+use crate::key_value::KvBytes;
+use crate::runtime::future_into_py;
+use crate::telemetry::sanitize_key;
+
+/// The keys under the prefix of a mirror, without it, and their decoded values.
+#[derive(Default)]
+struct Entries {
+    values: BTreeMap<Vec<u8>, PyObject>,
+    // The revision of the cluster which the values are up to date with.
+    revision: i64,
+}
+
+// A key whose value changed, with its previous and new values, `None` if it did not exist.
+pub type Change = (Vec<u8>, Option<PyObject>, Option<PyObject>);
+
+/// Keeps the entries of a mirror up to date, shared with its background task.
+#[derive(Clone)]
+pub struct Mirroring {
+    communicator: Py<PyCommunicator>,
+    prefix: Vec<u8>,
+    entries: Arc<Mutex<Entries>>,
+    callbacks: Arc<Mutex<Vec<PyObject>>>,
+    // Sent the changes like the callbacks, for them to be handled in Rust.
+    listeners: Arc<Mutex<Vec<mpsc::UnboundedSender<Change>>>>,
+}
+
+impl Mirroring {
+    pub fn new(communicator: Py<PyCommunicator>, prefix: Vec<u8>) -> Self {
+        Self {
+            communicator,
+            prefix,
+            entries: Arc::default(),
+            callbacks: Arc::default(),
+            listeners: Arc::default(),
+        }
+    }
+
+    pub fn communicator(&self) -> &Py<PyCommunicator> {
+        &self.communicator
+    }
+
+    /// Returns a receiver of the changes notified from now on.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<Change> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.listeners.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Calls `callback(key, old, new)` with each change notified from now on.
+    pub fn on_change(&self, callback: PyObject) {
+        self.callbacks.lock().unwrap().push(callback);
+    }
+
+    /// Returns the keys, without the prefix, and their values.
+    pub fn values(&self, py: Python) -> Vec<(Vec<u8>, PyObject)> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .values
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone_ref(py)))
+            .collect()
+    }
+
+    /// Decodes the value of `kv` like the communicator, or returns `None` after logging
+    /// a warning if it cannot be decoded, which leaves its key out of the mirror.
+    fn decode(&self, py: Python, kv: KeyValue) -> Option<PyObject> {
+        let communicator = self.communicator.borrow(py);
+        let decoding = communicator.connect_options.text_decoding;
+        let codec = communicator.connect_options.codec.as_ref();
+        let key = sanitize_key(Some(kv.key()));
+        match KvBytes::value(kv, decoding, codec) {
+            Ok(value) => Some(value.into_py(py)),
+            Err(e) => {
+                log::warn!(
+                    "leaving \"{key}\" out of the mirror, as its value failed to decode: {e}"
+                );
+                None
+            }
+        }
+    }
+
+    /// Reads the keys under the prefix, replacing the entries with them, and notifies
+    /// the changes from the previous entries if `notify` is set.
+    pub async fn load(&self, notify: bool) -> PyResult<()> {
+        let prefix = self.prefix.clone();
+        let request = Python::with_gil(|py| {
+            self.communicator.borrow(py).idempotent_request(
+                "mirror",
+                Some(prefix.clone()),
+                move |mut client| {
+                    let prefix = prefix.clone();
+                    async move {
+                        let options = GetOptions::new().with_prefix();
+                        client.get(prefix, Some(options)).await
+                    }
+                },
+            )
+        });
+        let mut response = request.await?;
+        let revision = response.header().map_or(0, |header| header.revision());
+
+        let changes = Python::with_gil(|py| {
+            let mut values = BTreeMap::new();
+            for kv in response.take_kvs() {
+                let Some(key) = kv.key().strip_prefix(self.prefix.as_slice()) else {
+                    continue;
+                };
+                let key = key.to_vec();
+                if let Some(value) = self.decode(py, kv) {
+                    values.insert(key, value);
+                }
+            }
+
+            let mut entries = self.entries.lock().unwrap();
+            let mut changes: Vec<Change> = vec![];
+            for (key, old) in entries.values.iter() {
+                if !values.contains_key(key) {
+                    changes.push((key.clone(), Some(old.clone_ref(py)), None));
+                }
+            }
+            for (key, new) in values.iter() {
+                let old = entries.values.get(key);
+                // A value which does not compare is taken as changed.
+                let unchanged = old.is_some_and(|old| old.as_ref(py).eq(new).unwrap_or(false));
+                if !unchanged {
+                    let old = old.map(|old| old.clone_ref(py));
+                    changes.push((key.clone(), old, Some(new.clone_ref(py))));
+                }
+            }
+            entries.values = values;
+            entries.revision = revision;
+            changes
+        });
+        if notify {
+            self.notify(changes);
+        }
+        Ok(())
+    }
+
+    /// Applies the events after the revision of the entries as they come,
+    /// until the watch is lost, which resets `attempts` once it is created.
+    async fn watch(&self, attempts: &mut u32) -> PyResult<()> {
+        let client = Python::with_gil(|py| {
+            let communicator = self.communicator.borrow(py);
+            communicator
+                .check_process()
+                .map(|_| communicator.client.clone())
+        })?;
+        let mut client = connected(&client).await?;
+        let revision = self.entries.lock().unwrap().revision;
+        let options = WatchOptions::new()
+            .with_prefix()
+            .with_start_revision(revision + 1)
+            .with_progress_notify();
+        let (_watcher, mut stream) = client
+            .watch(self.prefix.clone(), Some(options))
+            .await
+            .map_err(PyClientError)?;
+        *attempts = 0;
+
+        while let Some(response) = stream.message().await.map_err(PyClientError)? {
+            if response.canceled() {
+                if response.compact_revision() > 0 {
+                    // The events missed were compacted, so the keys are read again instead.
+                    log::warn!("reloading the mirror, as its revision {revision} was compacted");
+                    return self.load(true).await;
+                }
+                return Err(WatchError::new_err("the watch of the mirror was canceled"));
+            }
+
+            // Events are sent as the watch catches up, possibly behind the revision of the
+            // header, which only progress notifications tell the events are delivered up to.
+            let revision = match response.events().last() {
+                Some(event) => event.kv().map_or(0, |kv| kv.mod_revision()),
+                None if !response.created() => {
+                    response.header().map_or(0, |header| header.revision())
+                }
+                None => continue,
+            };
+            let changes = Python::with_gil(|py| {
+                let mut entries = self.entries.lock().unwrap();
+                let mut changes: Vec<Change> = vec![];
+                for event in response.events() {
+                    let Some(kv) = event.kv() else {
+                        continue;
+                    };
+                    let Some(key) = kv.key().strip_prefix(self.prefix.as_slice()) else {
+                        continue;
+                    };
+                    let key = key.to_vec();
+                    let new = match event.event_type() {
+                        EventType::Put => self.decode(py, kv.clone()),
+                        EventType::Delete => None,
+                    };
+                    let old = match new {
+                        Some(ref new) => entries.values.insert(key.clone(), new.clone_ref(py)),
+                        None => entries.values.remove(&key),
+                    };
+                    if old.is_some() || new.is_some() {
+                        changes.push((key, old, new));
+                    }
+                }
+                entries.revision = entries.revision.max(revision);
+                changes
+            });
+            self.notify(changes);
+        }
+        Err(ConnectionLostError::new_err(
+            "the watch stream of the mirror ended",
+        ))
+    }
+
+    /// Calls the callbacks with each of `changes`, logging the errors they raise,
+    /// outside of the lock of the entries, which they may read.
+    fn notify(&self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+        Python::with_gil(|py| {
+            self.listeners.lock().unwrap().retain(|listener| {
+                changes.iter().all(|(key, old, new)| {
+                    let old = old.as_ref().map(|old| old.clone_ref(py));
+                    let new = new.as_ref().map(|new| new.clone_ref(py));
+                    listener.send((key.clone(), old, new)).is_ok()
+                })
+            });
+
+            let callbacks: Vec<PyObject> = self
+                .callbacks
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|callback| callback.clone_ref(py))
+                .collect();
+            for (key, old, new) in changes {
+                let key = PyBytes::new(py, &key);
+                for callback in callbacks.iter() {
+                    if let Err(e) = callback.call1(py, (key, old.as_ref(), new.as_ref())) {
+                        log::warn!("the change callback of the mirror failed: {e}");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Spawns a task keeping the entries up to date until the communicator is closed.
+    pub fn spawn(&self, closed: watch::Receiver<bool>) -> JoinHandle<()> {
+        let follow = self.clone().follow();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = follow => {}
+                _ = wait_closed(closed) => {}
+            }
+        })
+    }
+
+    /// Keeps watching the prefix, watching it again after a backoff whenever the watch
+    /// is lost.
+    async fn follow(self) {
+        let reconnect =
+            Python::with_gil(|py| self.communicator.borrow(py).connect_options.reconnect);
+        let mut attempts = 0;
+        loop {
+            if let Err(e) = self.watch(&mut attempts).await {
+                let backoff = reconnect.backoff(attempts);
+                attempts += 1;
+                log::warn!("watching the prefix of the mirror again in {backoff:?}: {e}");
+                sleep(backoff).await;
+            }
+        }
+    }
+}
+
+/// A copy of the keys under a prefix, kept up to date by a watch in the background
+/// from when it is entered until it is exited or its communicator is closed.
+#[pyclass(name = "Mirror")]
+pub struct PyMirror {
+    mirroring: Mirroring,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl PyMirror {
+    pub fn new(communicator: Py<PyCommunicator>, prefix: Vec<u8>) -> Self {
+        Self {
+            mirroring: Mirroring::new(communicator, prefix),
+            task: Arc::default(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyMirror {
+    #[getter]
+    fn prefix<'a>(&self, py: Python<'a>) -> &'a PyBytes {
+        PyBytes::new(py, &self.mirroring.prefix)
+    }
+
+    /// The revision of the cluster which the mirror is up to date with, or 0 before it is entered.
+    #[getter]
+    fn revision(&self) -> i64 {
+        self.mirroring.entries.lock().unwrap().revision
+    }
+
+    /// Reads the keys under the prefix, then keeps them up to date in the background.
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let (mirroring, task) = {
+            let mirror = slf.borrow(py);
+            (mirror.mirroring.clone(), mirror.task.clone())
+        };
+        let closed = mirroring.communicator.borrow(py).closed.subscribe();
+
+        future_into_py(py, async move {
+            mirroring.load(false).await?;
+            let handle = mirroring.spawn(closed);
+            if let Some(previous) = task.lock().unwrap().replace(handle) {
+                previous.abort();
+            }
+            Ok(slf)
+        })
+    }
+
+    /// Stops keeping the keys up to date, leaving them as they were.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+        future_into_py(py, async move { Ok(()) })
+    }
+
+    /// Calls `callback(key, old, new)` whenever the value of a key changes, where `key` leaves
+    /// out the prefix and `old` or `new` is `None` if the key did not exist before or after.
+    fn on_change(&self, callback: PyObject) {
+        self.mirroring.on_change(callback);
+    }
+
+    /// Returns the value of `key`, without the prefix, or `default` if it does not exist.
+    #[pyo3(signature = (key, default=None))]
+    fn get(&self, py: Python, key: &PyBytes, default: Option<PyObject>) -> PyObject {
+        let entries = self.mirroring.entries.lock().unwrap();
+        match entries.values.get(key.as_bytes()) {
+            Some(value) => value.clone_ref(py),
+            None => default.unwrap_or_else(|| py.None()),
+        }
+    }
+
+    fn __getitem__(&self, py: Python, key: &PyBytes) -> PyResult<PyObject> {
+        let entries = self.mirroring.entries.lock().unwrap();
+        match entries.values.get(key.as_bytes()) {
+            Some(value) => Ok(value.clone_ref(py)),
+            None => Err(PyKeyError::new_err(PyObject::from(key))),
+        }
+    }
+
+    fn __contains__(&self, key: &PyBytes) -> bool {
+        let entries = self.mirroring.entries.lock().unwrap();
+        entries.values.contains_key(key.as_bytes())
+    }
+
+    fn __len__(&self) -> usize {
+        self.mirroring.entries.lock().unwrap().values.len()
+    }
+
+    /// Returns the keys, without the prefix, and their values as a dict.
+    fn to_dict<'a>(&self, py: Python<'a>) -> PyResult<&'a PyDict> {
+        let dict = PyDict::new(py);
+        for (key, value) in self.mirroring.entries.lock().unwrap().values.iter() {
+            dict.set_item(PyBytes::new(py, key), value)?;
+        }
+        Ok(dict)
+    }
+}
@@ -0,0 +1,75 @@
+use etcd_client::Client as EtcdClient;
+use etcd_client::ObserveStream;
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+
+use crate::communicator::{connected, wait_closed};
+use crate::election_response::PyLeaderResponse;
+use crate::error::PyClientError;
+use crate::runtime::future_into_py;
+
+#[pyclass(name = "Observe")]
+#[derive(Clone)]
+pub struct PyObserve {
+    client: Arc<Mutex<Vec<EtcdClient>>>,
+    closed: watch::Receiver<bool>,
+    name: Vec<u8>,
+    stream: Arc<Mutex<Option<ObserveStream>>>,
+}
+
+impl PyObserve {
+    pub fn new(
+        client: Arc<Mutex<Vec<EtcdClient>>>,
+        closed: watch::Receiver<bool>,
+        name: Vec<u8>,
+    ) -> Self {
+        Self {
+            client,
+            closed,
+            name,
+            stream: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+#[pymethods]
+impl PyObserve {
+    fn __aiter__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __anext__<'a>(&'a mut self, py: Python<'a>) -> PyResult<Option<PyObject>> {
+        let client = self.client.clone();
+        let closed = self.closed.clone();
+        let name = self.name.clone();
+        let stream = self.stream.clone();
+
+        Ok(Some(
+            future_into_py(py, async move {
+                let mut stream = stream.lock().await;
+
+                if stream.is_none() {
+                    // The stream is long-lived, so it gets its own handle of the client.
+                    let mut client = connected(&client).await?;
+                    *stream = Some(client.observe(name).await.map_err(PyClientError)?);
+                }
+
+                let message = tokio::select! {
+                    message = stream.as_mut().unwrap().message() => message,
+                    _ = wait_closed(closed) => {
+                        stream.take();
+                        return Err(PyStopAsyncIteration::new_err(()));
+                    }
+                };
+                match message {
+                    Ok(Some(response)) => Ok(PyLeaderResponse(response)),
+                    Ok(None) => Err(PyStopAsyncIteration::new_err(())),
+                    Err(error) => Err(PyClientError(error).into()),
+                }
+            })?
+            .into(),
+        ))
+    }
+}
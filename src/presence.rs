@@ -0,0 +1,276 @@
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyCFunction, PyDict, PyTuple};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::communicator::{wait_closed, PyCommunicator};
+use crate::error::InvalidArgsError;
+use crate::mirror::{Change, Mirroring};
+use crate::runtime::future_into_py;
+use crate::services::LeasedKey;
+
+// Where the keys of the members of groups are, as `presence/{group}/{member_id}`.
+const PRESENCE_PREFIX: &[u8] = b"presence/";
+
+/// Returns the prefix of the keys of the members of `group`.
+fn group_prefix(group: &[u8]) -> PyResult<Vec<u8>> {
+    // The members of a group would otherwise include those of others.
+    if group.is_empty() || group.contains(&b'/') {
+        return Err(InvalidArgsError::new_err(
+            "group must be non-empty and must not contain '/'",
+        ));
+    }
+    Ok([PRESENCE_PREFIX, group, b"/"].concat())
+}
+
+/// Returns "join" or "leave" for a change of the key of a member,
+/// or `None` if the member only changed its value.
+fn event(old_exists: bool, new_exists: bool) -> Option<&'static str> {
+    match (old_exists, new_exists) {
+        (false, true) => Some("join"),
+        (true, false) => Some("leave"),
+        _ => None,
+    }
+}
+
+/// Keeps the key of a member of a group alive while entered, so that the member leaves the
+/// group once exited, or once its lease expires after the process died.
+#[pyclass(name = "Presence")]
+pub struct PyPresence {
+    communicator: Py<PyCommunicator>,
+    #[pyo3(get)]
+    group: Py<PyBytes>,
+    #[pyo3(get)]
+    member_id: Py<PyBytes>,
+    key: Vec<u8>,
+    value: Vec<u8>,
+    ttl: i64,
+    leased: Arc<Mutex<Option<LeasedKey>>>,
+}
+
+impl PyPresence {
+    pub fn new(
+        communicator: Py<PyCommunicator>,
+        group: &PyBytes,
+        member_id: &PyBytes,
+        value: Vec<u8>,
+        ttl: i64,
+    ) -> PyResult<Self> {
+        if member_id.as_bytes().is_empty() {
+            return Err(InvalidArgsError::new_err("member_id must be non-empty"));
+        }
+        if ttl < 1 {
+            return Err(InvalidArgsError::new_err("ttl must be at least 1 second"));
+        }
+        let key = [
+            group_prefix(group.as_bytes())?,
+            member_id.as_bytes().to_vec(),
+        ]
+        .concat();
+        Ok(Self {
+            communicator,
+            group: group.into(),
+            member_id: member_id.into(),
+            key,
+            value,
+            ttl,
+            leased: Arc::default(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyPresence {
+    /// The ID of the lease of the key of the member while entered.
+    #[getter]
+    fn lease_id(&self) -> Option<i64> {
+        self.leased
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(LeasedKey::lease_id)
+    }
+
+    /// Joins the group by putting the key of the member, attached to a lease kept alive.
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let presence = slf.borrow(py);
+        let communicator = presence.communicator.clone_ref(py);
+        let (key, value, ttl) = (presence.key.clone(), presence.value.clone(), presence.ttl);
+        let leased = presence.leased.clone();
+        drop(presence);
+
+        future_into_py(py, async move {
+            let joined = LeasedKey::put(
+                &communicator,
+                "presence",
+                "presence",
+                key.clone(),
+                value,
+                ttl,
+            )
+            .await?;
+            let previous = leased.lock().unwrap().replace(joined);
+            if let Some(previous) = previous {
+                previous.release(&communicator, "presence", key).await?;
+            }
+            Ok(slf)
+        })
+    }
+
+    /// Leaves the group by revoking the lease of the key of the member.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        let communicator = self.communicator.clone_ref(py);
+        let key = self.key.clone();
+        let leased = self.leased.lock().unwrap().take();
+
+        future_into_py(py, async move {
+            if let Some(leased) = leased {
+                leased.release(&communicator, "presence", key).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The members of a group, read when it is entered and kept up to date by a watch of their
+/// keys in the background until it is exited or its communicator is closed.
+#[pyclass(name = "Members")]
+pub struct PyMembers {
+    mirroring: Mirroring,
+    #[pyo3(get)]
+    group: Py<PyBytes>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // Receives the changes from when the view is entered, for iterating over them.
+    changes: Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<Change>>>>,
+}
+
+impl PyMembers {
+    pub fn new(communicator: Py<PyCommunicator>, group: &PyBytes) -> PyResult<Self> {
+        Ok(Self {
+            mirroring: Mirroring::new(communicator, group_prefix(group.as_bytes())?),
+            group: group.into(),
+            task: Arc::default(),
+            changes: Arc::default(),
+        })
+    }
+}
+
+#[pymethods]
+impl PyMembers {
+    /// Reads the members of the group, then keeps them up to date in the background.
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let (mirroring, task, changes) = {
+            let members = slf.borrow(py);
+            let changes = members.changes.clone();
+            (members.mirroring.clone(), members.task.clone(), changes)
+        };
+        let closed = mirroring.communicator().borrow(py).closed.subscribe();
+
+        future_into_py(py, async move {
+            mirroring.load(false).await?;
+            *changes.lock().await = Some(mirroring.subscribe());
+            let handle = mirroring.spawn(closed);
+            if let Some(previous) = task.lock().unwrap().replace(handle) {
+                previous.abort();
+            }
+            Ok(slf)
+        })
+    }
+
+    /// Stops keeping the members up to date, which ends the iteration over their events.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+        let changes = self.changes.clone();
+        future_into_py(py, async move {
+            changes.lock().await.take();
+            Ok(())
+        })
+    }
+
+    /// Returns the IDs of the members, in order.
+    fn members<'a>(&self, py: Python<'a>) -> Vec<&'a PyBytes> {
+        let values = self.mirroring.values(py);
+        values
+            .iter()
+            .map(|(member_id, _)| PyBytes::new(py, member_id))
+            .collect()
+    }
+
+    /// Returns the IDs of the members and their values as a dict.
+    fn to_dict<'a>(&self, py: Python<'a>) -> PyResult<&'a PyDict> {
+        let dict = PyDict::new(py);
+        for (member_id, value) in self.mirroring.values(py) {
+            dict.set_item(PyBytes::new(py, &member_id), value)?;
+        }
+        Ok(dict)
+    }
+
+    fn __contains__(&self, py: Python, member_id: &PyBytes) -> bool {
+        let values = self.mirroring.values(py);
+        values.iter().any(|(id, _)| id == member_id.as_bytes())
+    }
+
+    fn __len__(&self, py: Python) -> usize {
+        self.mirroring.values(py).len()
+    }
+
+    /// Calls `callback(event, member_id)` whenever a member joins or leaves the group,
+    /// where `event` is `"join"` or `"leave"`.
+    fn on_change(&self, py: Python, callback: PyObject) -> PyResult<()> {
+        let on_change = PyCFunction::new_closure(
+            py,
+            None,
+            None,
+            move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                let (member_id, old, new): (&PyAny, &PyAny, &PyAny) = args.extract()?;
+                if let Some(event) = event(!old.is_none(), !new.is_none()) {
+                    callback.call1(args.py(), (event, member_id))?;
+                }
+                Ok(())
+            },
+        )?;
+        self.mirroring.on_change(on_change.into());
+        Ok(())
+    }
+
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Returns the next event of the members as `(event, member_id)`, like the arguments
+    /// of the callbacks of `on_change()`.
+    fn __anext__<'a>(&'a self, py: Python<'a>) -> PyResult<Option<PyObject>> {
+        let changes = self.changes.clone();
+        let closed = self.mirroring.communicator().borrow(py).closed.subscribe();
+
+        Ok(Some(
+            future_into_py(py, async move {
+                let mut changes = changes.lock().await;
+                let Some(receiver) = changes.as_mut() else {
+                    return Err(PyStopAsyncIteration::new_err(()));
+                };
+                loop {
+                    let change = tokio::select! {
+                        change = receiver.recv() => change,
+                        _ = wait_closed(closed.clone()) => None,
+                    };
+                    let Some((member_id, old, new)) = change else {
+                        return Err(PyStopAsyncIteration::new_err(()));
+                    };
+                    if let Some(event) = event(old.is_some(), new.is_some()) {
+                        return Python::with_gil(|py| {
+                            Ok((event, PyBytes::new(py, &member_id)).into_py(py))
+                        });
+                    }
+                }
+            })?
+            .into(),
+        ))
+    }
+}
@@ -0,0 +1,60 @@
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{IntoPyDict, PyType};
+
+/// A subclass of Python's `enum.Enum` or one of its variants such as `enum.IntEnum`,
+/// which gets iteration, hashing, `match` support and pickling from the `enum` module
+/// where a pyclass with class attributes cannot. Rust enums convert to and from its members
+/// through their values. The class is created on first use.
+pub struct PyEnum {
+    class: GILOnceCell<Py<PyType>>,
+    base: &'static str,
+    name: &'static str,
+    members: &'static [(&'static str, i32)],
+}
+
+impl PyEnum {
+    pub const fn new(
+        base: &'static str,
+        name: &'static str,
+        members: &'static [(&'static str, i32)],
+    ) -> Self {
+        Self {
+            class: GILOnceCell::new(),
+            base,
+            name,
+            members,
+        }
+    }
+
+    /// Returns the class, which the module exports under its name.
+    pub fn class<'py>(&self, py: Python<'py>) -> PyResult<&'py PyType> {
+        let class = self.class.get_or_try_init(py, || -> PyResult<_> {
+            let kwargs = [("module", "etcd_client")].into_py_dict(py);
+            let class = py
+                .import("enum")?
+                .getattr(self.base)?
+                .call((self.name, self.members.to_vec()), Some(kwargs))?;
+            Ok(class.downcast::<PyType>()?.into())
+        })?;
+        Ok(class.clone_ref(py).into_ref(py))
+    }
+
+    /// Returns the member with `value`.
+    pub fn member(&self, py: Python<'_>, value: i32) -> PyResult<PyObject> {
+        Ok(self.class(py)?.call1((value,))?.into())
+    }
+
+    /// Returns the value of `member`, raising `TypeError` if it is not a member.
+    pub fn value(&self, member: &PyAny) -> PyResult<i32> {
+        if !member.is_instance(self.class(member.py())?)? {
+            return Err(PyTypeError::new_err(format!(
+                "expected a {}, got {}",
+                self.name,
+                member.get_type().name()?
+            )));
+        }
+        member.getattr("value")?.extract()
+    }
+}
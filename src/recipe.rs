@@ -0,0 +1,62 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyTuple, PyType};
+
+/// The calls which built a configuration object, starting from its class, which pickling replays
+/// as the types of etcd-client it wraps, such as `ConnectOptions`, cannot be inspected.
+#[derive(Clone, Debug, Default)]
+pub struct Recipe(Vec<(&'static str, Py<PyTuple>)>);
+
+impl Recipe {
+    /// Starts a recipe with the call of `method` of the class, or of the class itself if empty.
+    pub fn new(method: &'static str, args: impl IntoPy<Py<PyTuple>>) -> Self {
+        Self::default().then(method, args)
+    }
+
+    /// Returns a copy of the recipe followed by the call of `method` with `args`.
+    pub fn then(&self, method: &'static str, args: impl IntoPy<Py<PyTuple>>) -> Self {
+        let mut result = self.clone();
+        Python::with_gil(|py| result.0.push((method, args.into_py(py))));
+        result
+    }
+
+    /// Returns the calls of the recipe, with their arguments, in order.
+    pub fn calls(&self) -> &[(&'static str, Py<PyTuple>)] {
+        &self.0
+    }
+
+    /// Returns the value of `__reduce__()` for an object of class `cls` built by the recipe.
+    pub fn reduce(&self, py: Python<'_>, cls: &PyType) -> PyResult<(PyObject, PyObject)> {
+        let replay = py.import("etcd_client")?.getattr("_replay")?;
+        let calls: Vec<(&str, Py<PyTuple>)> = self
+            .0
+            .iter()
+            .map(|(method, args)| (*method, args.clone_ref(py)))
+            .collect();
+        Ok((replay.into(), (cls, calls).into_py(py)))
+    }
+}
+
+/// Rebuilds an object pickled with a `Recipe`.
+#[pyfunction]
+#[pyo3(name = "_replay")]
+pub fn replay<'a>(cls: &'a PyAny, calls: Vec<(&str, &PyTuple)>) -> PyResult<&'a PyAny> {
+    let mut result = cls;
+    for (method, args) in calls {
+        result = match method {
+            "" => result.call1(args)?,
+            method => result.call_method1(method, args)?,
+        };
+    }
+    Ok(result)
+}
+
+/// Returns the value of `__reduce__()` for `name`, a class attribute of `cls` such as
+/// `CompareOp.EQUAL`, so that unpickling it returns that attribute.
+pub fn reduce_classattr(
+    py: Python<'_>,
+    cls: &PyType,
+    name: &'static str,
+) -> PyResult<(PyObject, PyObject)> {
+    let getattr = py.import("builtins")?.getattr("getattr")?;
+    Ok((getattr.into(), (cls, name).into_py(py)))
+}
@@ -0,0 +1,45 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How the communicator reconnects after a request failed on the transport.
+#[derive(Clone, Copy, Debug)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: f64,
+    pub max_backoff: f64,
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 0,
+            initial_backoff: 0.1,
+            max_backoff: 5.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Returns the delay before the reconnection `attempt` (counted from 0), which doubles
+    /// with every attempt up to `max_backoff` and is then spread by up to `jitter` of itself.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff =
+            (self.initial_backoff * 2f64.powi(attempt.min(64) as i32)).min(self.max_backoff);
+        // A fresh `RandomState` is randomly seeded, which is all the randomness needed here.
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        Duration::from_secs_f64(backoff * (1.0 + self.jitter * (2.0 * random - 1.0)))
+    }
+}
+
+/// Whether `error` means the request may not have reached the cluster,
+/// as opposed to having been rejected by it.
+pub fn is_transport_error(error: &etcd_client::Error) -> bool {
+    match error {
+        etcd_client::Error::TransportError(_) => true,
+        etcd_client::Error::GRpcStatus(status) => status.code() == tonic::Code::Unavailable,
+        _ => false,
+    }
+}
@@ -0,0 +1,440 @@
+use pyo3::exceptions::{PyAttributeError, PyException};
+use pyo3::prelude::*;
+use pyo3::types::{
+    PyBool, PyBytes, PyCFunction, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple, PyType,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::fs;
+use tokio::time::sleep;
+
+use crate::communicator::PyCommunicator;
+use crate::error::{
+    io_error, ClientError, InvalidArgsError, PyGRPCStatusCode, ReplayMismatchError,
+};
+use crate::fault::{injected_error, Faults, PyFault};
+use crate::runtime::{future_into_py, ProcessRuntime};
+
+/// Converts `value` to what `json.dumps()` accepts, tagging bytes, tuples and dicts as
+/// `{"bytes": base64}`, `{"tuple": [...]}` and `{"dict": [[key, value], ...]}` for `decode()`
+/// to restore them. Other objects are converted to `{"repr": repr(value)}`, which can only be
+/// compared, unless `strict`, which raises `InvalidArgsError` for them instead.
+fn encode(py: Python, value: &PyAny, strict: bool) -> PyResult<PyObject> {
+    if value.is_none()
+        || value.is_instance_of::<PyBool>()
+        || value.is_instance_of::<PyLong>()
+        || value.is_instance_of::<PyFloat>()
+        || value.is_instance_of::<PyString>()
+    {
+        return Ok(value.into());
+    }
+    if let Ok(list) = value.downcast::<PyList>() {
+        return Ok(encode_all(py, list.iter(), strict)?.into());
+    }
+
+    let tagged = PyDict::new(py);
+    if let Ok(bytes) = value.downcast::<PyBytes>() {
+        let encoded = py
+            .import("base64")?
+            .call_method1("b64encode", (bytes,))?
+            .call_method1("decode", ("ascii",))?;
+        tagged.set_item("bytes", encoded)?;
+    } else if let Ok(tuple) = value.downcast::<PyTuple>() {
+        tagged.set_item("tuple", encode_all(py, tuple.iter(), strict)?)?;
+    } else if let Ok(dict) = value.downcast::<PyDict>() {
+        let items = PyList::empty(py);
+        for (key, value) in dict {
+            items.append([encode(py, key, strict)?, encode(py, value, strict)?])?;
+        }
+        tagged.set_item("dict", items)?;
+    } else if strict {
+        return Err(InvalidArgsError::new_err(format!(
+            "{} objects cannot be recorded",
+            value.get_type().name()?
+        )));
+    } else {
+        tagged.set_item("repr", value.repr()?)?;
+    }
+    Ok(tagged.into())
+}
+
+fn encode_all<'a>(
+    py: Python<'a>,
+    values: impl Iterator<Item = &'a PyAny>,
+    strict: bool,
+) -> PyResult<&'a PyList> {
+    let encoded = values
+        .map(|value| encode(py, value, strict))
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, encoded))
+}
+
+/// Restores what `encode()` converted.
+fn decode(py: Python, value: &PyAny) -> PyResult<PyObject> {
+    if let Ok(list) = value.downcast::<PyList>() {
+        let decoded = list
+            .iter()
+            .map(|value| decode(py, value))
+            .collect::<PyResult<Vec<_>>>()?;
+        return Ok(PyList::new(py, decoded).into());
+    }
+    let Ok(tagged) = value.downcast::<PyDict>() else {
+        return Ok(value.into());
+    };
+    if let Some(encoded) = tagged.get_item("bytes")? {
+        return Ok(py
+            .import("base64")?
+            .call_method1("b64decode", (encoded,))?
+            .into());
+    }
+    if let Some(values) = tagged.get_item("tuple")? {
+        let decoded = values
+            .iter()?
+            .map(|value| decode(py, value?))
+            .collect::<PyResult<Vec<_>>>()?;
+        return Ok(PyTuple::new(py, decoded).into());
+    }
+    if let Some(items) = tagged.get_item("dict")? {
+        let dict = PyDict::new(py);
+        for item in items.iter()? {
+            let item = item?;
+            dict.set_item(
+                decode(py, item.get_item(0)?)?,
+                decode(py, item.get_item(1)?)?,
+            )?;
+        }
+        return Ok(dict.into());
+    }
+    Ok(value.into())
+}
+
+/// Returns the record of a call of `method`, as `{"method": ..., "args": [...], "kwargs": {...}}`.
+fn request<'a>(
+    py: Python<'a>,
+    method: &str,
+    args: &'a PyTuple,
+    kwargs: Option<&'a PyDict>,
+) -> PyResult<&'a PyDict> {
+    let record = PyDict::new(py);
+    record.set_item("method", method)?;
+    record.set_item("args", encode_all(py, args.iter(), false)?)?;
+    let encoded = PyDict::new(py);
+    for (name, value) in kwargs.into_iter().flatten() {
+        encoded.set_item(name, encode(py, value, false)?)?;
+    }
+    record.set_item("kwargs", encoded)?;
+    Ok(record)
+}
+
+/// Describes the request of `record` for errors, like a call.
+fn describe(record: &PyDict) -> PyResult<String> {
+    let field = |name: &str| -> PyResult<String> {
+        match record.get_item(name)? {
+            Some(value) => Ok(value.repr()?.to_string()),
+            None => Ok("?".to_owned()),
+        }
+    };
+    Ok(format!(
+        "{}(*{}, **{})",
+        field("method")?,
+        field("args")?,
+        field("kwargs")?
+    ))
+}
+
+/// Adds the outcome of a request to its record, as the `result`, or as the `error` with
+/// the name of its type and its message. Results which cannot be encoded, such as a `Watch`,
+/// are recorded by the name of their type as `unreplayable`, for the replay to reject.
+fn record_outcome(py: Python, record: &PyDict, outcome: &PyResult<PyObject>) -> PyResult<()> {
+    match outcome {
+        Ok(result) => match encode(py, result.as_ref(py), true) {
+            Ok(encoded) => record.set_item("result", encoded),
+            Err(_) => record.set_item("unreplayable", result.as_ref(py).get_type().name()?),
+        },
+        Err(e) => {
+            let error = PyDict::new(py);
+            error.set_item("type", e.get_type(py).name()?)?;
+            error.set_item("message", e.value(py).str()?)?;
+            record.set_item("error", error)
+        }
+    }
+}
+
+/// Returns the result recorded in `record`, or raises the error recorded in it as an error of
+/// this module or a built-in one of the same name, or as `ClientError` if there is none.
+fn replay_outcome(py: Python, record: &PyDict) -> PyResult<PyObject> {
+    if let Some(result) = record.get_item("result")? {
+        return decode(py, result);
+    }
+    if let Some(type_name) = record.get_item("unreplayable")? {
+        return Err(InvalidArgsError::new_err(format!(
+            "the {} returned by {} cannot be replayed",
+            type_name,
+            describe(record)?
+        )));
+    }
+    let Some(error) = record.get_item("error")? else {
+        return Err(ReplayMismatchError::new_err(format!(
+            "the record of {} has no outcome",
+            describe(record)?
+        )));
+    };
+    let name: String = error.get_item("type")?.extract()?;
+    let message: String = error.get_item("message")?.extract()?;
+    let error_type = py
+        .import("etcd_client")?
+        .getattr(name.as_str())
+        .or_else(|_| py.import("builtins")?.getattr(name.as_str()));
+    match error_type.map(|error_type| error_type.downcast::<PyType>()) {
+        Ok(Ok(error_type)) if error_type.is_subclass_of::<PyException>()? => {
+            Err(PyErr::from_type(error_type, message))
+        }
+        _ => Err(ClientError::new_err(message)),
+    }
+}
+
+/// Forwards every method call to a communicator, recording the requests with their results or
+/// errors, which are written to a file as JSON lines once exited, for a `ReplayCommunicator`
+/// to replay. Recording never changes what the calls return or raise.
+#[pyclass(name = "RecordingCommunicator")]
+pub struct PyRecordingCommunicator {
+    communicator: Py<PyCommunicator>,
+    #[pyo3(get)]
+    path: String,
+    // The records of the requests, in the order they were sent.
+    records: Arc<Mutex<Vec<Py<PyDict>>>>,
+}
+
+impl PyRecordingCommunicator {
+    pub fn new(communicator: Py<PyCommunicator>, path: String) -> Self {
+        Self {
+            communicator,
+            path,
+            records: Arc::default(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyRecordingCommunicator {
+    fn __getattr__(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        let attribute = self.communicator.as_ref(py).getattr(name)?;
+        if !attribute.is_callable() {
+            return Ok(attribute.into());
+        }
+        let attribute: PyObject = attribute.into();
+        let method = name.to_owned();
+        let records = self.records.clone();
+
+        let call = PyCFunction::new_closure(
+            py,
+            None,
+            None,
+            move |args: &PyTuple, kwargs: Option<&PyDict>| -> PyResult<PyObject> {
+                let py = args.py();
+                let record: Py<PyDict> = request(py, &method, args, kwargs)?.into();
+                records.lock().unwrap().push(record.clone_ref(py));
+
+                let returned = attribute.call(py, args, kwargs);
+                let awaited = match returned {
+                    Ok(ref returned) => returned.as_ref(py).hasattr("__await__").unwrap_or(false),
+                    Err(_) => false,
+                };
+                let _ = record.as_ref(py).set_item("awaited", awaited);
+                if !awaited {
+                    let _ = record_outcome(py, record.as_ref(py), &returned);
+                    return returned;
+                }
+
+                let response =
+                    pyo3_asyncio::generic::into_future::<ProcessRuntime>(returned?.as_ref(py))?;
+                Ok(future_into_py(py, async move {
+                    let outcome = response.await;
+                    Python::with_gil(|py| {
+                        let _ = record_outcome(py, record.as_ref(py), &outcome);
+                        outcome
+                    })
+                })?
+                .into())
+            },
+        )?;
+        Ok(call.into())
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Writes the records of the requests which completed to the file.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        let json = py.import("json")?;
+        let mut lines = String::new();
+        for record in self.records.lock().unwrap().iter() {
+            let record = record.as_ref(py);
+            // Requests which never completed, e.g. as they were cancelled, have no outcome.
+            if ["result", "unreplayable", "error"]
+                .iter()
+                .any(|field| record.contains(field).unwrap_or(false))
+            {
+                lines.push_str(json.call_method1("dumps", (record,))?.extract()?);
+                lines.push('\n');
+            }
+        }
+        let path = self.path.clone();
+
+        future_into_py(py, async move {
+            fs::write(&path, lines).await.map_err(io_error)?;
+            Ok(())
+        })
+    }
+}
+
+/// Replays the requests recorded by a `RecordingCommunicator` without connecting to etcd,
+/// returning their recorded results or raising their recorded errors, and raising
+/// `ReplayMismatchError` for any request other than the next recorded one.
+#[pyclass(name = "ReplayCommunicator")]
+pub struct PyReplayCommunicator {
+    #[pyo3(get)]
+    path: String,
+    // The records of the requests yet to be replayed.
+    records: Arc<Mutex<VecDeque<Py<PyDict>>>>,
+    faults: Faults,
+}
+
+#[pymethods]
+impl PyReplayCommunicator {
+    #[new]
+    fn new(py: Python, path: String) -> PyResult<Self> {
+        let text = std::fs::read_to_string(&path).map_err(io_error)?;
+        let json = py.import("json")?;
+        let records = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let record = json.call_method1("loads", (line,))?;
+                Ok(record.downcast::<PyDict>()?.into())
+            })
+            .collect::<PyResult<VecDeque<_>>>()?;
+        Ok(Self {
+            path,
+            records: Arc::new(Mutex::new(records)),
+            faults: Faults::default(),
+        })
+    }
+
+    /// Injects a fault into the replayed requests like `Communicator.inject_fault()`,
+    /// where `operation` is the name of the method called and `key` matches its first argument.
+    /// Requests failed by a fault are not compared with the recorded ones.
+    #[pyo3(signature = (operation=None, key=None, *, error=None, latency=0.0, every=1, times=None))]
+    fn inject_fault(
+        &self,
+        operation: Option<String>,
+        key: Option<&PyBytes>,
+        error: Option<PyGRPCStatusCode>,
+        latency: f64,
+        every: u64,
+        times: Option<u64>,
+    ) -> PyResult<PyFault> {
+        self.faults
+            .add(operation, key, error, latency, every, times)
+    }
+
+    fn clear_faults(&self) {
+        self.faults.clear();
+    }
+
+    fn __getattr__(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        // Looked up by Python itself, e.g. by `copy` and `pickle`, rather than called.
+        if name.starts_with("__") {
+            return Err(PyAttributeError::new_err(name.to_owned()));
+        }
+        let method = name.to_owned();
+        let records = self.records.clone();
+        let faults = self.faults.clone();
+
+        let call = PyCFunction::new_closure(
+            py,
+            None,
+            None,
+            move |args: &PyTuple, kwargs: Option<&PyDict>| -> PyResult<PyObject> {
+                let py = args.py();
+                let key = args
+                    .get_item(0)
+                    .ok()
+                    .and_then(|key| key.downcast::<PyBytes>().ok());
+                let (latency, error) = faults.hit(&method, key.map(PyBytes::as_bytes));
+                if let Some(error) = error {
+                    // Left for the request to be sent again, like after an actual error.
+                    let error = injected_error(error);
+                    return Ok(future_into_py(py, async move {
+                        sleep(latency).await;
+                        PyResult::<()>::Err(error)
+                    })?
+                    .into());
+                }
+
+                let request = request(py, &method, args, kwargs)?;
+                let Some(record) = records.lock().unwrap().pop_front() else {
+                    return Err(ReplayMismatchError::new_err(format!(
+                        "{} was not recorded",
+                        describe(request)?
+                    )));
+                };
+                let record = record.as_ref(py);
+                for field in ["method", "args", "kwargs"] {
+                    let recorded = record.get_item(field)?;
+                    let matches = match (recorded, request.get_item(field)?) {
+                        (Some(recorded), Some(sent)) => recorded.eq(sent)?,
+                        _ => false,
+                    };
+                    if !matches {
+                        return Err(ReplayMismatchError::new_err(format!(
+                            "expected {}, got {}",
+                            describe(record)?,
+                            describe(request)?
+                        )));
+                    }
+                }
+
+                let outcome = replay_outcome(py, record);
+                let awaited = match record.get_item("awaited")? {
+                    Some(awaited) => awaited.extract()?,
+                    None => false,
+                };
+                if !awaited {
+                    return outcome;
+                }
+                Ok(future_into_py(py, async move {
+                    sleep(latency).await;
+                    outcome
+                })?
+                .into())
+            },
+        )?;
+        Ok(call.into())
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Raises `ReplayMismatchError` if some recorded requests were not sent,
+    /// unless exiting with an error.
+    #[pyo3(signature = (*args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, args: &PyTuple) -> PyResult<&'a PyAny> {
+        let failed = args
+            .get_item(0)
+            .is_ok_and(|error_type| !error_type.is_none());
+        let records = self.records.lock().unwrap();
+        let result = match records.front() {
+            Some(next) if !failed => Err(ReplayMismatchError::new_err(format!(
+                "{} recorded requests were not sent, starting with {}",
+                records.len(),
+                describe(next.as_ref(py))?
+            ))),
+            _ => Ok(()),
+        };
+        future_into_py(py, async move { result })
+    }
+}
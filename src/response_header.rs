@@ -0,0 +1,29 @@
+use etcd_client::ResponseHeader;
+use pyo3::prelude::*;
+
+#[derive(Clone)]
+#[pyclass(name = "ResponseHeader")]
+pub struct PyResponseHeader(pub ResponseHeader);
+
+#[pymethods]
+impl PyResponseHeader {
+    pub fn cluster_id(&self) -> u64 {
+        self.0.cluster_id()
+    }
+
+    pub fn member_id(&self) -> u64 {
+        self.0.member_id()
+    }
+
+    pub fn revision(&self) -> i64 {
+        self.0.revision()
+    }
+
+    pub fn raft_term(&self) -> u64 {
+        self.0.raft_term()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
@@ -0,0 +1,215 @@
+use pyo3::prelude::*;
+use pyo3_asyncio::generic::{ContextExt, Runtime as GenericRuntime};
+use pyo3_asyncio::TaskLocals;
+use std::cell::OnceCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+use tokio::task::{JoinError, JoinHandle};
+
+use crate::error::{io_error, InvalidArgsError};
+
+// The runtime shared by the clients, along with the process which built it. Only locked with
+// the GIL held, so that a thread forking the process never leaves it locked.
+static RUNTIME: Mutex<Option<(u32, &'static Runtime)>> = Mutex::new(None);
+// The futures converted by `future_into_py()` which did not complete yet.
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug)]
+struct ShutdownOptions {
+    timeout: f64,
+    cancel: bool,
+    verbose: bool,
+}
+
+static SHUTDOWN_OPTIONS: Mutex<ShutdownOptions> = Mutex::new(ShutdownOptions {
+    timeout: 5.0,
+    cancel: true,
+    verbose: false,
+});
+
+/// Prepares the process for the runtime to start, which only happens on the first request,
+/// so that merely importing the module neither starts threads nor sets up logging.
+fn start(py: Python<'_>) -> PyResult<()> {
+    // Hands the diagnostics of this crate, but not those of its dependencies,
+    // to the `etcd_client` logger of Python's `logging`. Fails only if already installed.
+    let _ = pyo3_log::Logger::new(py, pyo3_log::Caching::LoggersAndLevels)?
+        .filter(log::LevelFilter::Off)
+        .filter_target("etcd_client".to_owned(), log::LevelFilter::Trace)
+        .install();
+    Ok(())
+}
+
+/// Returns the runtime which all clients share, building it on the first call.
+///
+/// `os.fork()` only copies the calling thread, so a child process inherits the runtime without
+/// its worker threads and with whatever locks they held. A forked process therefore builds a
+/// runtime of its own on its first call, leaving the inherited one, which cannot be shut down,
+/// to the communicators created before forking, which raise `ClientError` when used.
+pub fn get_runtime(py: Python<'_>) -> PyResult<&'static Runtime> {
+    let pid = std::process::id();
+    let mut runtime = RUNTIME.lock().unwrap();
+    match *runtime {
+        Some((runtime_pid, runtime)) if runtime_pid == pid => Ok(runtime),
+        inherited => {
+            start(py)?;
+            let built = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .map_err(io_error)?;
+            let built: &'static Runtime = Box::leak(Box::new(built));
+            if inherited.is_some() {
+                // The futures of the parent never complete here, and must not delay exiting.
+                RUNNING.store(0, Ordering::SeqCst);
+            }
+            *runtime = Some((pid, built));
+            Ok(built)
+        }
+    }
+}
+
+/// Returns the runtime of the current process, if it was built.
+fn current_runtime() -> Option<&'static Runtime> {
+    match *RUNTIME.lock().unwrap() {
+        Some((pid, runtime)) if pid == std::process::id() => Some(runtime),
+        _ => None,
+    }
+}
+
+tokio::task_local! {
+    static TASK_LOCALS: OnceCell<TaskLocals>;
+}
+
+/// Runs the futures converted by `future_into_py()` on the runtime of the current process,
+/// which pyo3-asyncio's own tokio runtime cannot be, as it is built only once.
+pub struct ProcessRuntime;
+
+impl GenericRuntime for ProcessRuntime {
+    type JoinError = JoinError;
+    type JoinHandle = JoinHandle<()>;
+
+    fn spawn<F>(fut: F) -> Self::JoinHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // Only called by `future_into_py()`, which builds the runtime of this process first.
+        current_runtime()
+            .expect("the runtime is built before spawning")
+            .spawn(fut)
+    }
+}
+
+impl ContextExt for ProcessRuntime {
+    fn scope<F, R>(locals: TaskLocals, fut: F) -> Pin<Box<dyn Future<Output = R> + Send>>
+    where
+        F: Future<Output = R> + Send + 'static,
+    {
+        let cell = OnceCell::new();
+        let _ = cell.set(locals);
+        Box::pin(TASK_LOCALS.scope(cell, fut))
+    }
+
+    fn get_task_locals() -> Option<TaskLocals> {
+        TASK_LOCALS
+            .try_with(|locals| locals.get().cloned())
+            .unwrap_or_default()
+    }
+}
+
+/// Converts `future` into a Python awaitable run by the runtime of the current process.
+// TODO: Support Trio, and AnyIO on its Trio backend. pyo3-asyncio only completes futures
+// through an asyncio event loop; the generic runtimes of its successor pyo3-async-runtimes
+// would let this pick the loop of the caller, but it needs PyO3 0.21 or later.
+pub fn future_into_py<F, T>(py: Python<'_>, future: F) -> PyResult<&PyAny>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: IntoPy<PyObject>,
+{
+    get_runtime(py)?;
+    RUNNING.fetch_add(1, Ordering::SeqCst);
+    let running = scopeguard::guard((), |_| {
+        RUNNING.fetch_sub(1, Ordering::SeqCst);
+    });
+    pyo3_asyncio::generic::future_into_py::<ProcessRuntime, _, _>(py, async move {
+        let _running = running;
+        future.await
+    })
+}
+
+fn shutdown() -> &'static watch::Sender<bool> {
+    static SHUTDOWN: OnceLock<watch::Sender<bool>> = OnceLock::new();
+    SHUTDOWN.get_or_init(|| watch::channel(false).0)
+}
+
+/// Resolves once `_cleanup_runtime()` was called.
+pub async fn wait_shutdown() {
+    let mut shutdown = shutdown().subscribe();
+    // The sender is never dropped.
+    let _ = shutdown.wait_for(|shutdown| *shutdown).await;
+}
+
+fn check_timeout(timeout: f64) -> PyResult<()> {
+    if !timeout.is_finite() || timeout < 0.0 {
+        return Err(InvalidArgsError::new_err(
+            "timeout must be a non-negative number",
+        ));
+    }
+    Ok(())
+}
+
+/// Sets the defaults of `_cleanup_runtime()`, which is called with them at exit.
+#[pyfunction]
+#[pyo3(signature = (timeout=5.0, cancel=true, verbose=false))]
+pub fn configure_shutdown(timeout: f64, cancel: bool, verbose: bool) -> PyResult<()> {
+    check_timeout(timeout)?;
+
+    *SHUTDOWN_OPTIONS.lock().unwrap() = ShutdownOptions {
+        timeout,
+        cancel,
+        verbose,
+    };
+    Ok(())
+}
+
+/// Ends the streams and background tasks of all communicators if `cancel` is set, and waits up
+/// to `timeout` for the requests still running, after which they are abandoned.
+/// Logging is stopped first, so that nothing left on the runtime calls into Python
+/// while the interpreter finalizes.
+/// Registered with `atexit` on import, which `atexit.unregister()` opts out of.
+#[pyfunction]
+#[pyo3(name = "_cleanup_runtime", signature = (timeout=None, cancel=None, verbose=None))]
+pub fn cleanup_runtime(
+    py: Python<'_>,
+    timeout: Option<f64>,
+    cancel: Option<bool>,
+    verbose: Option<bool>,
+) -> PyResult<()> {
+    let defaults = *SHUTDOWN_OPTIONS.lock().unwrap();
+    let timeout = timeout.unwrap_or(defaults.timeout);
+    check_timeout(timeout)?;
+    let cancel = cancel.unwrap_or(defaults.cancel);
+    let verbose = verbose.unwrap_or(defaults.verbose);
+
+    log::set_max_level(log::LevelFilter::Off);
+    if cancel {
+        shutdown().send_replace(true);
+    }
+
+    // The futures may need the GIL to complete.
+    let deadline = Instant::now() + Duration::from_secs_f64(timeout);
+    let running = py.allow_threads(|| loop {
+        let running = RUNNING.load(Ordering::SeqCst);
+        if running == 0 || Instant::now() >= deadline {
+            break running;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    });
+    if verbose && running > 0 {
+        eprintln!("etcd_client: abandoned {running} tasks still running after {timeout}s");
+    }
+    Ok(())
+}
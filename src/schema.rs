@@ -0,0 +1,142 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyString};
+
+use crate::config_watcher::matches;
+use crate::error::{InvalidArgsError, SchemaError};
+
+/// Returns `key` of a dict written to a prefix as bytes, encoding it in UTF-8 if it is a `str`.
+fn key_bytes(key: &PyAny) -> PyResult<Vec<u8>> {
+    if let Ok(key) = key.downcast::<PyBytes>() {
+        return Ok(key.as_bytes().to_vec());
+    }
+    if let Ok(key) = key.downcast::<PyString>() {
+        return Ok(key.to_str()?.as_bytes().to_vec());
+    }
+    Err(InvalidArgsError::new_err(format!(
+        "keys must be str or bytes, not {}",
+        key.get_type().name()?
+    )))
+}
+
+/// Flattens `items` into `flattened`, where the values which are dicts hold the keys under
+/// theirs, joining the keys with `/`, e.g. `{"db": {"port": 5432}}` into `db/port`.
+pub fn flatten<'a>(
+    items: &'a PyDict,
+    parent: &[u8],
+    flattened: &mut Vec<(Vec<u8>, &'a PyAny)>,
+) -> PyResult<()> {
+    for (key, value) in items {
+        let mut path = parent.to_vec();
+        if !path.is_empty() {
+            path.push(b'/');
+        }
+        path.extend(key_bytes(key)?);
+        match value.downcast::<PyDict>() {
+            Ok(inner) => flatten(inner, &path, flattened)?,
+            Err(_) => flattened.push((path, value)),
+        }
+    }
+    Ok(())
+}
+
+/// Raises `SchemaError` for the key at `path`, which it sets as its `path` attribute.
+fn schema_error(py: Python, path: &[u8], reason: &str, cause: Option<PyErr>) -> PyErr {
+    let path = String::from_utf8_lossy(path);
+    let error = SchemaError::new_err(format!("{path}: {reason}"));
+    // Setting attributes only fails for exceptions rejecting them, which this is not.
+    let _ = error.value(py).setattr("path", path.as_ref());
+    if cause.is_some() {
+        error.set_cause(py, cause);
+    }
+    error
+}
+
+/// The keys which may be written under a prefix, as glob patterns relative to it, with the
+/// validators of their values, checked before writing them so that malformed configuration
+/// never reaches etcd.
+#[pyclass(name = "Schema")]
+pub struct PySchema {
+    // Checked in order, the first pattern matching a key deciding how its value is validated.
+    rules: Vec<(Vec<u8>, Option<PyObject>)>,
+    allow_unknown: bool,
+}
+
+#[pymethods]
+impl PySchema {
+    #[new]
+    #[pyo3(signature = (rules, *, allow_unknown=false))]
+    fn new(rules: &PyDict, allow_unknown: bool) -> PyResult<Self> {
+        let rules = rules
+            .iter()
+            .map(|(pattern, validator)| {
+                let pattern = key_bytes(pattern)?;
+                if validator.is_none() {
+                    return Ok((pattern, None));
+                }
+                if !validator.is_callable() {
+                    return Err(InvalidArgsError::new_err(format!(
+                        "the validator of {:?} must be callable or None",
+                        String::from_utf8_lossy(&pattern)
+                    )));
+                }
+                Ok((pattern, Some(validator.into())))
+            })
+            .collect::<PyResult<_>>()?;
+        Ok(Self {
+            rules,
+            allow_unknown,
+        })
+    }
+
+    /// Checks the keys and values of `items`, flattened like `PrefixMapping.put_dict()`
+    /// flattens them, raising `SchemaError` for the first key which does not follow the schema.
+    fn validate(&self, py: Python, items: &PyDict) -> PyResult<()> {
+        let mut flattened = Vec::new();
+        flatten(items, b"", &mut flattened)?;
+        for (key, value) in flattened {
+            self.check(py, &key, value)?;
+        }
+        Ok(())
+    }
+
+    pub fn __repr__(&self) -> String {
+        let patterns = self
+            .rules
+            .iter()
+            .map(|(pattern, _)| String::from_utf8_lossy(pattern))
+            .collect::<Vec<_>>();
+        format!(
+            "Schema(patterns={:?}, allow_unknown={})",
+            patterns, self.allow_unknown
+        )
+    }
+}
+
+impl PySchema {
+    /// Checks that `key`, relative to the prefix, matches a pattern of the schema unless unknown
+    /// keys are allowed, and that the validator of the pattern accepts `value`, i.e. neither
+    /// raises nor returns `False`.
+    pub fn check(&self, py: Python, key: &[u8], value: &PyAny) -> PyResult<()> {
+        let rule = self.rules.iter().find(|(pattern, _)| matches(pattern, key));
+        let validator = match rule {
+            Some((_, validator)) => validator,
+            None if self.allow_unknown => return Ok(()),
+            None => return Err(schema_error(py, key, "the key is not in the schema", None)),
+        };
+        let Some(validator) = validator else {
+            return Ok(());
+        };
+        match validator.call1(py, (value,)) {
+            Ok(result) => match result.as_ref(py).downcast::<PyBool>() {
+                Ok(accepted) if !accepted.is_true() => Err(schema_error(
+                    py,
+                    key,
+                    &format!("{} was rejected by the validator", value.repr()?),
+                    None,
+                )),
+                _ => Ok(()),
+            },
+            Err(e) => Err(schema_error(py, key, &e.to_string(), Some(e))),
+        }
+    }
+}
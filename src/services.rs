@@ -0,0 +1,344 @@
+use etcd_client::PutOptions;
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyTuple};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::codec::encode_value;
+use crate::communicator::{connected, wait_closed, PyCommunicator};
+use crate::error::{InvalidArgsError, NotFoundError};
+use crate::keepalive::{keep_lease_alive, KeepAliveTask};
+use crate::mirror::{Change, Mirroring};
+use crate::runtime::future_into_py;
+
+/// Returns the prefix of the keys of the instances of `service` under `prefix`.
+fn service_prefix(prefix: &[u8], service: &[u8]) -> PyResult<Vec<u8>> {
+    // The instances of a service would otherwise include those of others.
+    if service.is_empty() || service.contains(&b'/') {
+        return Err(InvalidArgsError::new_err(
+            "service must be non-empty and must not contain '/'",
+        ));
+    }
+    Ok([prefix, service, b"/"].concat())
+}
+
+/// Returns the key of `instance_id` of `service` under `prefix`.
+fn instance_key(prefix: &[u8], service: &[u8], instance_id: &[u8]) -> PyResult<Vec<u8>> {
+    if instance_id.is_empty() {
+        return Err(InvalidArgsError::new_err("instance_id must be non-empty"));
+    }
+    Ok([service_prefix(prefix, service)?, instance_id.to_vec()].concat())
+}
+
+/// A key attached to a lease kept alive in the background, which lives until released,
+/// or until the lease expires once the process dies or the communicator is closed.
+pub struct LeasedKey {
+    lease_id: i64,
+    keepalive_task: KeepAliveTask,
+}
+
+impl LeasedKey {
+    /// Puts `value` as `key` with a lease of `ttl` seconds, which failures to keep alive
+    /// are reported for as those of a `kind`.
+    pub async fn put(
+        communicator: &Py<PyCommunicator>,
+        operation: &'static str,
+        kind: &'static str,
+        key: Vec<u8>,
+        value: Vec<u8>,
+        ttl: i64,
+    ) -> PyResult<Self> {
+        let (grant, client, closed, callback) = Python::with_gil(|py| {
+            let communicator = communicator.borrow(py);
+            let grant =
+                communicator.request(operation, Some(key.clone()), move |mut client| async move {
+                    client.lease_grant(ttl, None).await
+                });
+            let callback = communicator.connect_options.keepalive_callback.clone();
+            let closed = communicator.closed.subscribe();
+            (grant, communicator.client.clone(), closed, callback)
+        });
+        let lease_id = grant.await?.id();
+
+        let put = Python::with_gil(|py| {
+            let put_key = key.clone();
+            communicator
+                .borrow(py)
+                .request(operation, Some(key.clone()), move |mut client| {
+                    let (key, value) = (put_key.clone(), value.clone());
+                    async move {
+                        let options = PutOptions::new().with_lease(lease_id);
+                        client.put(key, value, Some(options)).await
+                    }
+                })
+        });
+        if let Err(e) = put.await {
+            let _ = revoke(communicator, operation, key, lease_id).await;
+            return Err(e);
+        }
+
+        let client = connected(&client).await?;
+        let interval = Duration::from_secs_f64(ttl as f64 / 3.0);
+        let keep_alive = keep_lease_alive(client, lease_id, interval);
+        let keepalive_task = KeepAliveTask::spawn(kind, key, lease_id, callback, async move {
+            // The lease expires on its own once the communicator is closed.
+            tokio::select! {
+                result = keep_alive => result,
+                _ = wait_closed(closed) => Ok(()),
+            }
+        });
+        Ok(Self {
+            lease_id,
+            keepalive_task,
+        })
+    }
+
+    pub fn lease_id(&self) -> i64 {
+        self.lease_id
+    }
+
+    /// Deletes the key by revoking its lease.
+    pub async fn release(
+        self,
+        communicator: &Py<PyCommunicator>,
+        operation: &'static str,
+        key: Vec<u8>,
+    ) -> PyResult<()> {
+        self.keepalive_task.abort();
+        revoke(communicator, operation, key, self.lease_id).await
+    }
+}
+
+/// Revokes `lease_id`, which is fine to have expired already.
+async fn revoke(
+    communicator: &Py<PyCommunicator>,
+    operation: &'static str,
+    key: Vec<u8>,
+    lease_id: i64,
+) -> PyResult<()> {
+    let request = Python::with_gil(|py| {
+        communicator
+            .borrow(py)
+            .request(operation, Some(key), move |mut client| async move {
+                client.lease_revoke(lease_id).await
+            })
+    });
+    match request.await {
+        Err(e) if Python::with_gil(|py| e.is_instance_of::<NotFoundError>(py)) => Ok(()),
+        result => result.map(|_| ()),
+    }
+}
+
+/// Registers instances of services under a prefix, each with the key
+/// `{prefix}{service}/{instance_id}` attached to a lease kept alive in the background,
+/// so that the instances of a process which dies are removed once their leases expire.
+#[pyclass(name = "ServiceRegistry")]
+pub struct PyServiceRegistry {
+    communicator: Py<PyCommunicator>,
+    #[pyo3(get)]
+    prefix: Py<PyBytes>,
+    instances: Arc<Mutex<HashMap<Vec<u8>, LeasedKey>>>,
+}
+
+impl PyServiceRegistry {
+    pub fn new(communicator: Py<PyCommunicator>, prefix: Py<PyBytes>) -> Self {
+        Self {
+            communicator,
+            prefix,
+            instances: Arc::default(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyServiceRegistry {
+    /// Puts `metadata` as the key of `instance_id` of `service` with a lease of `ttl` seconds
+    /// kept alive until deregistered, replacing the previous registration of the instance.
+    /// Returns the ID of the lease.
+    #[pyo3(signature = (service, instance_id, metadata, ttl=10))]
+    fn register<'a>(
+        &'a self,
+        py: Python<'a>,
+        service: &PyBytes,
+        instance_id: &PyBytes,
+        metadata: &PyAny,
+        ttl: i64,
+    ) -> PyResult<&'a PyAny> {
+        let prefix = self.prefix.as_ref(py).as_bytes();
+        let key = instance_key(prefix, service.as_bytes(), instance_id.as_bytes())?;
+        if ttl < 1 {
+            return Err(InvalidArgsError::new_err("ttl must be at least 1 second"));
+        }
+        let communicator = self.communicator.clone_ref(py);
+        let codec = communicator.borrow(py).connect_options.codec.clone();
+        let metadata = encode_value(py, codec.as_ref(), metadata)?;
+        let instances = self.instances.clone();
+
+        future_into_py(py, async move {
+            let instance = LeasedKey::put(
+                &communicator,
+                "register",
+                "service",
+                key.clone(),
+                metadata,
+                ttl,
+            )
+            .await?;
+            let lease_id = instance.lease_id();
+            let replaced = instances.lock().unwrap().insert(key.clone(), instance);
+            if let Some(replaced) = replaced {
+                replaced.release(&communicator, "register", key).await?;
+            }
+            Ok(lease_id)
+        })
+    }
+
+    /// Removes the key of `instance_id` of `service` by revoking its lease, if registered here.
+    fn deregister<'a>(
+        &'a self,
+        py: Python<'a>,
+        service: &PyBytes,
+        instance_id: &PyBytes,
+    ) -> PyResult<&'a PyAny> {
+        let prefix = self.prefix.as_ref(py).as_bytes();
+        let key = instance_key(prefix, service.as_bytes(), instance_id.as_bytes())?;
+        let communicator = self.communicator.clone_ref(py);
+        let instance = self.instances.lock().unwrap().remove(&key);
+
+        future_into_py(py, async move {
+            if let Some(instance) = instance {
+                instance.release(&communicator, "deregister", key).await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns a discovery of the instances of `service` registered under the prefix.
+    fn discover(&self, py: Python, service: &PyBytes) -> PyResult<PyServiceDiscovery> {
+        let prefix = service_prefix(self.prefix.as_ref(py).as_bytes(), service.as_bytes())?;
+        Ok(PyServiceDiscovery {
+            mirroring: Mirroring::new(self.communicator.clone_ref(py), prefix),
+            service: service.into(),
+            task: Arc::default(),
+            changes: Arc::default(),
+        })
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    /// Deregisters every instance registered here.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        let communicator = self.communicator.clone_ref(py);
+        let instances = std::mem::take(&mut *self.instances.lock().unwrap());
+
+        future_into_py(py, async move {
+            for (key, instance) in instances {
+                instance.release(&communicator, "deregister", key).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The instances of a service and their metadata, read when it is entered and kept up to date
+/// by a watch of their keys in the background until it is exited or its communicator is closed.
+#[pyclass(name = "ServiceDiscovery")]
+pub struct PyServiceDiscovery {
+    mirroring: Mirroring,
+    #[pyo3(get)]
+    service: Py<PyBytes>,
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // Receives the changes from when the discovery is entered, for iterating over them.
+    changes: Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<Change>>>>,
+}
+
+#[pymethods]
+impl PyServiceDiscovery {
+    /// Reads the instances of the service, then keeps them up to date in the background.
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let (mirroring, task, changes) = {
+            let discovery = slf.borrow(py);
+            let changes = discovery.changes.clone();
+            (discovery.mirroring.clone(), discovery.task.clone(), changes)
+        };
+        let closed = mirroring.communicator().borrow(py).closed.subscribe();
+
+        future_into_py(py, async move {
+            mirroring.load(false).await?;
+            *changes.lock().await = Some(mirroring.subscribe());
+            let handle = mirroring.spawn(closed);
+            if let Some(previous) = task.lock().unwrap().replace(handle) {
+                previous.abort();
+            }
+            Ok(slf)
+        })
+    }
+
+    /// Stops keeping the instances up to date, which ends the iteration over their changes.
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+        let changes = self.changes.clone();
+        future_into_py(py, async move {
+            changes.lock().await.take();
+            Ok(())
+        })
+    }
+
+    /// Returns the IDs of the instances and their metadata as a dict.
+    fn instances<'a>(&self, py: Python<'a>) -> PyResult<&'a PyDict> {
+        let dict = PyDict::new(py);
+        for (instance_id, metadata) in self.mirroring.values(py) {
+            dict.set_item(PyBytes::new(py, &instance_id), metadata)?;
+        }
+        Ok(dict)
+    }
+
+    /// Calls `callback(instance_id, old, new)` whenever an instance is registered, deregistered
+    /// or has its metadata replaced, where `old` or `new` is `None` if it was not registered
+    /// before or after.
+    fn on_change(&self, callback: PyObject) {
+        self.mirroring.on_change(callback);
+    }
+
+    fn __aiter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Returns the next change of the instances as `(instance_id, old, new)`, like the arguments
+    /// of the callbacks of `on_change()`.
+    fn __anext__<'a>(&'a self, py: Python<'a>) -> PyResult<Option<PyObject>> {
+        let changes = self.changes.clone();
+        let closed = self.mirroring.communicator().borrow(py).closed.subscribe();
+
+        Ok(Some(
+            future_into_py(py, async move {
+                let mut changes = changes.lock().await;
+                let Some(receiver) = changes.as_mut() else {
+                    return Err(PyStopAsyncIteration::new_err(()));
+                };
+                let change = tokio::select! {
+                    change = receiver.recv() => change,
+                    _ = wait_closed(closed) => None,
+                };
+                let Some((instance_id, old, new)) = change else {
+                    return Err(PyStopAsyncIteration::new_err(()));
+                };
+                Python::with_gil(|py| {
+                    let instance_id = PyBytes::new(py, &instance_id);
+                    Ok((instance_id, old, new).into_py(py))
+                })
+            })?
+            .into(),
+        ))
+    }
+}
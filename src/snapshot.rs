@@ -0,0 +1,67 @@
+use etcd_client::Client as EtcdClient;
+use etcd_client::SnapshotStreaming;
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+
+use crate::communicator::{connected, wait_closed};
+use crate::error::PyClientError;
+use crate::maintenance_response::PySnapshotResponse;
+use crate::runtime::future_into_py;
+
+#[pyclass(name = "Snapshot")]
+#[derive(Clone)]
+pub struct PySnapshot {
+    client: Arc<Mutex<Vec<EtcdClient>>>,
+    closed: watch::Receiver<bool>,
+    stream: Arc<Mutex<Option<SnapshotStreaming>>>,
+}
+
+impl PySnapshot {
+    pub fn new(client: Arc<Mutex<Vec<EtcdClient>>>, closed: watch::Receiver<bool>) -> Self {
+        Self {
+            client,
+            closed,
+            stream: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+#[pymethods]
+impl PySnapshot {
+    fn __aiter__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __anext__<'a>(&'a mut self, py: Python<'a>) -> PyResult<Option<PyObject>> {
+        let client = self.client.clone();
+        let closed = self.closed.clone();
+        let stream = self.stream.clone();
+
+        Ok(Some(
+            future_into_py(py, async move {
+                let mut stream = stream.lock().await;
+
+                if stream.is_none() {
+                    let mut client = connected(&client).await?;
+                    *stream = Some(client.snapshot().await.map_err(PyClientError)?);
+                }
+
+                let message = tokio::select! {
+                    message = stream.as_mut().unwrap().message() => message,
+                    _ = wait_closed(closed) => {
+                        stream.take();
+                        return Err(PyStopAsyncIteration::new_err(()));
+                    }
+                };
+                match message {
+                    Ok(Some(response)) => Ok(PySnapshotResponse(response)),
+                    Ok(None) => Err(PyStopAsyncIteration::new_err(())),
+                    Err(error) => Err(PyClientError(error).into()),
+                }
+            })?
+            .into(),
+        ))
+    }
+}
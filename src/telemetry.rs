@@ -0,0 +1,150 @@
+use etcd_client::{DeleteResponse, GetResponse, TxnResponse};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::any::Any;
+use std::time::Duration;
+
+// Keys logged longer than this are cut, as values are sometimes stored in them.
+const LOGGED_KEY_LENGTH: usize = 128;
+
+/// Returns the tracer of OpenTelemetry's Python API which `ConnectOptions.with_tracing()`
+/// uses by default, raising `ImportError` if `opentelemetry-api` is not installed.
+pub fn default_tracer(py: Python<'_>) -> PyResult<PyObject> {
+    let trace = py.import("opentelemetry.trace")?;
+    Ok(trace.call_method1("get_tracer", ("etcd_client",))?.into())
+}
+
+/// A client span of OpenTelemetry's Python API covering a request. Started by the caller
+/// of the request, so that it nests under the span current there, and exported by whatever
+/// the application configured, e.g. an OTLP exporter. Ends when dropped, e.g. as the request
+/// was cancelled, unless ended with the result of the request.
+pub struct RequestSpan(Option<PyObject>);
+
+impl RequestSpan {
+    /// Starts a span for `operation` on `key` if `tracer` is set.
+    /// Tracing never fails a request, so an error starting the span is only logged.
+    pub fn start(tracer: Option<&PyObject>, operation: &str, key: Option<&[u8]>) -> Self {
+        let Some(tracer) = tracer else {
+            return Self(None);
+        };
+        let span = Python::with_gil(|py| -> PyResult<PyObject> {
+            let trace = py.import("opentelemetry.trace")?;
+            let attributes = PyDict::new(py);
+            attributes.set_item("db.system", "etcd")?;
+            attributes.set_item("db.operation", operation)?;
+            if let Some(key) = key {
+                attributes.set_item("db.etcd.key", String::from_utf8_lossy(key))?;
+            }
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("kind", trace.getattr("SpanKind")?.getattr("CLIENT")?)?;
+            kwargs.set_item("attributes", attributes)?;
+            let name = format!("etcd {operation}");
+            tracer.call_method(py, "start_span", (name,), Some(kwargs))
+        });
+        match span {
+            Ok(span) => Self(Some(span)),
+            Err(e) => {
+                log::warn!("failed to start a span for {operation}: {e}");
+                Self(None)
+            }
+        }
+    }
+
+    /// Ends the span, recording `error` as its status if the request failed.
+    pub fn end(mut self, error: Option<&PyErr>) {
+        let Some(span) = self.0.take() else {
+            return;
+        };
+        Python::with_gil(|py| {
+            let result = (|| -> PyResult<()> {
+                if let Some(error) = error {
+                    let trace = py.import("opentelemetry.trace")?;
+                    let status = trace
+                        .getattr("Status")?
+                        .call1((trace.getattr("StatusCode")?.getattr("ERROR")?,))?;
+                    span.call_method1(py, "record_exception", (error.value(py),))?;
+                    span.call_method1(py, "set_status", (status,))?;
+                }
+                span.call_method0(py, "end")?;
+                Ok(())
+            })();
+            if let Err(e) = result {
+                log::warn!("failed to end a span: {e}");
+            }
+        });
+    }
+}
+
+impl Drop for RequestSpan {
+    fn drop(&mut self) {
+        if let Some(span) = self.0.take() {
+            Python::with_gil(|py| {
+                let _ = span.call_method0(py, "end");
+            });
+        }
+    }
+}
+
+/// Python callables called around every request: `before(operation, key)` as it is sent and
+/// `after(operation, key, duration, status)` as it completes, where `status` is "ok" or the
+/// name of the exception raised. Like tracing, hooks never fail a request, so their errors
+/// are only logged.
+#[derive(Debug, Clone, Default)]
+pub struct RequestHooks {
+    pub before: Option<PyObject>,
+    pub after: Option<PyObject>,
+}
+
+impl RequestHooks {
+    pub fn before(&self, operation: &str, key: Option<&[u8]>) {
+        if let Some(ref before) = self.before {
+            Python::with_gil(|py| {
+                let key = key.map(|key| PyBytes::new(py, key));
+                if let Err(e) = before.call1(py, (operation, key)) {
+                    log::warn!("the before hook failed for {operation}: {e}");
+                }
+            });
+        }
+    }
+
+    pub fn after(&self, operation: &str, key: Option<&[u8]>, elapsed: Duration, status: &str) {
+        if let Some(ref after) = self.after {
+            Python::with_gil(|py| {
+                let key = key.map(|key| PyBytes::new(py, key));
+                let args = (operation, key, elapsed.as_secs_f64(), status);
+                if let Err(e) = after.call1(py, args) {
+                    log::warn!("the after hook failed for {operation}: {e}");
+                }
+            });
+        }
+    }
+}
+
+/// Returns `key` as it is logged, with the bytes other than printable ASCII escaped
+/// and the bytes after the first `LOGGED_KEY_LENGTH` left out.
+pub fn sanitize_key(key: Option<&[u8]>) -> String {
+    let key = key.unwrap_or_default();
+    let mut sanitized: String = key
+        .iter()
+        .take(LOGGED_KEY_LENGTH)
+        .flat_map(|byte| std::ascii::escape_default(*byte))
+        .map(char::from)
+        .collect();
+    if key.len() > LOGGED_KEY_LENGTH {
+        sanitized.push_str(&format!("... ({} bytes)", key.len()));
+    }
+    sanitized
+}
+
+/// Returns the number of items in `response`, which is that of the keys read or deleted
+/// or the operations of a transaction run, if it has items at all.
+pub fn response_size(response: &dyn Any) -> Option<usize> {
+    if let Some(response) = response.downcast_ref::<GetResponse>() {
+        Some(response.kvs().len())
+    } else if let Some(response) = response.downcast_ref::<DeleteResponse>() {
+        Some(response.deleted() as usize)
+    } else {
+        let response = response.downcast_ref::<TxnResponse>()?;
+        Some(response.op_responses().len())
+    }
+}
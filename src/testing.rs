@@ -0,0 +1,394 @@
+use etcd_client::{Client as EtcdClient, ConnectOptions};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use std::future::Future;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::time::sleep;
+
+use crate::client::{PyClient, PyConnectOptions};
+use crate::error::{io_error, InvalidArgsError, IoError, RequestTimeoutError};
+use crate::runtime::future_into_py;
+
+// The release of etcd downloaded when no binary is given nor found on `PATH`.
+const DEFAULT_VERSION: &str = "3.5.17";
+
+/// Returns the path of `name` in a directory of `PATH`, if any.
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|directory| directory.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Returns the directory which downloaded releases of etcd are kept in across runs.
+fn cache_dir() -> PathBuf {
+    let cache = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(cache) => PathBuf::from(cache),
+        None => std::env::var_os("HOME").map_or_else(std::env::temp_dir, |home| {
+            PathBuf::from(home).join(".cache")
+        }),
+    };
+    cache.join("etcd-client-py")
+}
+
+/// Downloads the release `version` of etcd from GitHub into the cache, unless it already is
+/// there, and returns the path of its binary.
+fn download(py: Python, version: &str) -> PyResult<PathBuf> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "darwin",
+        os => {
+            return Err(InvalidArgsError::new_err(format!(
+                "etcd has no release for {os}, so a binary must be given"
+            )))
+        }
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        arch => {
+            return Err(InvalidArgsError::new_err(format!(
+                "etcd has no release for {arch}, so a binary must be given"
+            )))
+        }
+    };
+    let name = format!("etcd-v{version}-{os}-{arch}");
+    let cache = cache_dir();
+    let binary = cache.join(&name).join("etcd");
+    if binary.is_file() {
+        return Ok(binary);
+    }
+
+    std::fs::create_dir_all(&cache).map_err(io_error)?;
+    let extension = if os == "linux" { "tar.gz" } else { "zip" };
+    let release = format!("https://github.com/etcd-io/etcd/releases/download/v{version}");
+    let url = format!("{release}/{name}.{extension}");
+    // Named after the process, so that concurrent downloads do not overwrite each other.
+    let archive = cache.join(format!("{name}.{}.{extension}", std::process::id()));
+    let unpacked = cache.join(format!("{name}.{}", std::process::id()));
+    log::info!("downloading etcd from {url}");
+    let installed = install(py, &release, &url, &archive, &unpacked, &cache.join(&name));
+    let _ = std::fs::remove_file(&archive);
+    let _ = std::fs::remove_dir_all(&unpacked);
+    installed?;
+    Ok(binary)
+}
+
+/// Downloads the archive at `url` of `release` to `archive`, checks it against the
+/// `SHA256SUMS` of the release, and unpacks it into `unpacked`, then moves its directory to
+/// `directory` at once, so that other processes never find a partly unpacked binary there.
+fn install(
+    py: Python,
+    release: &str,
+    url: &str,
+    archive: &Path,
+    unpacked: &Path,
+    directory: &Path,
+) -> PyResult<()> {
+    let urllib = py.import("urllib.request")?;
+    urllib.call_method1("urlretrieve", (url, archive))?;
+    let sums: String = urllib
+        .call_method1("urlopen", (format!("{release}/SHA256SUMS"),))?
+        .call_method0("read")?
+        .call_method1("decode", ("utf-8",))?
+        .extract()?;
+    let file_name = url.rsplit('/').next().unwrap_or(url);
+    let expected = sums
+        .lines()
+        .find_map(|line| {
+            let (sum, listed) = line.split_once(char::is_whitespace)?;
+            (listed.trim().trim_start_matches('*') == file_name).then(|| sum.to_lowercase())
+        })
+        .ok_or_else(|| {
+            io_error(format!(
+                "{file_name} is not listed in the SHA256SUMS of {release}"
+            ))
+        })?;
+    let content = std::fs::read(archive).map_err(io_error)?;
+    let actual: String = py
+        .import("hashlib")?
+        .call_method1("sha256", (PyBytes::new(py, &content),))?
+        .call_method0("hexdigest")?
+        .extract()?;
+    if actual != expected {
+        return Err(io_error(format!(
+            "the SHA-256 of {url} is {actual} rather than {expected}"
+        )));
+    }
+
+    py.import("shutil")?
+        .call_method1("unpack_archive", (archive, unpacked))?;
+    let name = directory.file_name().unwrap_or_default();
+    // Zip archives do not keep the permissions of the binary.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(
+            unpacked.join(name).join("etcd"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .map_err(io_error)?;
+    }
+    match std::fs::rename(unpacked.join(name), directory) {
+        Ok(()) => Ok(()),
+        // Another process installed it first.
+        Err(_) if directory.join("etcd").is_file() => Ok(()),
+        Err(e) => Err(io_error(e)),
+    }
+}
+
+/// Returns two ports free on the loopback interface, bound at once so that they differ.
+fn free_ports() -> PyResult<(u16, u16)> {
+    let client = TcpListener::bind(("127.0.0.1", 0)).map_err(io_error)?;
+    let peer = TcpListener::bind(("127.0.0.1", 0)).map_err(io_error)?;
+    let port =
+        |listener: TcpListener| PyResult::Ok(listener.local_addr().map_err(io_error)?.port());
+    Ok((port(client)?, port(peer)?))
+}
+
+/// Returns the last lines of the log of etcd, to tell why it failed to start.
+async fn log_tail(log: &Path) -> String {
+    let log = fs::read_to_string(log).await.unwrap_or_default();
+    let lines = log.lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(10)..].join("\n")
+}
+
+/// A running etcd, which is killed and has its working directory removed once dropped.
+struct Process {
+    child: Child,
+    // Holds the log of etcd, and its data unless a directory was given for them.
+    workdir: PathBuf,
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.workdir);
+    }
+}
+
+impl Process {
+    /// Waits until etcd answers requests for its status at `endpoint`, for up to `timeout`.
+    async fn wait_ready(&mut self, endpoint: &str, timeout: Duration) -> PyResult<()> {
+        let deadline = Instant::now() + timeout;
+        let options = ConnectOptions::new()
+            .with_connect_timeout(Duration::from_millis(500))
+            .with_timeout(Duration::from_millis(500));
+        loop {
+            if let Some(status) = self.child.try_wait().map_err(io_error)? {
+                let log = log_tail(&self.workdir.join("etcd.log")).await;
+                return Err(IoError::new_err(format!(
+                    "etcd exited with {status} before getting ready:\n{log}"
+                )));
+            }
+            if let Ok(mut client) = EtcdClient::connect([endpoint], Some(options.clone())).await {
+                if client.status().await.is_ok() {
+                    return Ok(());
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(RequestTimeoutError::new_err(format!(
+                    "etcd did not get ready within {timeout:?}"
+                )));
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+/// How to start etcd.
+#[derive(Clone)]
+struct Launch {
+    binary: Option<String>,
+    version: String,
+    data_dir: Option<String>,
+    args: Vec<String>,
+    startup_timeout: Duration,
+}
+
+impl Launch {
+    /// Starts etcd as a single member cluster listening on free ports of the loopback interface,
+    /// and returns it with its endpoint once it is ready.
+    async fn start(self) -> PyResult<(Process, String)> {
+        let binary = match self
+            .binary
+            .map(PathBuf::from)
+            .or_else(|| find_on_path("etcd"))
+        {
+            Some(binary) => binary,
+            None => {
+                let version = self.version.clone();
+                tokio::task::spawn_blocking(move || Python::with_gil(|py| download(py, &version)))
+                    .await
+                    .map_err(io_error)??
+            }
+        };
+
+        let (client_port, peer_port) = free_ports()?;
+        let endpoint = format!("http://127.0.0.1:{client_port}");
+        let peer_url = format!("http://127.0.0.1:{peer_port}");
+        let workdir = std::env::temp_dir().join(format!(
+            "etcd-client-py-{}-{client_port}",
+            std::process::id()
+        ));
+        let data_dir = self
+            .data_dir
+            .map_or_else(|| workdir.join("data"), PathBuf::from);
+        fs::create_dir_all(&workdir).await.map_err(io_error)?;
+        let log = std::fs::File::create(workdir.join("etcd.log")).map_err(io_error)?;
+
+        let child = Command::new(&binary)
+            .arg("--name=default")
+            .arg(format!("--data-dir={}", data_dir.display()))
+            .arg(format!("--listen-client-urls={endpoint}"))
+            .arg(format!("--advertise-client-urls={endpoint}"))
+            .arg(format!("--listen-peer-urls={peer_url}"))
+            .arg(format!("--initial-advertise-peer-urls={peer_url}"))
+            .arg(format!("--initial-cluster=default={peer_url}"))
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log)
+            .spawn();
+        let child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&workdir).await;
+                return Err(io_error(format!(
+                    "failed to run {}: {}",
+                    binary.display(),
+                    e
+                )));
+            }
+        };
+
+        let mut process = Process { child, workdir };
+        process.wait_ready(&endpoint, self.startup_timeout).await?;
+        Ok((process, endpoint))
+    }
+}
+
+/// Runs etcd as a local process for tests, using the given binary, or the one on `PATH`,
+/// or else a release downloaded once into a cache.
+#[pyclass(name = "EtcdServer", module = "etcd_client.testing")]
+pub struct PyEtcdServer {
+    launch: Launch,
+    // The endpoint of etcd while it runs.
+    endpoint: Arc<Mutex<Option<String>>>,
+    process: Arc<tokio::sync::Mutex<Option<Process>>>,
+}
+
+impl PyEtcdServer {
+    /// Starts etcd, which must not be running, and returns its endpoint once it is ready.
+    fn starting(&self) -> impl Future<Output = PyResult<String>> + Send + 'static {
+        let launch = self.launch.clone();
+        let (endpoint, process) = (self.endpoint.clone(), self.process.clone());
+
+        async move {
+            let mut process = process.lock().await;
+            if process.is_some() {
+                return Err(InvalidArgsError::new_err("the server is already running"));
+            }
+            let (started, started_endpoint) = launch.start().await?;
+            *process = Some(started);
+            *endpoint.lock().unwrap() = Some(started_endpoint.clone());
+            Ok(started_endpoint)
+        }
+    }
+
+    fn stopping(&self) -> impl Future<Output = PyResult<()>> + Send + 'static {
+        let (endpoint, process) = (self.endpoint.clone(), self.process.clone());
+
+        async move {
+            endpoint.lock().unwrap().take();
+            if let Some(process) = process.lock().await.take() {
+                // Dropping it kills etcd and removes its directory, which block.
+                let _ = tokio::task::spawn_blocking(move || drop(process)).await;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[pymethods]
+impl PyEtcdServer {
+    #[new]
+    #[pyo3(signature = (
+        binary=None,
+        *,
+        version=DEFAULT_VERSION.to_owned(),
+        data_dir=None,
+        args=vec![],
+        startup_timeout=30.0,
+    ))]
+    fn new(
+        binary: Option<String>,
+        version: String,
+        data_dir: Option<String>,
+        args: Vec<String>,
+        startup_timeout: f64,
+    ) -> PyResult<Self> {
+        if !startup_timeout.is_finite() || startup_timeout <= 0.0 {
+            return Err(InvalidArgsError::new_err(
+                "startup_timeout must be a positive number of seconds",
+            ));
+        }
+        Ok(Self {
+            launch: Launch {
+                binary,
+                version,
+                data_dir,
+                args,
+                startup_timeout: Duration::from_secs_f64(startup_timeout),
+            },
+            endpoint: Arc::default(),
+            process: Arc::default(),
+        })
+    }
+
+    /// The endpoints of etcd while it runs, or an empty list.
+    #[getter]
+    fn endpoints(&self) -> Vec<String> {
+        self.endpoint.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Returns a client of etcd, which must be running.
+    #[pyo3(signature = (connect_options=None))]
+    fn client(&self, connect_options: Option<PyConnectOptions>) -> PyResult<PyClient> {
+        let endpoints = self.endpoints();
+        if endpoints.is_empty() {
+            return Err(InvalidArgsError::new_err("the server is not running"));
+        }
+        PyClient::from_endpoints(endpoints, connect_options, None)
+    }
+
+    /// Starts etcd and waits until it is ready, returning its endpoints.
+    fn start<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let starting = self.starting();
+        future_into_py(py, async move { Ok(vec![starting.await?]) })
+    }
+
+    /// Kills etcd and removes its data, unless `data_dir` was given.
+    fn stop<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        future_into_py(py, self.stopping())
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        let starting = slf.borrow(py).starting();
+        future_into_py(py, async move {
+            starting.await?;
+            Ok(slf)
+        })
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        self.stop(py)
+    }
+}
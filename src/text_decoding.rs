@@ -0,0 +1,84 @@
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp as PyO3CompareOp;
+use pyo3::types::{PyBytes, PyString};
+
+use crate::error::Utf8Error;
+use crate::recipe::reduce_classattr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDecoding {
+    Bytes,
+    Strict,
+    SurrogateEscape,
+}
+
+impl TextDecoding {
+    /// Checks that `bytes` can be decoded, so that a key or value which is not UTF-8
+    /// fails the request with `Utf8Error` before its result is handed over to Python.
+    pub fn check(self, bytes: &[u8]) -> PyResult<()> {
+        match self {
+            TextDecoding::Strict => match std::str::from_utf8(bytes) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(Utf8Error::new_err(format!("Utf8Error(err={})", e))),
+            },
+            TextDecoding::Bytes | TextDecoding::SurrogateEscape => Ok(()),
+        }
+    }
+
+    /// Converts `bytes`, which `check()` accepted, to `bytes` or `str`.
+    pub fn decode(self, py: Python<'_>, bytes: &[u8]) -> PyObject {
+        match self {
+            TextDecoding::Bytes => PyBytes::new(py, bytes).into_py(py),
+            TextDecoding::Strict => {
+                let text = std::str::from_utf8(bytes).expect("checked before decoding");
+                PyString::new(py, text).into_py(py)
+            }
+            // Decoded by Python, as Rust strings cannot hold the lone surrogates
+            // which the bytes that are not UTF-8 become.
+            TextDecoding::SurrogateEscape => {
+                PyString::from_object(PyBytes::new(py, bytes), "utf-8", "surrogateescape")
+                    .expect("surrogateescape decodes any bytes")
+                    .into_py(py)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[pyclass(name = "TextDecoding")]
+pub struct PyTextDecoding(pub TextDecoding);
+
+#[pymethods]
+impl PyTextDecoding {
+    #[classattr]
+    const BYTES: Self = Self(TextDecoding::Bytes);
+    #[classattr]
+    const STRICT: Self = Self(TextDecoding::Strict);
+    #[classattr]
+    const SURROGATEESCAPE: Self = Self(TextDecoding::SurrogateEscape);
+
+    pub fn __repr__(&self) -> String {
+        match self.0 {
+            TextDecoding::Bytes => "TextDecoding.BYTES".to_owned(),
+            TextDecoding::Strict => "TextDecoding.STRICT".to_owned(),
+            TextDecoding::SurrogateEscape => "TextDecoding.SURROGATEESCAPE".to_owned(),
+        }
+    }
+
+    pub fn __richcmp__(&self, py: Python, rhs: &PyTextDecoding, op: PyO3CompareOp) -> PyObject {
+        match op {
+            PyO3CompareOp::Eq => (self.0 == rhs.0).into_py(py),
+            PyO3CompareOp::Ne => (self.0 != rhs.0).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        let name = match self.0 {
+            TextDecoding::Bytes => "BYTES",
+            TextDecoding::Strict => "STRICT",
+            TextDecoding::SurrogateEscape => "SURROGATEESCAPE",
+        };
+        reduce_classattr(py, py.get_type::<Self>(), name)
+    }
+}
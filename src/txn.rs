@@ -2,72 +2,98 @@ use etcd_client::{DeleteOptions, GetOptions, PutOptions, Txn, TxnOp};
 use pyo3::{prelude::*, types::PyBytes};
 
 use crate::compare::PyCompare;
+use crate::recipe::Recipe;
 
 #[derive(Debug, Clone)]
 #[pyclass(name = "TxnOp")]
-pub struct PyTxnOp(pub TxnOp);
+pub struct PyTxnOp(pub TxnOp, Recipe);
 
 #[pymethods]
 impl PyTxnOp {
     #[staticmethod]
     fn get(key: &PyBytes) -> PyResult<Self> {
+        let recipe = Recipe::new("get", (key,));
         let key = key.as_bytes().to_vec();
         let options = GetOptions::new();
-        Ok(PyTxnOp(TxnOp::get(key, Some(options))))
+        Ok(PyTxnOp(TxnOp::get(key, Some(options)), recipe))
     }
 
     #[staticmethod]
     fn put(key: &PyBytes, value: &PyBytes) -> PyResult<Self> {
+        let recipe = Recipe::new("put", (key, value));
         let key = key.as_bytes().to_vec();
         let value = value.as_bytes().to_vec();
         let options = PutOptions::new();
-        Ok(PyTxnOp(TxnOp::put(key, value, Some(options))))
+        Ok(PyTxnOp(TxnOp::put(key, value, Some(options)), recipe))
     }
 
     #[staticmethod]
     fn delete(key: &PyBytes) -> PyResult<Self> {
+        let recipe = Recipe::new("delete", (key,));
         let key = key.as_bytes().to_vec();
         let options = DeleteOptions::new();
-        Ok(PyTxnOp(TxnOp::delete(key, Some(options))))
+        Ok(PyTxnOp(TxnOp::delete(key, Some(options)), recipe))
     }
 
     #[staticmethod]
     fn txn(txn: PyTxn) -> PyResult<Self> {
-        Ok(PyTxnOp(TxnOp::txn(txn.0)))
+        let recipe = Recipe::new("txn", (txn.clone(),));
+        Ok(PyTxnOp(TxnOp::txn(txn.0), recipe))
     }
 
     pub fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        self.1.reduce(py, py.get_type::<Self>())
+    }
 }
 
-#[derive(Debug, Default, Clone)]
+impl PyTxnOp {
+    /// Returns the key of the op if it is a get, which can be sent as a read of its own.
+    pub fn get_key(&self, py: Python) -> Option<Vec<u8>> {
+        match self.1.calls() {
+            [("get", args)] => args.as_ref(py).get_item(0).ok()?.extract().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 #[pyclass(name = "Txn")]
-pub struct PyTxn(pub Txn);
+pub struct PyTxn(pub Txn, Recipe);
 
 #[pymethods]
 impl PyTxn {
     #[new]
     fn new() -> Self {
-        PyTxn(Txn::new())
+        PyTxn(Txn::new(), Recipe::new("", ()))
     }
 
     fn when(&self, compares: Vec<PyCompare>) -> PyResult<Self> {
+        let recipe = self.1.then("when", (compares.clone(),));
         let compares = compares.into_iter().map(|c| c.0).collect::<Vec<_>>();
-        Ok(PyTxn(self.0.clone().when(compares)))
+        Ok(PyTxn(self.0.clone().when(compares), recipe))
     }
 
     fn and_then(&self, operations: Vec<PyTxnOp>) -> PyResult<Self> {
+        let recipe = self.1.then("and_then", (operations.clone(),));
         let operations = operations.into_iter().map(|c| c.0).collect::<Vec<_>>();
-        Ok(PyTxn(self.0.clone().and_then(operations)))
+        Ok(PyTxn(self.0.clone().and_then(operations), recipe))
     }
 
     fn or_else(&self, operations: Vec<PyTxnOp>) -> PyResult<Self> {
+        let recipe = self.1.then("or_else", (operations.clone(),));
         let operations = operations.into_iter().map(|c| c.0).collect::<Vec<_>>();
-        Ok(PyTxn(self.0.clone().or_else(operations)))
+        Ok(PyTxn(self.0.clone().or_else(operations), recipe))
     }
 
     pub fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
+
+    fn __reduce__(&self, py: Python) -> PyResult<(PyObject, PyObject)> {
+        self.1.reduce(py, py.get_type::<Self>())
+    }
 }
@@ -1,18 +1,50 @@
 use etcd_client::TxnResponse;
 use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[pyclass(name = "TxnResponse")]
-pub struct PyTxnResponse(pub TxnResponse);
+pub struct PyTxnResponse {
+    succeeded: bool,
+}
+
+impl From<TxnResponse> for PyTxnResponse {
+    fn from(response: TxnResponse) -> Self {
+        Self {
+            succeeded: response.succeeded(),
+        }
+    }
+}
 
 // TODO: Add ResponseHeader, TxnOpResponse
 #[pymethods]
 impl PyTxnResponse {
+    #[new]
+    #[pyo3(signature = (*, succeeded))]
+    fn new(succeeded: bool) -> Self {
+        Self { succeeded }
+    }
+
     pub fn succeeded(&self) -> PyResult<bool> {
-        Ok(self.0.succeeded())
+        Ok(self.succeeded)
     }
 
     pub fn __repr__(&self) -> String {
-        format!("{:?}", self.0)
+        format!(
+            "TxnResponse(succeeded={})",
+            if self.succeeded { "True" } else { "False" }
+        )
+    }
+
+    fn __richcmp__(&self, py: Python, other: &Self, op: CompareOp) -> PyObject {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.succeeded as u64
     }
 }
@@ -1,55 +1,132 @@
 use etcd_client::Client as EtcdClient;
 use etcd_client::WatchOptions;
 use etcd_client::Watcher;
-use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::exceptions::{PyStopAsyncIteration, PyTypeError};
 use pyo3::prelude::*;
-use pyo3_asyncio::tokio::future_into_py;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::Arc;
+use tokio::sync::watch;
 use tokio::sync::Mutex;
 use tokio::sync::Notify;
 
+use crate::codec::Codec;
+use crate::communicator::{connected, wait_closed};
 use crate::condvar::PyCondVar;
 use crate::error::PyClientError;
+use crate::runtime::future_into_py;
+use crate::telemetry::RequestSpan;
+use crate::text_decoding::TextDecoding;
 use crate::watch_event_stream::PyWatchEventStream;
 
+/// What a watch notifies once it is ready: a `CondVar`, or an `asyncio.Event`,
+/// which is not thread-safe and so is set through the event loop the watch was created on.
+#[derive(Clone)]
+pub enum ReadyEvent {
+    CondVar(PyCondVar),
+    Event {
+        event: PyObject,
+        event_loop: PyObject,
+    },
+}
+
+impl<'a> FromPyObject<'a> for ReadyEvent {
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        if let Ok(condvar) = ob.extract::<PyCondVar>() {
+            return Ok(ReadyEvent::CondVar(condvar));
+        }
+        let py = ob.py();
+        if !ob.is_instance(py.import("asyncio")?.getattr("Event")?)? {
+            return Err(PyTypeError::new_err(
+                "ready_event must be a CondVar or an asyncio.Event",
+            ));
+        }
+        Ok(ReadyEvent::Event {
+            event: ob.into(),
+            event_loop: pyo3_asyncio::get_running_loop(py)?.into(),
+        })
+    }
+}
+
+impl ReadyEvent {
+    async fn notify(&self) -> PyResult<()> {
+        match self {
+            ReadyEvent::CondVar(condvar) => {
+                condvar._notify_waiters().await;
+                Ok(())
+            }
+            ReadyEvent::Event { event, event_loop } => Python::with_gil(|py| {
+                let set = event.getattr(py, "set")?;
+                event_loop.call_method1(py, "call_soon_threadsafe", (set,))?;
+                Ok(())
+            }),
+        }
+    }
+}
+
 #[pyclass(name = "Watch")]
 #[derive(Clone)]
 pub struct PyWatch {
-    client: Arc<Mutex<EtcdClient>>,
+    client: Arc<Mutex<Vec<EtcdClient>>>,
+    closed: watch::Receiver<bool>,
     key: Vec<u8>,
     once: bool,
     options: Option<WatchOptions>,
     watcher: Arc<Mutex<Option<Watcher>>>,
     event_stream_init_notifier: Arc<Notify>,
     event_stream: Arc<Mutex<Option<PyWatchEventStream>>>,
-    ready_event: Option<PyCondVar>,
+    // Published by the event stream, which stays locked while waiting for events.
+    lag: Arc<AtomicI64>,
+    ready_event: Option<ReadyEvent>,
+    text_decoding: TextDecoding,
+    codec: Option<Codec>,
+    tracer: Option<PyObject>,
     #[allow(dead_code)]
     cleanup_event: Option<PyCondVar>,
 }
 
 impl PyWatch {
     pub fn new(
-        client: Arc<Mutex<EtcdClient>>,
+        client: Arc<Mutex<Vec<EtcdClient>>>,
+        closed: watch::Receiver<bool>,
         key: Vec<u8>,
         once: bool,
         options: Option<WatchOptions>,
-        ready_event: Option<PyCondVar>,
+        ready_event: Option<ReadyEvent>,
         cleanup_event: Option<PyCondVar>,
     ) -> Self {
         Self {
             client,
+            closed,
             key,
             once,
             options,
             event_stream_init_notifier: Arc::new(Notify::new()),
             event_stream: Arc::new(Mutex::new(None)),
             watcher: Arc::new(Mutex::new(None)),
+            lag: Arc::new(AtomicI64::new(0)),
             ready_event,
+            text_decoding: TextDecoding::Bytes,
+            codec: None,
+            tracer: None,
             cleanup_event,
         }
     }
 
-    pub async fn init(&mut self) -> Result<(), PyClientError> {
+    /// Decodes the keys and values of the events like `ConnectOptions.with_text_decoding()`,
+    /// or the values with `codec` if set.
+    pub fn with_decoding(mut self, text_decoding: TextDecoding, codec: Option<Codec>) -> Self {
+        self.text_decoding = text_decoding;
+        self.codec = codec;
+        self
+    }
+
+    /// Traces the creation of the watch with `tracer` like the requests of its communicator.
+    pub fn with_tracer(mut self, tracer: Option<PyObject>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
+    pub async fn init(&mut self) -> PyResult<()> {
         // Already initialized
         let mut event_stream = self.event_stream.lock().await;
         if event_stream.is_some() {
@@ -58,21 +135,37 @@ impl PyWatch {
 
         let event_stream_init_notifier = self.event_stream_init_notifier.clone();
 
-        let mut client = self.client.lock().await;
+        let span = RequestSpan::start(self.tracer.as_ref(), "watch", Some(&self.key));
+        let mut client = match connected(&self.client).await {
+            Ok(client) => client,
+            Err(error) => {
+                span.end(Some(&error));
+                return Err(error);
+            }
+        };
 
-        match client.watch(self.key.clone(), self.options.clone()).await {
+        let result = client.watch(self.key.clone(), self.options.clone()).await;
+        let result = result.map_err(|error| PyErr::from(PyClientError(error)));
+        span.end(result.as_ref().err());
+        match result {
             Ok((watcher, stream)) => {
-                *event_stream = Some(PyWatchEventStream::new(stream, self.once));
+                *event_stream = Some(PyWatchEventStream::new(
+                    stream,
+                    self.once,
+                    self.text_decoding,
+                    self.codec.clone(),
+                    self.lag.clone(),
+                ));
                 *self.watcher.lock().await = Some(watcher);
 
                 event_stream_init_notifier.notify_waiters();
 
                 if let Some(ready_event) = &self.ready_event {
-                    ready_event._notify_waiters().await;
+                    ready_event.notify().await?;
                 }
                 Ok(())
             }
-            Err(error) => Err(PyClientError(error)),
+            Err(error) => Err(error),
         }
     }
 }
@@ -83,10 +176,19 @@ impl PyWatch {
         self.clone()
     }
 
+    /// How many revisions the events delivered so far lag behind the latest revision
+    /// of the cluster told by the watch stream, counting the events received but not
+    /// delivered yet, or 0 if the watch is not created yet.
+    #[getter]
+    fn lag(&self) -> i64 {
+        self.lag.load(Ordering::Relaxed)
+    }
+
     fn __anext__<'a>(&'a mut self, py: Python<'a>) -> PyResult<Option<PyObject>> {
         let watch = Arc::new(Mutex::new(self.clone()));
         let event_stream_init_notifier = self.event_stream_init_notifier.clone();
         let watcher = self.watcher.clone();
+        let closed = self.closed.clone();
         let once = self.once;
 
         Ok(Some(
@@ -100,11 +202,18 @@ impl PyWatch {
                     event_stream_init_notifier.notified().await;
                 }
 
-                let event_stream = event_stream.as_mut().unwrap();
+                let next = tokio::select! {
+                    next = event_stream.as_mut().unwrap().next() => next,
+                    _ = wait_closed(closed) => {
+                        event_stream.take();
+                        watcher.lock().await.take();
+                        return Err(PyStopAsyncIteration::new_err(()));
+                    }
+                };
 
-                let event = match event_stream.next().await {
+                let event = match next {
                     Some(result) => {
-                        if once {
+                        if once && result.is_ok() {
                             let mut watcher = watcher.lock().await;
                             watcher.as_mut().unwrap().cancel().await.unwrap();
                         }
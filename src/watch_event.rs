@@ -2,21 +2,85 @@ use etcd_client::Event as EtcdClientEvent;
 use etcd_client::EventType as EtcdClientEventType;
 use pyo3::prelude::*;
 use pyo3::pyclass::CompareOp;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::codec::Codec;
+use crate::py_enum::PyEnum;
+use crate::text_decoding::TextDecoding;
 
 // Note: Event = namedtuple("Event", "key event value"), not asyncio.Event, threading.Event
-#[pyclass(get_all, name = "WatchEvent")]
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[pyclass(name = "WatchEvent")]
+#[derive(Clone, Debug)]
 pub struct PyWatchEvent {
     key: Vec<u8>,
     value: Vec<u8>,
     event: PyWatchEventType,
     prev_value: Option<Vec<u8>>,
+    // How the getters return `key`, `value` and `prev_value`, which equality ignores.
+    text_decoding: TextDecoding,
+    codec: Option<Codec>,
+}
+
+impl PyWatchEvent {
+    /// Converts `event`, failing if its key or value cannot be decoded with `text_decoding`.
+    /// Values are rather converted by `codec` if set, when they are accessed.
+    pub fn decoded(
+        event: EtcdClientEvent,
+        text_decoding: TextDecoding,
+        codec: Option<Codec>,
+    ) -> PyResult<Self> {
+        let kv = event.kv().unwrap();
+        // Only sent by watches with `prev_kv`.
+        let prev_value = event.prev_kv().map(|prev_kv| prev_kv.value());
+        text_decoding.check(kv.key())?;
+        if codec.is_none() {
+            text_decoding.check(kv.value())?;
+            if let Some(prev_value) = prev_value {
+                text_decoding.check(prev_value)?;
+            }
+        }
+        Ok(Self {
+            key: kv.key().to_owned(),
+            value: kv.value().to_owned(),
+            event: PyWatchEventType(event.event_type()),
+            prev_value: prev_value.map(ToOwned::to_owned),
+            text_decoding,
+            codec,
+        })
+    }
+
+    fn decode_value(&self, py: Python, value: &[u8]) -> PyResult<PyObject> {
+        match self.codec {
+            Some(ref codec) => codec.decode(py, value),
+            None => Ok(self.text_decoding.decode(py, value)),
+        }
+    }
+
+    fn fields(&self) -> (&[u8], &[u8], &PyWatchEventType, Option<&[u8]>) {
+        let prev_value = self.prev_value.as_deref();
+        (&self.key, &self.value, &self.event, prev_value)
+    }
+}
+
+impl PartialEq for PyWatchEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
+    }
+}
+
+impl Eq for PyWatchEvent {}
+
+impl Hash for PyWatchEvent {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fields().hash(state);
+    }
 }
 
 #[pymethods]
 impl PyWatchEvent {
     #[new]
-    #[pyo3(signature = (key, value, event, prev_value))]
+    #[pyo3(signature = (key, value, event, prev_value=None))]
     fn new(
         key: Vec<u8>,
         value: Vec<u8>,
@@ -28,9 +92,39 @@ impl PyWatchEvent {
             value,
             event,
             prev_value,
+            text_decoding: TextDecoding::Bytes,
+            codec: None,
         }
     }
 
+    #[classattr]
+    fn __match_args__() -> (&'static str, &'static str, &'static str, &'static str) {
+        ("key", "value", "event", "prev_value")
+    }
+
+    #[getter]
+    fn key(&self, py: Python) -> PyObject {
+        self.text_decoding.decode(py, &self.key)
+    }
+
+    #[getter]
+    fn value(&self, py: Python) -> PyResult<PyObject> {
+        self.decode_value(py, &self.value)
+    }
+
+    #[getter]
+    fn event(&self) -> PyWatchEventType {
+        self.event.clone()
+    }
+
+    #[getter]
+    fn prev_value(&self, py: Python) -> PyResult<Option<PyObject>> {
+        self.prev_value
+            .as_ref()
+            .map(|prev_value| self.decode_value(py, prev_value))
+            .transpose()
+    }
+
     pub fn __repr__(&self) -> String {
         format!(
             "Event(event={:?}, key={:?}, value={:?}, prev_value={:?})",
@@ -45,48 +139,34 @@ impl PyWatchEvent {
             _ => py.NotImplemented(),
         }
     }
-}
 
-impl From<EtcdClientEvent> for PyWatchEvent {
-    fn from(event: EtcdClientEvent) -> Self {
-        let kv = event.kv().unwrap();
-        let key = kv.key().to_owned();
-        let value = kv.value().to_owned();
-        let prev_value = None;
-        let event = PyWatchEventType(event.event_type());
-        Self {
-            key,
-            value,
-            event,
-            prev_value,
-        }
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
     }
 }
 
-#[pyclass(name = "WatchEventType")]
-#[derive(PartialEq, Eq, Clone, Debug)]
-pub struct PyWatchEventType(EtcdClientEventType);
-
-#[pymethods]
-impl PyWatchEventType {
-    #[classattr]
-    const PUT: Self = Self(EtcdClientEventType::Put);
+pub static WATCH_EVENT_TYPE: PyEnum =
+    PyEnum::new("Enum", "WatchEventType", &[("PUT", 0), ("DELETE", 1)]);
 
-    #[classattr]
-    const DELETE: Self = Self(EtcdClientEventType::Delete);
+/// Converts to and from the members of `WatchEventType`, an `enum.Enum`.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct PyWatchEventType(pub EtcdClientEventType);
 
-    pub fn __repr__(&self) -> String {
-        match self.0 {
-            EtcdClientEventType::Put => "WatchEventType.PUT".to_string(),
-            EtcdClientEventType::Delete => "WatchEventType.DELETE".to_string(),
-        }
+impl IntoPy<PyObject> for PyWatchEventType {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        WATCH_EVENT_TYPE
+            .member(py, self.0 as i32)
+            .expect("WatchEventType has a member for every event type")
     }
+}
 
-    pub fn __richcmp__(&self, py: Python, rhs: &PyWatchEventType, op: CompareOp) -> PyObject {
-        match op {
-            CompareOp::Eq => (self.0 == rhs.0).into_py(py),
-            CompareOp::Ne => (self.0 != rhs.0).into_py(py),
-            _ => py.NotImplemented(),
+impl<'a> FromPyObject<'a> for PyWatchEventType {
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        match WATCH_EVENT_TYPE.value(ob)? {
+            0 => Ok(Self(EtcdClientEventType::Put)),
+            _ => Ok(Self(EtcdClientEventType::Delete)),
         }
     }
 }
@@ -1,55 +1,201 @@
-use etcd_client::WatchStream;
-use pyo3::pyclass;
+use etcd_client::{WatchResponse, WatchStream};
+use pyo3::{pyclass, PyErr, Python};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
 use tokio_stream::StreamExt;
 
-use crate::{error::PyClientError, watch_event::PyWatchEvent};
+use crate::{
+    codec::Codec,
+    error::{compacted_error, is_retryable, ConnectionLostError, PyClientError},
+    metrics,
+    text_decoding::TextDecoding,
+    watch_event::PyWatchEvent,
+};
+
+// How many events are received ahead of those delivered, for the lag to count them.
+const PREFETCH: usize = 1024;
+
+/// What the stream received, in the order it is delivered.
+enum Item {
+    Event(PyWatchEvent, i64),
+    // An event whose key or value could not be decoded, which does not end the stream.
+    Error(PyErr),
+    // A progress notification, telling that every event up to its revision was sent.
+    Progress(i64),
+    // The end of a response, whose header tells the revision its events were sent at.
+    Through(i64),
+}
+
+/// How the stream ended, which is told once the items received before are delivered.
+enum End {
+    Canceled,
+    Compacted(i64),
+    Failed(etcd_client::Error),
+    Lost(Option<etcd_client::Error>),
+}
 
 #[pyclass(name = "WatchEventStream")]
 pub struct PyWatchEventStream {
     stream: WatchStream,
-    events: Vec<PyWatchEvent>,
-    index: usize,
+    items: VecDeque<Item>,
+    end: Option<End>,
+    // The revision of the last event delivered, or of the last progress notification
+    // after it, or 0 before the first one.
+    revision: i64,
+    // The latest revision of the cluster told by the headers of the responses, and the one
+    // up to which their events were delivered, whose difference is published to `lag`.
+    cluster_revision: i64,
+    delivered_revision: i64,
+    lag: Arc<AtomicI64>,
     once: bool,
+    text_decoding: TextDecoding,
+    codec: Option<Codec>,
 }
 
 impl PyWatchEventStream {
-    pub fn new(stream: WatchStream, once: bool) -> Self {
+    pub fn new(
+        stream: WatchStream,
+        once: bool,
+        text_decoding: TextDecoding,
+        codec: Option<Codec>,
+        lag: Arc<AtomicI64>,
+    ) -> Self {
         Self {
             stream,
-            events: Vec::new(),
-            index: 0,
+            items: VecDeque::new(),
+            end: None,
+            revision: 0,
+            cluster_revision: 0,
+            delivered_revision: 0,
+            lag,
             once,
+            text_decoding,
+            codec,
         }
     }
 
-    pub async fn next(&mut self) -> Option<Result<PyWatchEvent, PyClientError>> {
-        if self.once && self.index > 0 {
+    pub async fn next(&mut self) -> Option<Result<PyWatchEvent, PyErr>> {
+        if self.once && self.revision > 0 {
             return None;
         }
 
-        if self.index < self.events.len() {
-            let event = self.events[self.index].clone();
-            self.index += 1;
-            return Some(Ok(event));
-        }
-
-        match self.stream.next().await {
-            Some(Ok(response)) => {
-                let events = response.events();
-                for event in events {
-                    self.events.push(event.clone().into());
+        loop {
+            // Takes in the responses which already arrived without waiting for more,
+            // so that the lag counts the events the application did not get to yet.
+            while self.end.is_none() && self.items.len() < PREFETCH {
+                match timeout(Duration::ZERO, self.stream.next()).await {
+                    Ok(message) => self.receive(message),
+                    Err(_) => break,
                 }
+            }
 
-                if !self.events.is_empty() {
-                    let event = self.events[self.index].clone();
-                    self.index += 1;
+            let Some(item) = self.items.pop_front() else {
+                if let Some(end) = self.end.take() {
+                    return self.ended(end);
+                }
+                let message = self.stream.next().await;
+                self.receive(message);
+                continue;
+            };
+            let result = match item {
+                Item::Event(event, revision) => {
+                    self.revision = revision;
+                    self.delivered_revision = self.delivered_revision.max(revision);
+                    metrics::record_watch_event();
                     Some(Ok(event))
-                } else {
+                }
+                Item::Error(error) => Some(Err(error)),
+                Item::Progress(revision) => {
+                    self.revision = self.revision.max(revision);
+                    self.delivered_revision = self.delivered_revision.max(revision);
                     None
                 }
+                Item::Through(revision) => {
+                    self.delivered_revision = self.delivered_revision.max(revision);
+                    None
+                }
+            };
+            self.lag.store(
+                (self.cluster_revision - self.delivered_revision).max(0),
+                Ordering::Relaxed,
+            );
+            if result.is_some() {
+                return result;
             }
-            Some(Err(error)) => Some(Err(PyClientError(error))),
-            None => None,
         }
     }
+
+    /// Queues what `message` holds, or takes note of how the stream ended.
+    fn receive(&mut self, message: Option<Result<WatchResponse, etcd_client::Error>>) {
+        let response = match message {
+            Some(Ok(response)) => response,
+            // Errors which a request could be retried after mean the connection dropped.
+            Some(Err(error)) if is_retryable(&error, true) => {
+                self.end = Some(End::Lost(Some(error)));
+                return;
+            }
+            Some(Err(error)) => {
+                self.end = Some(End::Failed(error));
+                return;
+            }
+            None => {
+                self.end = Some(End::Lost(None));
+                return;
+            }
+        };
+
+        if response.canceled() {
+            self.end = Some(match response.compact_revision() {
+                0 => End::Canceled,
+                compact_revision => End::Compacted(compact_revision),
+            });
+            return;
+        }
+        let header_revision = response.header().map_or(0, |header| header.revision());
+        self.cluster_revision = self.cluster_revision.max(header_revision);
+        // Progress notifications carry no events, but tell that every event up to
+        // their revision was delivered, so a lost watch can resume from there.
+        if response.events().is_empty() && !response.created() && !self.once {
+            self.items.push_back(Item::Progress(header_revision));
+            return;
+        }
+        for event in response.events() {
+            let revision = event.kv().map_or(0, |kv| kv.mod_revision());
+            self.items.push_back(
+                match PyWatchEvent::decoded(event.clone(), self.text_decoding, self.codec.clone()) {
+                    Ok(event) => Item::Event(event, revision),
+                    Err(error) => Item::Error(error),
+                },
+            );
+        }
+        self.items.push_back(Item::Through(header_revision));
+    }
+
+    fn ended(&self, end: End) -> Option<Result<PyWatchEvent, PyErr>> {
+        match end {
+            End::Canceled => None,
+            End::Compacted(compact_revision) => Some(Err(compacted_error(compact_revision))),
+            End::Failed(error) => Some(Err(PyClientError(error).into())),
+            End::Lost(error) => Some(Err(self.connection_lost(error))),
+        }
+    }
+
+    /// Returns `ConnectionLostError` with the revision to watch again from,
+    /// caused by the `error` which ended the stream, if any.
+    fn connection_lost(&self, error: Option<etcd_client::Error>) -> PyErr {
+        let lost = ConnectionLostError::new_err(format!(
+            "the watch stream ended after revision {}",
+            self.revision
+        ));
+        Python::with_gil(|py| {
+            let _ = lost.value(py).setattr("revision", self.revision);
+            if let Some(error) = error {
+                lost.set_cause(py, Some(PyClientError(error).into()));
+            }
+        });
+        lost
+    }
 }
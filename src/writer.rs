@@ -0,0 +1,167 @@
+use etcd_client::{Txn, TxnOp};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::communicator::PyCommunicator;
+use crate::error::ClientError;
+use crate::runtime::{future_into_py, get_runtime, wait_shutdown};
+
+enum Command {
+    Put(Vec<u8>, Vec<u8>),
+    Flush(oneshot::Sender<()>),
+}
+
+type FirstError = Arc<std::sync::Mutex<Option<PyErr>>>;
+
+/// Queues puts and writes them in the background, each batch of up to `batch_size` puts
+/// in a single transaction, while the application keeps queueing the next ones.
+#[pyclass(name = "PutWriter")]
+pub struct PyPutWriter {
+    queue: mpsc::Sender<Command>,
+    // The first batch which failed to be written, after which the remaining puts are dropped.
+    error: FirstError,
+}
+
+#[pymethods]
+impl PyPutWriter {
+    /// Queues a put, waiting while the queue is full.
+    fn put<'a>(&'a self, py: Python<'a>, key: &PyBytes, value: &PyBytes) -> PyResult<&'a PyAny> {
+        raise_error(py, &self.error)?;
+        let command = Command::Put(key.as_bytes().to_vec(), value.as_bytes().to_vec());
+        let queue = self.queue.clone();
+        future_into_py(py, async move {
+            queue.send(command).await.map_err(|_| closed_error())
+        })
+    }
+
+    /// Waits until every put queued so far has been written,
+    /// raising the error of the first batch which failed.
+    fn flush<'a>(&'a self, py: Python<'a>) -> PyResult<&'a PyAny> {
+        let queue = self.queue.clone();
+        let error = self.error.clone();
+        future_into_py(py, async move {
+            let (done, flushed) = oneshot::channel();
+            queue
+                .send(Command::Flush(done))
+                .await
+                .map_err(|_| closed_error())?;
+            flushed.await.map_err(|_| closed_error())?;
+            Python::with_gil(|py| raise_error(py, &error))
+        })
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<&PyAny> {
+        future_into_py(py, async move { Ok(slf) })
+    }
+
+    fn __aexit__<'a>(&'a self, py: Python<'a>, _args: &PyTuple) -> PyResult<&'a PyAny> {
+        self.flush(py)
+    }
+}
+
+impl PyPutWriter {
+    pub fn new(
+        py: Python<'_>,
+        communicator: Py<PyCommunicator>,
+        batch_size: usize,
+        queue_size: usize,
+    ) -> PyResult<Self> {
+        let (queue, commands) = mpsc::channel(queue_size);
+        let error = FirstError::default();
+        get_runtime(py)?.spawn(write(communicator, commands, batch_size, error.clone()));
+        Ok(PyPutWriter { queue, error })
+    }
+}
+
+/// Writes the queued puts until the writer is dropped or the interpreter exits.
+async fn write(
+    communicator: Py<PyCommunicator>,
+    mut commands: mpsc::Receiver<Command>,
+    batch_size: usize,
+    error: FirstError,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut keys = HashSet::with_capacity(batch_size);
+    // Put back when it could not join the batch, as etcd rejects a key put twice in one txn.
+    let mut pending = None;
+    loop {
+        let command = match pending.take() {
+            Some(command) => command,
+            None if batch.is_empty() => {
+                let command = tokio::select! {
+                    command = commands.recv() => command,
+                    _ = wait_shutdown() => None,
+                };
+                match command {
+                    Some(command) => command,
+                    None => return,
+                }
+            }
+            // Writes the batch once no more puts are queued, rather than waiting for it to fill up.
+            None => match commands.try_recv() {
+                Ok(command) => command,
+                Err(_) => {
+                    write_batch(&communicator, &mut batch, &mut keys, &error).await;
+                    continue;
+                }
+            },
+        };
+
+        match command {
+            Command::Put(key, value) if batch.len() < batch_size && !keys.contains(&key) => {
+                if error.lock().unwrap().is_none() {
+                    keys.insert(key.clone());
+                    batch.push(TxnOp::put(key, value, None));
+                }
+            }
+            Command::Put(..) => {
+                pending = Some(command);
+                write_batch(&communicator, &mut batch, &mut keys, &error).await;
+            }
+            Command::Flush(done) => {
+                write_batch(&communicator, &mut batch, &mut keys, &error).await;
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+async fn write_batch(
+    communicator: &Py<PyCommunicator>,
+    batch: &mut Vec<TxnOp>,
+    keys: &mut HashSet<Vec<u8>>,
+    error: &FirstError,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    let txn = Txn::new().and_then(std::mem::take(batch));
+    keys.clear();
+
+    let request = Python::with_gil(|py| {
+        communicator
+            .borrow(py)
+            .request("writer", None, move |mut client| {
+                let txn = txn.clone();
+                async move { client.txn(txn).await }
+            })
+    });
+    if let Err(e) = request.await {
+        log::error!("failed to write a batch of puts, dropping the remaining ones: {e}");
+        error.lock().unwrap().get_or_insert(e);
+    }
+}
+
+fn raise_error(py: Python<'_>, error: &FirstError) -> PyResult<()> {
+    match *error.lock().unwrap() {
+        Some(ref e) => Err(e.clone_ref(py)),
+        None => Ok(()),
+    }
+}
+
+fn closed_error() -> PyErr {
+    ClientError::new_err("the writer is closed")
+}
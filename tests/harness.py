@@ -155,6 +155,7 @@ class AsyncEtcd:
         else:
             self._connect_options = None
 
+        self.addr = addr
         self.ns = namespace
         log.info('using etcd cluster from {} with namespace "{}"', addr, namespace)
         self.encoding = encoding
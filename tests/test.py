@@ -1,7 +1,59 @@
 import asyncio
+import concurrent.futures
+import json
+import logging
+import os
+import pickle
+import shutil
+import socket
+import subprocess
+import sys
+import time
 
 import pytest
-from etcd_client import CondVar, WatchEventType
+from etcd_client import (
+    Client,
+    CircuitOpenError,
+    ClientError,
+    CompactedError,
+    Compare,
+    CompareOp,
+    ConflictError,
+    CondVar,
+    ConnectionLostError,
+    ConnectionState,
+    ConnectOptions,
+    EndpointError,
+    EtcdLockOption,
+    GRPCStatusCode,
+    GRPCStatusError,
+    InvalidArgsError,
+    InvalidUriError,
+    IoError,
+    LoadBalancing,
+    LockError,
+    NotFoundError,
+    PermissionDeniedError,
+    PermissionType,
+    ReplayCommunicator,
+    ReplayMismatchError,
+    RequestTimeoutError,
+    Schema,
+    SchemaError,
+    TextDecoding,
+    Txn,
+    TxnOp,
+    TxnResponse,
+    UnavailableError,
+    Utf8Error,
+    WatchEvent,
+    WatchEventType,
+    Workload,
+    bench,
+    configure_shutdown,
+    metrics_snapshot,
+)
+from etcd_client.testing import EtcdServer
 from tests.harness import AsyncEtcd, ConfigScopes
 
 
@@ -262,6 +314,77 @@ async def test_watch(etcd: AsyncEtcd) -> None:
     assert records_prefix[3].value == ""
 
 
+@pytest.mark.asyncio
+async def test_watch_asyncio_ready_event(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    ready = asyncio.Event()
+
+    async with etcd.etcd.connect() as communicator:
+
+        async def _watch():
+            async for event in communicator.watch(b"ready-event", once=True, ready_event=ready):
+                return event
+
+        async with asyncio.timeout(10):
+            watch = asyncio.create_task(_watch())
+            await ready.wait()
+            await communicator.put(b"ready-event", b"1")
+            assert (await watch).value == b"1"
+
+        with pytest.raises(TypeError):
+            communicator.watch(b"ready-event", ready_event=object())
+
+
+@pytest.mark.asyncio
+async def test_watch_options(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"watch-options/a", b"1")
+        await communicator.put(b"watch-options/a", b"2")
+        await communicator.delete(b"watch-options/a")
+        revision = (await communicator.status()).header().revision()
+
+        events = []
+        async with asyncio.timeout(10):
+            async for event in communicator.watch(
+                b"watch-options/",
+                prefix=True,
+                prev_kv=True,
+                start_revision=revision - 2,
+                filters=[WatchEventType.DELETE],
+            ):
+                events.append(event)
+                if len(events) == 2:
+                    break
+
+        assert events == [
+            WatchEvent(b"watch-options/a", b"1", WatchEventType.PUT),
+            WatchEvent(b"watch-options/a", b"2", WatchEventType.PUT, b"1"),
+        ]
+
+
+@pytest.mark.asyncio
+async def test_watch_lag(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        for i in range(3):
+            await communicator.put(b"lag/key", str(i).encode())
+        revision = (await communicator.status()).header().revision()
+
+        # Replaying the history sends the events at once, along with the latest revision.
+        watch = communicator.watch(b"lag/", prefix=True, start_revision=revision - 2)
+        assert watch.lag == 0
+        lags = []
+        async with asyncio.timeout(10):
+            async for _ in watch:
+                lags.append(watch.lag)
+                if len(lags) == 3:
+                    break
+        assert lags == [2, 1, 0]
+
+
 @pytest.mark.asyncio
 async def test_watch_once(etcd: AsyncEtcd) -> None:
     etcd = await etcd
@@ -309,3 +432,1876 @@ async def test_watch_once(etcd: AsyncEtcd) -> None:
     assert records_prefix[0].key == "wow/city1"
     assert records_prefix[0].event == WatchEventType.PUT
     assert records_prefix[0].value == "seoul"
+
+
+@pytest.mark.asyncio
+async def test_lock_fencing_token(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    tokens = []
+    for _ in range(2):
+        lock_client = etcd.etcd.with_lock(EtcdLockOption(lock_name=b"fencing"))
+        assert lock_client.fencing_token is None
+        async with lock_client:
+            tokens.append(lock_client.fencing_token)
+        assert lock_client.fencing_token is None
+
+    assert tokens[0] is not None
+    assert tokens[1] > tokens[0]
+
+    # A holder which waited for the lock gets the create revision of its own key,
+    # regardless of the writes made while it waited.
+    first = etcd.etcd.with_lock(EtcdLockOption(lock_name=b"fencing-contended"))
+    second = etcd.etcd.with_lock(EtcdLockOption(lock_name=b"fencing-contended"))
+
+    async def _wait_for_lock() -> tuple[int, bool]:
+        async with second as communicator:
+            [key] = await communicator.keys_prefix(b"fencing-contended")
+            created_at = Compare.create_revision(
+                key, CompareOp.EQUAL, second.fencing_token
+            )
+            txn = Txn().when([created_at])
+            return second.fencing_token, (await communicator.txn(txn)).succeeded()
+
+    async with asyncio.timeout(10):
+        async with first:
+            first_token = first.fencing_token
+            waiter = asyncio.create_task(_wait_for_lock())
+            await asyncio.sleep(0.5)
+            await etcd.put("fencing-unrelated", "1")
+        second_token, matches_key = await waiter
+    assert matches_key
+    assert second_token > first_token
+
+
+@pytest.mark.asyncio
+async def test_lock_timeout_releases_orphan(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.with_lock(EtcdLockOption(lock_name=b"orphan")):
+        with pytest.raises(LockError):
+            async with etcd.etcd.with_lock(EtcdLockOption(lock_name=b"orphan", timeout=0.5)):
+                pass
+
+    # The timed-out waiter must not keep holding the lock after the first holder is gone.
+    async with asyncio.timeout(5):
+        async with etcd.etcd.with_lock(EtcdLockOption(lock_name=b"orphan")):
+            pass
+
+
+@pytest.mark.asyncio
+async def test_lock_cancellation_releases_orphan(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.with_lock(EtcdLockOption(lock_name=b"cancelled")):
+        waiter = etcd.etcd.with_lock(EtcdLockOption(lock_name=b"cancelled"))
+        with pytest.raises(TimeoutError):
+            await asyncio.wait_for(waiter.__aenter__(), timeout=0.5)
+
+    # The cancelled waiter must not keep holding the lock after the first holder is gone.
+    async with asyncio.timeout(5):
+        async with etcd.etcd.with_lock(EtcdLockOption(lock_name=b"cancelled")):
+            pass
+
+
+@pytest.mark.asyncio
+async def test_keepalive_callback(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    events = []
+
+    options = ConnectOptions().with_keepalive_callback(events.append)
+    client = Client([str(etcd.addr)], options)
+    lock_client = client.with_lock(EtcdLockOption(lock_name=b"keepalive", ttl=1))
+    async with lock_client as communicator:
+        lease_id = lock_client.lease_id
+        # Revoking the lease behind the lock makes the next keepalive fail.
+        await communicator.lease_revoke(lease_id)
+        async with asyncio.timeout(5):
+            while not events:
+                await asyncio.sleep(0.05)
+
+    # Releasing the lock stops the keepalive without notifying it.
+    async with client.with_lock(EtcdLockOption(lock_name=b"keepalive", ttl=1)):
+        pass
+    await asyncio.sleep(0.3)
+
+    assert len(events) == 1
+    assert events[0].kind == "lock"
+    assert events[0].name == b"keepalive"
+    assert events[0].lease_id == lease_id
+    assert not events[0].aborted
+    assert events[0].error is not None
+
+
+@pytest.mark.asyncio
+async def test_election(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        lease = await communicator.lease_grant(10)
+        res = await communicator.campaign(b"election", b"node-1", lease.id())
+        leader_key = res.leader()
+        assert bytes(leader_key.name()) == b"election"
+        assert leader_key.lease() == lease.id()
+
+        current = await communicator.leader(b"election")
+        assert bytes(current.kv().value()) == b"node-1"
+
+        await communicator.proclaim(b"node-1:8080", leader_key)
+        current = await communicator.leader(b"election")
+        assert bytes(current.kv().value()) == b"node-1:8080"
+
+        await communicator.resign(leader_key)
+        await communicator.lease_revoke(lease.id())
+
+
+@pytest.mark.asyncio
+async def test_elect_lost_on_leader_key_deletion(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.elect(b"elect", b"node-1", ttl=10) as leadership:
+        async with etcd.etcd.connect() as communicator:
+            current = await communicator.leader(b"elect")
+            assert bytes(current.kv().value()) == b"node-1"
+            await communicator.delete(bytes(leadership.leader_key().key()))
+
+        async with asyncio.timeout(5):
+            await leadership.lost()
+
+
+@pytest.mark.asyncio
+async def test_run_as_leader(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    runs: list[str] = []
+    started = asyncio.Event()
+
+    async def _task() -> str:
+        runs.append("started")
+        if len(runs) == 1:
+            started.set()
+            try:
+                await asyncio.sleep(60)
+            except asyncio.CancelledError:
+                runs.append("cancelled")
+                raise
+        return "done"
+
+    runner = asyncio.create_task(
+        etcd.etcd.run_as_leader(b"singleton", b"node-1", _task, ttl=10)
+    )
+    async with asyncio.timeout(10):
+        await started.wait()
+        async with etcd.etcd.connect() as communicator:
+            current = await communicator.leader(b"singleton")
+            # Losing the leadership cancels the task, which runs again once re-elected.
+            await communicator.delete(bytes(current.kv().key()))
+        assert await runner == "done"
+    assert runs == ["started", "cancelled", "started"]
+
+    async with etcd.etcd.connect() as communicator:
+        assert await communicator.keys_prefix(b"singleton") == []
+
+    # Campaigns failing for good, e.g. with a TTL over etcd's limit, are not retried.
+    async with asyncio.timeout(10):
+        with pytest.raises(GRPCStatusError):
+            await etcd.etcd.run_as_leader(b"singleton", b"node-1", _task, ttl=10**10)
+
+
+@pytest.mark.asyncio
+async def test_member_list_and_status(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        res = await communicator.member_list()
+        members = res.members()
+        assert len(members) >= 1
+        assert all(member.client_urls() for member in members)
+        assert res.header().member_id() in {member.id() for member in members}
+
+        status = await communicator.status()
+        assert status.leader() in {member.id() for member in members}
+        assert status.db_size() > 0
+
+
+@pytest.mark.asyncio
+async def test_endpoint_health(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    results = await etcd.etcd.health_all()
+    assert len(results) >= 1
+    assert all(health.healthy and health.error is None for health in results)
+    assert all(health.latency > 0 for health in results)
+
+    health = await etcd.etcd.health("http://127.0.0.1:1")
+    assert not health.healthy
+    assert health.endpoint == "http://127.0.0.1:1"
+    assert health.error is not None
+
+    # A member which accepts connections but never answers is reported once timed out.
+    with socket.socket() as blackhole:
+        blackhole.bind(("127.0.0.1", 0))
+        blackhole.listen()
+        host, port = blackhole.getsockname()
+        health = await etcd.etcd.health(f"http://{host}:{port}", timeout=0.5)
+        assert not health.healthy
+        assert "timed out" in health.error
+
+    with pytest.raises(InvalidArgsError):
+        await etcd.etcd.health("http://127.0.0.1:1", timeout=0)
+
+
+@pytest.mark.asyncio
+async def test_for_endpoint(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    [health, *_] = await etcd.etcd.health_all()
+    async with etcd.etcd.for_endpoint(health.endpoint).connect() as communicator:
+        status = await communicator.status()
+        res = await communicator.defragment()
+        assert res.header().member_id() == status.header().member_id()
+
+
+@pytest.mark.asyncio
+async def test_cluster_info(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    info = await etcd.etcd.cluster_info()
+    assert len(info.members) >= 1
+    assert all(member.status is not None for member in info.members)
+    assert [member.member.id() for member in info.members if member.is_leader] == [
+        info.leader
+    ]
+    assert info.alarms == []
+
+    with pytest.raises(InvalidArgsError):
+        await etcd.etcd.cluster_info(timeout=-1)
+
+
+@pytest.mark.asyncio
+async def test_user_change_password_of_unknown_user(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(GRPCStatusError):
+            await communicator.user_change_password("no-such-user", "secret")
+
+
+@pytest.mark.asyncio
+async def test_authenticate_unknown_user(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(GRPCStatusError):
+            await communicator.authenticate("no-such-user", "secret")
+
+
+@pytest.mark.asyncio
+async def test_with_user_does_not_affect_original_client(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    tenant = etcd.etcd.with_user("no-such-user", "secret")
+    with pytest.raises(GRPCStatusError):
+        async with tenant.connect():
+            pass
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"with-user", b"value")
+        assert bytes(await communicator.get(b"with-user")) == b"value"
+        await communicator.delete(b"with-user")
+
+
+@pytest.mark.asyncio
+async def test_can_unknown_user(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(GRPCStatusError):
+            await communicator.can("no-such-user", b"/key", PermissionType.READ)
+
+
+@pytest.mark.asyncio
+async def test_user_grant_role_to_unknown_user(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(GRPCStatusError):
+            await communicator.user_grant_role("no-such-user", "root")
+        with pytest.raises(GRPCStatusError):
+            await communicator.user_revoke_role("no-such-user", "root")
+
+
+@pytest.mark.asyncio
+async def test_role_management(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.role_add("test-role")
+        try:
+            assert "test-role" in (await communicator.role_list()).roles()
+            assert (await communicator.role_get("test-role")).permissions() == []
+        finally:
+            await communicator.role_delete("test-role")
+
+        assert "test-role" not in (await communicator.role_list()).roles()
+        with pytest.raises(GRPCStatusError):
+            await communicator.role_get("test-role")
+
+
+@pytest.mark.asyncio
+async def test_role_grant_permission(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.role_add("test-role")
+        try:
+            await communicator.role_grant_permission(
+                "test-role", b"/tenant/a/", perm_type=PermissionType.READWRITE, prefix=True
+            )
+            await communicator.role_grant_permission("test-role", b"/a", b"/c")
+
+            perms = (await communicator.role_get("test-role")).permissions()
+            by_key = {bytes(perm.key()): perm for perm in perms}
+            assert by_key[b"/tenant/a/"].perm_type() == PermissionType.READWRITE
+            assert by_key[b"/tenant/a/"].is_prefix()
+            assert by_key[b"/a"].perm_type() == PermissionType.READ
+            assert bytes(by_key[b"/a"].range_end()) == b"/c"
+
+            await communicator.role_revoke_permission("test-role", b"/tenant/a/", prefix=True)
+            perms = (await communicator.role_get("test-role")).permissions()
+            assert [bytes(perm.key()) for perm in perms] == [b"/a"]
+
+            with pytest.raises(InvalidArgsError):
+                await communicator.role_grant_permission(
+                    "test-role", b"/a", b"/c", prefix=True
+                )
+        finally:
+            await communicator.role_delete("test-role")
+
+
+@pytest.mark.asyncio
+async def test_auth_retry_limit_option(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    options = ConnectOptions().with_auth_retry_limit(0)
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.put(b"auth-retry", b"value")
+        assert bytes(await communicator.get(b"auth-retry")) == b"value"
+        await communicator.delete(b"auth-retry")
+
+
+def test_tls_options_validation(tmp_path) -> None:
+    with pytest.raises(IoError):
+        ConnectOptions().with_tls(str(tmp_path / "missing-ca.pem"))
+
+    ca_path = tmp_path / "ca.pem"
+    ca_path.write_text("")
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_tls(str(ca_path), cert_path=str(ca_path))
+
+    ConnectOptions().with_tls_domain("etcd.example.com").with_tls(str(ca_path))
+
+
+@pytest.mark.asyncio
+async def test_discover_srv_without_records() -> None:
+    client = Client.discover_srv("etcd-client-py.invalid")
+    with pytest.raises(EndpointError):
+        async with client.connect():
+            pass
+
+
+@pytest.mark.asyncio
+async def test_pinned_load_balancing(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    [health, *_] = await etcd.etcd.health_all()
+    options = (
+        ConnectOptions()
+        .with_load_balancing(LoadBalancing.PINNED)
+        .with_serializable_reads(prefer_followers=True)
+    )
+    client = Client(["http://127.0.0.1:1", health.endpoint], options)
+    async with client.connect() as communicator:
+        await communicator.put(b"pinned", b"value")
+        assert bytes(await communicator.get(b"pinned")) == b"value"
+        await communicator.delete(b"pinned")
+
+
+@pytest.mark.asyncio
+async def test_from_env(etcd: AsyncEtcd, monkeypatch) -> None:
+    etcd = await etcd
+    monkeypatch.setenv("ETCD_ENDPOINTS", f"http://{etcd.addr}, ")
+    async with Client.from_env().connect() as communicator:
+        await communicator.put(b"from-env", b"value")
+        assert bytes(await communicator.get(b"from-env")) == b"value"
+        await communicator.delete(b"from-env")
+
+    monkeypatch.setenv("ETCDCTL_USER", "root")
+    monkeypatch.delenv("ETCDCTL_PASSWORD", raising=False)
+    with pytest.raises(InvalidArgsError):
+        Client.from_env()
+
+
+@pytest.mark.asyncio
+async def test_from_url(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    client = Client.from_url(f"etcd://{etcd.addr}/?timeout=5&connect_timeout=1")
+    async with client.connect() as communicator:
+        await communicator.put(b"from-url", b"value")
+        assert bytes(await communicator.get(b"from-url")) == b"value"
+        await communicator.delete(b"from-url")
+
+    for url in [
+        "redis://127.0.0.1:2379",
+        "etcd://127.0.0.1:2379/path",
+        "etcd://root@127.0.0.1:2379",
+        "etcd://127.0.0.1:2379/?timeout=soon",
+        "etcd://127.0.0.1:2379/?unknown=1",
+    ]:
+        with pytest.raises(InvalidUriError):
+            Client.from_url(url)
+    with pytest.raises(EndpointError):
+        Client.from_url("etcd:///")
+
+
+@pytest.mark.asyncio
+async def test_endpoint_validation(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    async with Client(str(etcd.addr)).connect() as communicator:
+        await communicator.put(b"single-endpoint", b"value")
+        assert bytes(await communicator.get(b"single-endpoint")) == b"value"
+        await communicator.delete(b"single-endpoint")
+
+    for endpoints in [[], ["http:://127.0.0.1:2379"], ["127.0.0.1:2379", "http://"]]:
+        with pytest.raises(EndpointError):
+            Client(endpoints)
+    with pytest.raises(EndpointError, match="grpc://127.0.0.1:2379"):
+        Client("grpc://127.0.0.1:2379")
+
+
+@pytest.mark.asyncio
+async def test_open_and_close(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    communicator = await Client([f"http://{etcd.addr}"]).open()
+    await communicator.put(b"open-close", b"value")
+    assert bytes(await communicator.get(b"open-close")) == b"value"
+    await communicator.delete(b"open-close")
+
+    watch = communicator.watch(b"open-close")
+
+    async def watch_until_closed() -> list:
+        return [event async for event in watch]
+
+    watch_task = asyncio.create_task(watch_until_closed())
+    await asyncio.sleep(0.1)
+    await communicator.close()
+    assert await asyncio.wait_for(watch_task, timeout=5) == []
+
+    with pytest.raises(ClientError):
+        await communicator.get(b"open-close")
+
+
+@pytest.mark.asyncio
+async def test_reconnect_option(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_reconnect(3, initial_backoff=1.0, max_backoff=0.5)
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_reconnect(3, jitter=2.0)
+
+    options = (
+        ConnectOptions()
+        .with_connect_timeout(1)
+        .with_reconnect(3, initial_backoff=0.01)
+    )
+    before = metrics_snapshot()["reconnects"]
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError):
+            await communicator.get(b"reconnect")
+    assert metrics_snapshot()["reconnects"] == before + 3
+
+    # Requests which may have been applied are not sent again after reconnecting.
+    options = ConnectOptions().with_reconnect(3, initial_backoff=0.01)
+    async with etcd.etcd.connect(options) as communicator:
+        put_fault = communicator.inject_fault("put", error=GRPCStatusCode.Unavailable)
+        with pytest.raises(UnavailableError):
+            await communicator.put(b"reconnect", b"value")
+        assert put_fault.injected == 1
+
+        get_fault = communicator.inject_fault(
+            "get", error=GRPCStatusCode.Unavailable, times=2
+        )
+        assert await communicator.get(b"reconnect") is None
+        assert get_fault.injected == 2
+
+
+@pytest.mark.asyncio
+async def test_circuit_breaker_option(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_circuit_breaker(0)
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_circuit_breaker(3, reset_timeout=-1.0)
+
+    options = (
+        ConnectOptions()
+        .with_connect_timeout(1)
+        .with_circuit_breaker(2, reset_timeout=0.5)
+    )
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        for _ in range(2):
+            with pytest.raises(ClientError) as e:
+                await communicator.get(b"circuit-breaker")
+            assert not isinstance(e.value, CircuitOpenError)
+        with pytest.raises(CircuitOpenError):
+            await communicator.get(b"circuit-breaker")
+
+        # Once the reset timeout elapses, a single probe gets through.
+        await asyncio.sleep(0.5)
+        results = await asyncio.gather(
+            communicator.get(b"circuit-breaker"),
+            communicator.get(b"circuit-breaker"),
+            return_exceptions=True,
+        )
+        assert all(isinstance(result, ClientError) for result in results)
+        assert sum(isinstance(result, CircuitOpenError) for result in results) == 1
+
+
+@pytest.mark.asyncio
+async def test_health_check(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_health_check(0)
+
+    async with etcd.etcd.connect() as communicator:
+        assert communicator.connected is None
+
+    options = ConnectOptions().with_health_check(0.1)
+    async with etcd.etcd.connect(options) as communicator:
+        async with asyncio.timeout(5):
+            while not communicator.connected:
+                await asyncio.sleep(0.1)
+
+
+@pytest.mark.asyncio
+async def test_connection_state(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    communicator = await etcd.etcd.open()
+    assert communicator.connection_state == ConnectionState.CONNECTING
+    changes = communicator.connection_state_changes()
+    assert await anext(changes) == ConnectionState.CONNECTING
+
+    await communicator.ping()
+    assert communicator.connection_state == ConnectionState.READY
+    assert await anext(changes) == ConnectionState.READY
+
+    await communicator.close()
+    with pytest.raises(StopAsyncIteration):
+        await anext(changes)
+
+    options = ConnectOptions().with_connect_timeout(1).with_timeout(1)
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError):
+            await communicator.ping()
+        assert communicator.connection_state == ConnectionState.TRANSIENT_FAILURE
+
+
+@pytest.mark.asyncio
+async def test_ping(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        assert await communicator.ping() >= 0
+
+    options = ConnectOptions().with_connect_timeout(1).with_timeout(1)
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError):
+            await communicator.ping()
+
+
+@pytest.mark.asyncio
+async def test_reconnect(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    communicator = await etcd.etcd.open()
+    await communicator.put(b"reconnect", b"value")
+    await communicator.reconnect()
+    assert bytes(await communicator.get(b"reconnect")) == b"value"
+    await communicator.delete(b"reconnect")
+
+    await communicator.close()
+    with pytest.raises(ClientError):
+        await communicator.reconnect()
+
+
+@pytest.mark.asyncio
+async def test_concurrent_requests(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        keys = [f"concurrent/{i}".encode() for i in range(100)]
+        await asyncio.gather(*(communicator.put(key, key) for key in keys))
+        values = await asyncio.gather(*(communicator.get(key) for key in keys))
+        assert [bytes(value) for value in values] == keys
+        await communicator.delete_prefix(b"concurrent/")
+
+
+@pytest.mark.asyncio
+async def test_channels(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_channels(0)
+
+    async with etcd.etcd.connect(ConnectOptions().with_channels(4)) as communicator:
+        keys = [f"channels/{i}".encode() for i in range(100)]
+        await asyncio.gather(*(communicator.put(key, key) for key in keys))
+        values = await asyncio.gather(*(communicator.get(key) for key in keys))
+        assert [bytes(value) for value in values] == keys
+        await communicator.delete_prefix(b"channels/")
+
+
+@pytest.mark.asyncio
+async def test_get_default_and_strict(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"get/empty", b"")
+        assert await communicator.get(b"get/missing") is None
+        assert await communicator.get(b"get/missing", b"default") == b"default"
+        assert await communicator.get(b"get/empty", b"default") == b""
+        assert await communicator.get(b"get/empty", strict=True) == b""
+
+        with pytest.raises(NotFoundError) as exc_info:
+            await communicator.get(b"get/missing", strict=True)
+        assert exc_info.value.code == GRPCStatusCode.NotFound
+        assert exc_info.value.key == b"get/missing"
+
+
+@pytest.mark.asyncio
+async def test_update(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    def _increment(value):
+        return str(int(value or b"0") + 1).encode()
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.delete(b"update/counter")
+        assert await communicator.update(b"update/counter", _increment) == b"1"
+        await asyncio.gather(
+            *(communicator.update(b"update/counter", _increment) for _ in range(10))
+        )
+        assert await communicator.get(b"update/counter") == b"11"
+
+        attempts = []
+
+        def _conflicting(value):
+            attempts.append(value)
+            # Modifies the key between the read and the put.
+            asyncio.run_coroutine_threadsafe(
+                communicator.put(b"update/conflict", b"other"), loop
+            ).result()
+            return b"mine"
+
+        loop = asyncio.get_running_loop()
+        with pytest.raises(ConflictError) as exc_info:
+            await communicator.update(b"update/conflict", _conflicting, max_retries=2)
+        assert len(attempts) == 3
+        assert exc_info.value.key == b"update/conflict"
+
+
+@pytest.mark.asyncio
+async def test_copy_and_move_prefix(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        for i in range(10):
+            await communicator.put(f"migration/src/{i}".encode(), str(i).encode())
+
+        progress = []
+        copied = await communicator.copy_prefix(
+            b"migration/src/",
+            b"migration/copy/",
+            chunk_size=3,
+            progress=lambda done, total: progress.append((done, total)),
+        )
+        assert copied == 10
+        assert progress == [(3, 10), (6, 10), (9, 10), (10, 10)]
+        assert await communicator.get_prefix(b"migration/copy/") == [
+            (f"migration/copy/{i}".encode(), str(i).encode()) for i in range(10)
+        ]
+        assert len(await communicator.keys_prefix(b"migration/src/")) == 10
+
+        assert await communicator.move_prefix(b"migration/copy/", b"migration/moved/") == 10
+        assert await communicator.keys_prefix(b"migration/copy/") == []
+        assert len(await communicator.keys_prefix(b"migration/moved/")) == 10
+
+        with pytest.raises(InvalidArgsError):
+            await communicator.copy_prefix(b"migration/", b"migration/src/")
+
+
+@pytest.mark.asyncio
+async def test_export_and_import_prefix(etcd: AsyncEtcd, tmp_path) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        for i in range(5):
+            await communicator.put(f"export/src/{i}".encode(), str(i).encode())
+        await communicator.put(b"export/src/binary", b"\xff\x00")
+
+        for path in [tmp_path / "export.json", tmp_path / "export.jsonl"]:
+            exported = await communicator.export_prefix(
+                b"export/src/", str(path), chunk_size=2
+            )
+            assert exported == 6
+            await communicator.delete_prefix(b"export/dst/")
+            await communicator.put(b"export/dst/0", b"kept")
+
+            imported = await communicator.import_prefix(
+                str(path), b"export/dst/", chunk_size=4
+            )
+            assert imported == 5
+            assert await communicator.get(b"export/dst/0") == b"kept"
+            assert await communicator.get(b"export/dst/binary") == b"\xff\x00"
+
+            imported = await communicator.import_prefix(
+                str(path), b"export/dst/", overwrite=True
+            )
+            assert imported == 6
+            assert await communicator.get_prefix(b"export/dst/") == [
+                (key.replace(b"src", b"dst"), value)
+                for key, value in await communicator.get_prefix(b"export/src/")
+            ]
+
+
+@pytest.mark.asyncio
+async def test_service_registry(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        async with communicator.service_registry(b"registry/") as registry:
+            lease_id = await registry.register(b"api", b"node-1", b"10.0.0.1:8080", ttl=2)
+            await registry.register(b"api", b"node-2", b"10.0.0.2:8080", ttl=2)
+            # Outlives its ttl, as its lease is kept alive.
+            await asyncio.sleep(3)
+            assert await communicator.get_prefix(b"registry/api/") == [
+                (b"registry/api/node-1", b"10.0.0.1:8080"),
+                (b"registry/api/node-2", b"10.0.0.2:8080"),
+            ]
+
+            await registry.deregister(b"api", b"node-1")
+            assert await communicator.keys_prefix(b"registry/api/") == [
+                b"registry/api/node-2"
+            ]
+            with pytest.raises(NotFoundError):
+                await communicator.lease_revoke(lease_id)
+
+            with pytest.raises(InvalidArgsError):
+                await registry.register(b"api/v2", b"node-1", b"")
+
+        assert await communicator.keys_prefix(b"registry/") == []
+
+
+@pytest.mark.asyncio
+async def test_service_discovery(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        registry = communicator.service_registry(b"discovery/")
+        await registry.register(b"api", b"node-1", b"10.0.0.1:8080")
+        await registry.register(b"apiv2", b"node-1", b"10.0.0.1:9090")
+
+        async with registry, registry.discover(b"api") as discovery:
+            assert discovery.instances() == {b"node-1": b"10.0.0.1:8080"}
+            changes = discovery.__aiter__()
+
+            await registry.register(b"api", b"node-2", b"10.0.0.2:8080")
+            change = await asyncio.wait_for(changes.__anext__(), 5)
+            assert change == (b"node-2", None, b"10.0.0.2:8080")
+
+            await registry.deregister(b"api", b"node-1")
+            change = await asyncio.wait_for(changes.__anext__(), 5)
+            assert change == (b"node-1", b"10.0.0.1:8080", None)
+            assert discovery.instances() == {b"node-2": b"10.0.0.2:8080"}
+
+
+@pytest.mark.asyncio
+async def test_presence(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        async with communicator.presence(b"workers", b"node-1", ttl=2) as presence:
+            assert presence.lease_id is not None
+
+            async with communicator.members(b"workers") as members:
+                assert members.members() == [b"node-1"]
+                events = members.__aiter__()
+
+                async with communicator.presence(b"workers", b"node-2", ttl=2):
+                    event = await asyncio.wait_for(events.__anext__(), 5)
+                    assert event == ("join", b"node-2")
+                    # Outlives its ttl, as its lease is kept alive.
+                    await asyncio.sleep(3)
+                    assert b"node-2" in members and len(members) == 2
+
+                event = await asyncio.wait_for(events.__anext__(), 5)
+                assert event == ("leave", b"node-2")
+
+        assert presence.lease_id is None
+        assert await communicator.keys_prefix(b"presence/workers/") == []
+
+        with pytest.raises(InvalidArgsError):
+            communicator.presence(b"a/b", b"node-1")
+
+
+@pytest.mark.asyncio
+async def test_changefeed(etcd: AsyncEtcd, tmp_path) -> None:
+    etcd = await etcd
+    checkpoint = str(tmp_path / "changefeed.checkpoint")
+    path = tmp_path / "changefeed.jsonl"
+
+    async with etcd.etcd.connect() as communicator:
+        changefeed = communicator.changefeed(b"changefeed/", checkpoint, path=str(path))
+        runner = asyncio.create_task(changefeed.run())
+        async with asyncio.timeout(5):
+            while changefeed.revision == 0:
+                await asyncio.sleep(0.1)
+            await communicator.put(b"changefeed/a", b"1")
+            await communicator.delete(b"changefeed/a")
+            while not path.exists() or len(path.read_text().splitlines()) < 2:
+                await asyncio.sleep(0.1)
+            records = [json.loads(line) for line in path.read_text().splitlines()]
+            while changefeed.revision < records[-1]["revision"]:
+                await asyncio.sleep(0.1)
+        runner.cancel()
+
+        assert [(r["key"], r["type"], r.get("value")) for r in records] == [
+            ("changefeed/a", "PUT", "1"),
+            ("changefeed/a", "DELETE", None),
+        ]
+
+        # Resumes after the checkpoint, missing none of the changes made meanwhile.
+        await communicator.put(b"changefeed/b", b"2")
+        delivered = []
+        changefeed = communicator.changefeed(
+            b"changefeed/", checkpoint, sink=delivered.extend
+        )
+        runner = asyncio.create_task(changefeed.run())
+        async with asyncio.timeout(5):
+            while not delivered:
+                await asyncio.sleep(0.1)
+        runner.cancel()
+        assert [(event.key, event.value) for event in delivered] == [
+            (b"changefeed/b", b"2")
+        ]
+        assert delivered[0].event == WatchEventType.PUT
+
+        with pytest.raises(InvalidArgsError):
+            communicator.changefeed(b"changefeed/", checkpoint)
+
+
+@pytest.mark.asyncio
+async def test_record_and_replay(etcd: AsyncEtcd, tmp_path) -> None:
+    etcd = await etcd
+    path = str(tmp_path / "recording.jsonl")
+
+    async def scenario(communicator) -> list:
+        await communicator.put(b"recording/a", b"1")
+        value = await communicator.get(b"recording/a")
+        prefix = await communicator.get_prefix(b"recording/")
+        with pytest.raises(NotFoundError):
+            await communicator.get(b"recording/missing", strict=True)
+        return [value, prefix]
+
+    async with etcd.etcd.connect() as communicator:
+        async with communicator.record(path) as recording:
+            recorded = await scenario(recording)
+
+    async with ReplayCommunicator(path) as replay:
+        assert await scenario(replay) == recorded
+    assert recorded == [b"1", [(b"recording/a", b"1")]]
+
+    with pytest.raises(ReplayMismatchError):
+        async with ReplayCommunicator(path) as replay:
+            await replay.put(b"recording/a", b"2")
+
+    # Recorded requests which were not sent fail the replay too.
+    with pytest.raises(ReplayMismatchError):
+        async with ReplayCommunicator(path) as replay:
+            await replay.put(b"recording/a", b"1")
+
+    # Results which cannot be recorded are returned unchanged, but cannot be replayed.
+    async with etcd.etcd.connect() as communicator:
+        async with communicator.record(path) as recording:
+            txn = Txn().and_then([TxnOp.put(b"recording/b", b"2")])
+            assert (await recording.txn(txn)).succeeded()
+        assert await communicator.get(b"recording/b") == b"2"
+
+    async with ReplayCommunicator(path) as replay:
+        with pytest.raises(InvalidArgsError):
+            await replay.txn(txn)
+
+
+@pytest.mark.asyncio
+async def test_inject_fault(etcd: AsyncEtcd, tmp_path) -> None:
+    etcd = await etcd
+    path = str(tmp_path / "recording.jsonl")
+
+    async with etcd.etcd.connect() as communicator:
+        fault = communicator.inject_fault(
+            "put", b"faults/*", error=GRPCStatusCode.PermissionDenied, every=2
+        )
+        await communicator.put(b"faults/a", b"1")
+        with pytest.raises(PermissionDeniedError) as exc_info:
+            await communicator.put(b"faults/b", b"2")
+        assert exc_info.value.operation == "put"
+        await communicator.put(b"other", b"3")
+        assert fault.injected == 1
+        fault.remove()
+        await communicator.put(b"faults/b", b"2")
+
+        communicator.inject_fault("get", latency=0.2)
+        started = time.monotonic()
+        assert await communicator.get(b"faults/b") == b"2"
+        assert time.monotonic() - started >= 0.2
+        communicator.clear_faults()
+
+        with pytest.raises(InvalidArgsError):
+            communicator.inject_fault("get")
+
+        async with communicator.record(path) as recording:
+            await recording.put(b"faults/c", b"4")
+
+    # Requests failed by a fault are not compared, so that they can be sent again.
+    async with ReplayCommunicator(path) as replay:
+        replay.inject_fault(error=GRPCStatusCode.Unavailable, times=1)
+        with pytest.raises(UnavailableError):
+            await replay.put(b"faults/c", b"4")
+        await replay.put(b"faults/c", b"4")
+
+
+@pytest.mark.asyncio
+@pytest.mark.skipif(shutil.which("etcd") is None, reason="etcd is not on PATH")
+async def test_etcd_server(tmp_path) -> None:
+    async with EtcdServer(data_dir=str(tmp_path / "data")) as server:
+        assert len(server.endpoints) == 1
+        async with server.client().connect() as communicator:
+            await communicator.put(b"server", b"1")
+            assert await communicator.get(b"server") == b"1"
+    assert server.endpoints == []
+    # A data directory which was given is kept.
+    assert (tmp_path / "data").exists()
+
+    with pytest.raises(InvalidArgsError):
+        server.client()
+    with pytest.raises(IoError):
+        await EtcdServer(str(tmp_path / "missing")).start()
+
+
+@pytest.mark.asyncio
+async def test_bench(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    for operation in ["put", "get", "watch"]:
+        workload = Workload(
+            operation, requests=200, concurrency=8, keys=20, prefix=b"bench-test/"
+        )
+        result = await bench(f"http://{etcd.addr}", workload)
+        assert result.operation == operation
+        assert result.requests == 200
+        assert result.errors == 0
+        assert result.throughput > 0
+        assert 0 < result.p50 <= result.p95 <= result.p99 <= result.max
+
+    # The keys of the workload are deleted once done.
+    async with etcd.etcd.connect() as communicator:
+        assert await communicator.get_prefix(b"bench-test/") == []
+
+    with pytest.raises(InvalidArgsError):
+        Workload("delete")
+    with pytest.raises(InvalidArgsError):
+        Workload("put", concurrency=0)
+
+
+@pytest.mark.asyncio
+async def test_as_mapping(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        mapping = communicator.as_mapping(b"mapping/")
+        assert mapping.prefix == b"mapping/"
+        assert await mapping.aget(b"a") is None
+        assert await mapping.aget(b"a", b"default") == b"default"
+
+        await mapping.aset(b"a", b"1")
+        await mapping.aset(b"b", b"2")
+        assert await communicator.get(b"mapping/a") == b"1"
+        assert await mapping.aget(b"a") == b"1"
+        assert await mapping.akeys() == [b"a", b"b"]
+        assert await mapping.aitems() == [(b"a", b"1"), (b"b", b"2")]
+
+        await mapping.adel(b"a")
+        assert await mapping.akeys() == [b"b"]
+        with pytest.raises(KeyError):
+            await mapping.adel(b"a")
+
+    options = ConnectOptions().with_json_codec()
+    async with etcd.etcd.connect(options) as communicator:
+        mapping = communicator.as_mapping(b"json-mapping/")
+        await mapping.aset(b"config", {"replicas": 3})
+        assert await mapping.aitems() == [(b"config", {"replicas": 3})]
+
+
+@pytest.mark.asyncio
+async def test_mapping_schema(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    def _port(value):
+        if not 0 < int(value) < 65536:
+            raise ValueError("not a port")
+
+    schema = Schema(
+        {
+            "db/host": None,
+            "db/port": _port,
+            "features/*": lambda value: value in (b"on", b"off"),
+        }
+    )
+    async with etcd.etcd.connect() as communicator:
+        mapping = communicator.as_mapping(b"schema/", schema=schema)
+        await mapping.put_dict(
+            {"db": {"host": b"db.local", "port": b"5432"}, "features": {"x": b"on"}}
+        )
+        assert await mapping.aget(b"db/port") == b"5432"
+        assert await mapping.aget(b"features/x") == b"on"
+
+        # Nothing is written once a key is rejected.
+        with pytest.raises(SchemaError) as e:
+            await mapping.put_dict({"db": {"host": b"other", "port": b"70000"}})
+        assert e.value.path == "db/port"
+        assert isinstance(e.value.__cause__, ValueError)
+        assert await mapping.aget(b"db/host") == b"db.local"
+
+        with pytest.raises(SchemaError) as e:
+            await mapping.aset(b"features/y", b"maybe")
+        assert e.value.path == "features/y"
+        with pytest.raises(SchemaError) as e:
+            await mapping.aset(b"unknown", b"1")
+        assert e.value.path == "unknown"
+        assert await mapping.aget(b"unknown") is None
+
+        Schema({}, allow_unknown=True).validate({"unknown": b"1"})
+        with pytest.raises(InvalidArgsError):
+            Schema({"db/port": 5432})
+
+
+@pytest.mark.asyncio
+async def test_mirror(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    changes = []
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.delete_prefix(b"mirror/")
+        await communicator.put(b"mirror/a", b"1")
+
+        async with communicator.mirror(b"mirror/") as mirror:
+            mirror.on_change(lambda key, old, new: changes.append((key, old, new)))
+            assert mirror.prefix == b"mirror/"
+            assert mirror.to_dict() == {b"a": b"1"}
+            assert mirror.revision > 0
+
+            await communicator.put(b"mirror/b", b"2")
+            await communicator.put(b"mirror/a", b"3")
+            await communicator.delete(b"mirror/b")
+            async with asyncio.timeout(5):
+                while len(changes) < 3:
+                    await asyncio.sleep(0.05)
+
+            assert changes == [
+                (b"b", None, b"2"),
+                (b"a", b"1", b"3"),
+                (b"b", b"2", None),
+            ]
+            assert mirror[b"a"] == b"3"
+            assert mirror.get(b"b", b"missing") == b"missing"
+            assert b"b" not in mirror
+            assert len(mirror) == 1
+            with pytest.raises(KeyError):
+                mirror[b"b"]
+
+        # Exiting the mirror stops the updates.
+        await communicator.put(b"mirror/c", b"4")
+        await asyncio.sleep(0.2)
+        assert b"c" not in mirror
+
+
+@pytest.mark.asyncio
+async def test_config_watcher(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    limits = []
+    names = []
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(InvalidArgsError):
+            communicator.config_watcher(b"reload/", debounce=-1.0)
+
+        await communicator.delete_prefix(b"reload/")
+        await communicator.put(b"reload/limits/cpu", b"1")
+        await communicator.put(b"reload/name", b"a")
+
+        watcher = communicator.config_watcher(b"reload/", debounce=0.2)
+        watcher.register(
+            b"limits/*", lambda key, old, new: limits.append((key, old, new)), int
+        )
+        watcher.register(b"name", lambda key, old, new: names.append((old, new)))
+        async with watcher:
+            assert limits == [(b"limits/cpu", None, 1)]
+            assert names == [(None, b"a")]
+
+            # Coalesced into a single change, from the first old value to the last new one.
+            for value in (b"2", b"3", b"4"):
+                await communicator.put(b"reload/limits/cpu", value)
+            # Left out, as the value ends up unchanged.
+            await communicator.put(b"reload/name", b"b")
+            await communicator.put(b"reload/name", b"a")
+            async with asyncio.timeout(5):
+                while len(limits) < 2:
+                    await asyncio.sleep(0.05)
+            await asyncio.sleep(0.3)
+
+    assert limits == [(b"limits/cpu", None, 1), (b"limits/cpu", 1, 4)]
+    assert names == [(None, b"a")]
+
+
+@pytest.mark.asyncio
+async def test_bulk(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(InvalidArgsError):
+            await communicator.bulk([], concurrency=0)
+
+        keys = [f"bulk/{i}".encode() for i in range(100)]
+        results = await communicator.bulk(
+            [TxnOp.put(key, key) for key in keys], concurrency=8
+        )
+        assert results == [None] * len(keys)
+
+        results = await communicator.bulk(
+            [TxnOp.get(key) for key in keys] + [TxnOp.get(b"bulk/missing")]
+        )
+        assert [bytes(value) for value in results[:-1]] == keys
+        assert results[-1] is None
+
+        # Values are decoded like those of `get()` on the same view.
+        decoding = communicator.with_options(
+            ConnectOptions().with_text_decoding(TextDecoding.STRICT)
+        )
+        assert await decoding.bulk([TxnOp.get(keys[0])]) == ["bulk/0"]
+        assert await decoding.get(keys[0]) == "bulk/0"
+
+        await communicator.bulk([TxnOp.delete(key) for key in keys])
+        assert await communicator.get_prefix(b"bulk/") == []
+
+
+@pytest.mark.asyncio
+async def test_put_writer(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(InvalidArgsError):
+            communicator.writer(batch_size=0)
+
+        keys = [f"writer/{i:04}".encode() for i in range(1000)]
+        async with communicator.writer(batch_size=64, queue_size=256) as writer:
+            for key in keys:
+                await writer.put(key, key)
+            # Puts of the same key end up in separate transactions.
+            await writer.put(keys[0], b"overwritten")
+
+        assert len(await communicator.get_prefix(b"writer/")) == len(keys)
+        assert bytes(await communicator.get(keys[0])) == b"overwritten"
+        await communicator.delete_prefix(b"writer/")
+
+
+@pytest.mark.asyncio
+async def test_large_value(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    value = bytes(range(256)) * 4096
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"large", value)
+        assert await communicator.get(b"large") == value
+        assert await communicator.get_prefix(b"large") == [(b"large", value)]
+        assert await communicator.keys_prefix(b"large") == [b"large"]
+        assert await communicator.get(b"large/missing") is None
+        await communicator.delete(b"large")
+
+
+@pytest.mark.asyncio
+async def test_logging(caplog: pytest.LogCaptureFixture) -> None:
+    options = (
+        ConnectOptions()
+        .with_connect_timeout(1)
+        .with_timeout(1)
+        .with_reconnect(2, initial_backoff=0.01)
+    )
+    with caplog.at_level(logging.WARNING, logger="etcd_client"):
+        async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+            with pytest.raises(ClientError):
+                await communicator.get(b"logging")
+
+    assert any(
+        record.name.startswith("etcd_client") and "reconnecting" in record.getMessage()
+        for record in caplog.records
+    )
+
+
+@pytest.mark.asyncio
+async def test_slow_threshold(
+    etcd: AsyncEtcd, caplog: pytest.LogCaptureFixture
+) -> None:
+    etcd = await etcd
+
+    with pytest.raises(InvalidArgsError):
+        ConnectOptions().with_slow_threshold(-1)
+
+    options = ConnectOptions().with_slow_threshold(0)
+    with caplog.at_level(logging.WARNING, logger="etcd_client"):
+        async with etcd.etcd.connect(options) as communicator:
+            await communicator.put(b"slow", b"1")
+
+    assert any(
+        "slow put of \"slow\"" in record.getMessage() for record in caplog.records
+    )
+
+
+@pytest.mark.asyncio
+async def test_debug_logging(
+    etcd: AsyncEtcd, caplog: pytest.LogCaptureFixture
+) -> None:
+    etcd = await etcd
+
+    options = ConnectOptions().with_debug_logging()
+    with caplog.at_level(logging.DEBUG, logger="etcd_client"):
+        async with etcd.etcd.connect(options) as communicator:
+            await communicator.put(b"debug/\x00", b"1")
+            await communicator.get_prefix(b"debug/")
+
+    messages = [record.getMessage() for record in caplog.records]
+    assert any(message.startswith('put of "debug/\\x00"') for message in messages)
+    assert any(
+        message.startswith('get_prefix of "debug/"') and "ok, 1 items" in message
+        for message in messages
+    )
+
+
+@pytest.mark.asyncio
+async def test_fork(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    await etcd.put("fork", "value")
+
+    async with etcd.etcd.connect() as communicator:
+        pid = os.fork()
+        if pid == 0:
+
+            async def child() -> None:
+                # Communicators created before forking raise instead of hanging,
+                with pytest.raises(ClientError):
+                    await communicator.get(b"fork")
+                # while new ones run on a runtime of the forked process.
+                async with etcd.etcd.connect() as forked:
+                    assert await forked.get(b"fork") == b"value"
+
+            try:
+                # On a thread, as the event loop of the parent is not usable here.
+                with concurrent.futures.ThreadPoolExecutor() as executor:
+                    executor.submit(asyncio.run, child()).result(timeout=10)
+            except BaseException:
+                os._exit(1)
+            os._exit(0)
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0
+
+        assert await communicator.get(b"fork") == b"value"
+    assert await etcd.get("fork") == "value"
+
+
+@pytest.mark.asyncio
+async def test_exit_with_open_watch(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    # A watch left running must not call into the interpreter while it finalizes.
+    script = """
+import asyncio, sys
+from etcd_client import Client
+
+async def main():
+    communicator = await Client([sys.argv[1]]).open()
+    watch = communicator.watch(b"exit-with-open-watch")
+    asyncio.ensure_future(watch.__anext__())
+    await asyncio.sleep(0.1)
+
+asyncio.run(main())
+"""
+    result = subprocess.run(
+        [sys.executable, "-c", script, f"http://{etcd.addr}"], timeout=30
+    )
+    assert result.returncode == 0
+
+
+def test_configure_shutdown() -> None:
+    with pytest.raises(InvalidArgsError):
+        configure_shutdown(timeout=-1.0)
+    configure_shutdown(timeout=1.0, cancel=False, verbose=True)
+    configure_shutdown()
+
+
+def test_import_is_lazy() -> None:
+    # Importing starts no runtime threads and leaves logging alone until the first request.
+    script = """
+import os, sys
+import etcd_client
+assert "logging" not in sys.modules
+assert len(os.listdir("/proc/self/task")) == 1
+"""
+    result = subprocess.run([sys.executable, "-c", script], timeout=30)
+    assert result.returncode == 0
+
+
+@pytest.mark.asyncio
+async def test_grpc_status_error_classes(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(NotFoundError) as exc_info:
+            await communicator.lease_revoke(0x7FFFFFFF)
+        assert isinstance(exc_info.value, GRPCStatusError)
+        for key, value in exc_info.value.metadata:
+            assert isinstance(key, str)
+            assert isinstance(value, bytes if key.endswith("-bin") else str)
+
+
+@pytest.mark.asyncio
+async def test_retryable(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(NotFoundError) as exc_info:
+            await communicator.lease_revoke(0x7FFFFFFF)
+        assert exc_info.value.retryable is False
+
+    options = ConnectOptions().with_connect_timeout(1).with_timeout(1)
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError) as exc_info:
+            await communicator.get(b"retryable")
+        assert exc_info.value.retryable is True
+        # Writes are retryable too when the connection was refused, as nothing was sent.
+        with pytest.raises(ClientError) as exc_info:
+            await communicator.put(b"retryable", b"1")
+        assert exc_info.value.retryable is True
+
+    with pytest.raises(InvalidArgsError) as exc_info:
+        ConnectOptions().with_channels(0)
+    assert exc_info.value.retryable is False
+
+
+@pytest.mark.asyncio
+async def test_grpc_status_code(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    assert GRPCStatusCode.from_int(5) == GRPCStatusCode.NotFound
+    assert int(GRPCStatusCode.NotFound) == 5
+    assert hash(GRPCStatusCode.NotFound) == hash(5)
+    assert {GRPCStatusCode.NotFound: "missing"}[GRPCStatusCode.from_int(5)] == "missing"
+    with pytest.raises(ValueError):
+        GRPCStatusCode.from_int(17)
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(NotFoundError) as exc_info:
+            await communicator.lease_revoke(0x7FFFFFFF)
+        assert exc_info.value.args[0]["code"] == GRPCStatusCode.NotFound
+
+
+@pytest.mark.asyncio
+async def test_error_context(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(NotFoundError) as exc_info:
+            await communicator.lease_revoke(0x7FFFFFFF)
+        assert exc_info.value.operation == "lease_revoke"
+        assert exc_info.value.key is None
+
+    options = (
+        ConnectOptions()
+        .with_connect_timeout(1)
+        .with_timeout(1)
+        .with_load_balancing(LoadBalancing.PINNED)
+    )
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError) as exc_info:
+            await communicator.get(b"error-context")
+        assert exc_info.value.operation == "get"
+        assert exc_info.value.key == b"error-context"
+        assert exc_info.value.endpoint == "http://127.0.0.1:1"
+
+    with pytest.raises(InvalidArgsError) as exc_info:
+        ConnectOptions().with_channels(0)
+    assert exc_info.value.operation is None
+
+
+@pytest.mark.asyncio
+async def test_watch_connection_lost(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    # Forwards the connections to etcd until they are dropped.
+    writers = []
+
+    async def _pipe(reader, writer):
+        try:
+            while data := await reader.read(65536):
+                writer.write(data)
+                await writer.drain()
+        except (ConnectionError, asyncio.CancelledError):
+            pass
+        finally:
+            writer.close()
+
+    async def _forward(reader, writer):
+        upstream_reader, upstream_writer = await asyncio.open_connection(
+            etcd.addr.host, etcd.addr.port
+        )
+        writers.extend([writer, upstream_writer])
+        await asyncio.gather(
+            _pipe(reader, upstream_writer), _pipe(upstream_reader, writer)
+        )
+
+    proxy = await asyncio.start_server(_forward, "127.0.0.1", 0)
+    port = proxy.sockets[0].getsockname()[1]
+    ready = CondVar()
+
+    async def _watch():
+        async with Client([f"http://127.0.0.1:{port}"]).connect() as communicator:
+            async for _ in communicator.watch(b"connection-lost", ready_event=ready):
+                proxy.close()
+                for writer in writers:
+                    writer.close()
+
+    async with asyncio.timeout(10):
+        watch = asyncio.create_task(_watch())
+        await ready.wait()
+        async with etcd.etcd.connect() as communicator:
+            await communicator.put(b"connection-lost", b"1")
+        with pytest.raises(ConnectionLostError) as exc_info:
+            await watch
+    assert exc_info.value.revision > 0
+
+
+@pytest.mark.asyncio
+async def test_condvar_wait_for() -> None:
+    condvar = CondVar()
+    items: list[int] = []
+
+    assert await condvar.wait_for(lambda: True) is True
+
+    async def _produce():
+        for item in range(3):
+            items.append(item)
+            await condvar.notify_waiters()
+            await asyncio.sleep(0.01)
+
+    async with asyncio.timeout(10):
+        producer = asyncio.create_task(_produce())
+        assert await condvar.wait_for(lambda: len(items) >= 3 and items) == [0, 1, 2]
+        await producer
+
+    with pytest.raises(ZeroDivisionError):
+        await condvar.wait_for(lambda: 1 / 0)
+
+
+@pytest.mark.asyncio
+async def test_compacted_error(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    assert issubclass(CompactedError, GRPCStatusError)
+    assert CompactedError.compact_revision is None
+
+    # Also out of range, but not compacted.
+    async with etcd.etcd.connect() as communicator:
+        with pytest.raises(GRPCStatusError) as exc_info:
+            await communicator.hash_kv(1 << 62)
+        assert exc_info.value.args[0]["code"] == GRPCStatusCode.OutOfRange
+        assert not isinstance(exc_info.value, CompactedError)
+
+    etcdctl = shutil.which("etcdctl")
+    if etcdctl is None:
+        pytest.skip("etcdctl is needed to compact the store")
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"compacted", b"1")
+        await communicator.put(b"compacted", b"2")
+        revision = (await communicator.status()).header().revision()
+        subprocess.run(
+            [etcdctl, f"--endpoints=http://{etcd.addr}", "compact", str(revision)],
+            check=True,
+            capture_output=True,
+        )
+
+        # Reads below the compaction point fail, but etcd does not tell where it is.
+        with pytest.raises(CompactedError) as exc_info:
+            await communicator.hash_kv(revision - 1)
+        assert exc_info.value.compact_revision is None
+
+        # Watches below it are canceled along with it.
+        with pytest.raises(CompactedError) as exc_info:
+            async with asyncio.timeout(10):
+                async for _ in communicator.watch(
+                    b"compacted", start_revision=revision - 1
+                ):
+                    pass
+        assert exc_info.value.compact_revision == revision
+
+
+@pytest.mark.asyncio
+async def test_error_cause() -> None:
+    options = ConnectOptions().with_connect_timeout(1).with_timeout(1)
+    async with Client(["http://127.0.0.1:1"], options).connect() as communicator:
+        with pytest.raises(ClientError) as exc_info:
+            await communicator.get(b"error-cause")
+
+    # The refused connection is chained rather than only formatted into the message.
+    causes = []
+    cause = exc_info.value.__cause__
+    while cause is not None:
+        causes.append(cause)
+        cause = cause.__cause__
+    assert causes
+    assert all(isinstance(cause, Exception) for cause in causes)
+
+
+@pytest.mark.asyncio
+async def test_request_timeout_error() -> None:
+    assert not issubclass(RequestTimeoutError, GRPCStatusError)
+
+    # Accepts connections but never answers, so that the request times out
+    # rather than being refused.
+    options = ConnectOptions().with_connect_timeout(0.2).with_timeout(0.2)
+    with socket.socket() as blackhole:
+        blackhole.bind(("127.0.0.1", 0))
+        blackhole.listen()
+        host, port = blackhole.getsockname()
+        async with Client([f"http://{host}:{port}"], options).connect() as communicator:
+            with pytest.raises(RequestTimeoutError) as exc_info:
+                await communicator.get(b"request-timeout")
+            assert exc_info.value.retryable is True
+
+
+@pytest.mark.asyncio
+async def test_pickle(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    options = (
+        ConnectOptions()
+        .with_timeout(5)
+        .with_load_balancing(LoadBalancing.PINNED)
+        .with_reconnect(3)
+    )
+    unpickled = pickle.loads(pickle.dumps(options))
+    assert pickle.dumps(unpickled) == pickle.dumps(options)
+
+    assert pickle.loads(pickle.dumps(CompareOp.GREATER)) is CompareOp.GREATER
+    assert pickle.loads(pickle.dumps(LoadBalancing.PINNED)) == LoadBalancing.PINNED
+
+    lock_option = EtcdLockOption(b"pickle", 1.0, 10)
+    assert repr(pickle.loads(pickle.dumps(lock_option))) == repr(lock_option)
+
+    txn = (
+        Txn()
+        .when([Compare.value(b"pickle", CompareOp.EQUAL, b"1")])
+        .and_then([TxnOp.put(b"pickle", b"2")])
+        .or_else([TxnOp.get(b"pickle")])
+    )
+    unpickled = pickle.loads(pickle.dumps(txn))
+    assert repr(unpickled) == repr(txn)
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"pickle", b"1")
+        response = await communicator.txn(unpickled)
+        assert response.succeeded()
+        assert await communicator.get(b"pickle") == b"2"
+
+
+@pytest.mark.asyncio
+async def test_response_equality(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        response = await communicator.txn(Txn().and_then([TxnOp.put(b"equality", b"1")]))
+    assert response == TxnResponse(succeeded=True)
+    assert response != TxnResponse(succeeded=False)
+    assert len({response, TxnResponse(succeeded=True)}) == 1
+
+    event = WatchEvent(key=b"equality", value=b"1", event=WatchEventType.PUT)
+    assert event == WatchEvent(b"equality", b"1", WatchEventType.PUT, None)
+    assert len({event, WatchEvent(b"equality", b"1", WatchEventType.PUT)}) == 1
+    match event:
+        case WatchEvent(key, value, WatchEventType.PUT):
+            assert (key, value) == (b"equality", b"1")
+        case _:
+            pytest.fail("the event did not match")
+
+    assert int(CompareOp.NOT_EQUAL) == 3
+    assert CompareOp(1) is CompareOp.GREATER
+    assert CompareOp[CompareOp.LESS.name] is CompareOp.LESS
+    assert sorted({CompareOp.NOT_EQUAL, CompareOp.EQUAL}) == [
+        CompareOp.EQUAL,
+        CompareOp.NOT_EQUAL,
+    ]
+    with pytest.raises(TypeError):
+        Compare.version(b"equality", 0, 1)
+
+    assert list(WatchEventType) == [WatchEventType.PUT, WatchEventType.DELETE]
+    assert WatchEventType["DELETE"] is WatchEventType.DELETE
+    handlers = {WatchEventType.PUT: "put", WatchEventType.DELETE: "delete"}
+    assert handlers[event.event] == "put"
+    assert pickle.loads(pickle.dumps(event.event)) is WatchEventType.PUT
+    with pytest.raises(TypeError):
+        WatchEvent(b"equality", b"1", 0)
+
+
+@pytest.mark.asyncio
+async def test_text_decoding(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"decoding/text", "caf\u00e9".encode())
+        await communicator.put(b"decoding/binary", b"\xff")
+        assert await communicator.get(b"decoding/binary") == b"\xff"
+
+    options = ConnectOptions().with_text_decoding(TextDecoding.STRICT)
+    async with etcd.etcd.connect(options) as communicator:
+        assert await communicator.get(b"decoding/text") == "caf\u00e9"
+        assert await communicator.keys_prefix(b"decoding/") == [
+            "decoding/binary",
+            "decoding/text",
+        ]
+        with pytest.raises(Utf8Error):
+            await communicator.get(b"decoding/binary")
+        with pytest.raises(Utf8Error):
+            await communicator.get_prefix(b"decoding/")
+
+    options = ConnectOptions().with_text_decoding(TextDecoding.SURROGATEESCAPE)
+    async with etcd.etcd.connect(options) as communicator:
+        value = await communicator.get(b"decoding/binary")
+        assert value == "\udcff"
+        assert value.encode(errors="surrogateescape") == b"\xff"
+
+
+@pytest.mark.asyncio
+async def test_codecs(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    options = ConnectOptions().with_json_codec()
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.put(b"codec/json", {"replicas": 3, "name": "caf\u00e9"})
+        assert await communicator.get(b"codec/json") == {"replicas": 3, "name": "caf\u00e9"}
+        assert await communicator.get_prefix(b"codec/") == [
+            (b"codec/json", {"replicas": 3, "name": "caf\u00e9"})
+        ]
+
+    async with etcd.etcd.connect() as communicator:
+        assert await communicator.get(b"codec/json") == b'{"replicas": 3, "name": "caf\\u00e9"}'
+
+    options = ConnectOptions().with_codec(
+        lambda value: str(value).encode(), lambda value: int(value)
+    )
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.put(b"codec/custom", 42)
+        assert await communicator.get(b"codec/custom") == 42
+        with pytest.raises(ValueError):
+            await communicator.get(b"codec/json")
+
+    bad_options = ConnectOptions().with_codec(lambda value: value, bytes)
+    async with etcd.etcd.connect(bad_options) as communicator:
+        with pytest.raises(TypeError):
+            await communicator.put(b"codec/bad", "not bytes")
+
+
+@pytest.mark.asyncio
+async def test_metrics_snapshot(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    before = metrics_snapshot()
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"metrics", b"1")
+        await communicator.get(b"metrics")
+        with pytest.raises(NotFoundError):
+            await communicator.lease_revoke(0x7EADBEEF)
+        await communicator.reconnect()
+    after = metrics_snapshot()
+
+    def _count(snapshot, key):
+        return snapshot["requests"].get(key, 0)
+
+    assert _count(after, ("put", "ok")) == _count(before, ("put", "ok")) + 1
+    assert _count(after, ("get", "ok")) == _count(before, ("get", "ok")) + 1
+    assert (
+        _count(after, ("lease_revoke", "NotFoundError"))
+        == _count(before, ("lease_revoke", "NotFoundError")) + 1
+    )
+    assert after["reconnects"] == before["reconnects"] + 1
+
+    histogram = after["latency"]["get"]
+    assert histogram["buckets"][-1] == (float("inf"), histogram["count"])
+    counts = [count for _, count in histogram["buckets"]]
+    assert counts == sorted(counts)
+    assert histogram["sum"] > 0
+
+
+@pytest.mark.asyncio
+async def test_stats(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        assert communicator.stats() == {}
+        for i in range(10):
+            await communicator.put(b"stats", str(i).encode())
+        await communicator.with_timeout(5).get(b"stats")
+
+        stats = communicator.stats()
+        assert stats["put"]["count"] == 10
+        assert stats["get"]["count"] == 1
+        put = stats["put"]
+        assert 0 < put["p50"] <= put["p95"] <= put["p99"]
+
+
+@pytest.mark.asyncio
+async def test_hooks(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    sent = []
+    completed = []
+
+    def _before(operation, key):
+        sent.append((operation, key))
+
+    def _after(operation, key, duration, status):
+        assert duration >= 0
+        completed.append((operation, key, status))
+
+    def _failing(*args):
+        raise RuntimeError("hooks never fail requests")
+
+    options = ConnectOptions().with_hooks(before=_before, after=_after)
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.put(b"hooks", b"1")
+        with pytest.raises(NotFoundError):
+            await communicator.lease_revoke(0x7EADBEEF)
+
+    assert sent == [("put", b"hooks"), ("lease_revoke", None)]
+    assert completed == [
+        ("put", b"hooks", "ok"),
+        ("lease_revoke", None, "NotFoundError"),
+    ]
+
+    options = ConnectOptions().with_hooks(before=_failing, after=_failing)
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.put(b"hooks", b"2")
+        assert await communicator.get(b"hooks") == b"2"
+
+
+@pytest.mark.asyncio
+async def test_audit(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+    events = []
+
+    options = ConnectOptions().with_audit(events.append)
+    async with etcd.etcd.connect(options) as communicator:
+        await communicator.delete(b"audit")
+        await communicator.put(b"audit", b"1")
+        audited = communicator.with_audit_context("alice")
+        await audited.put(b"audit", b"2")
+        await audited.delete(b"audit")
+        await communicator.get(b"audit")
+        await communicator.txn(Txn().and_then([TxnOp.get(b"audit")]))
+
+    assert [(e.operation, e.key, e.context) for e in events] == [
+        ("put", b"audit", None),
+        ("put", b"audit", "alice"),
+        ("delete", b"audit", "alice"),
+    ]
+    assert events[0].old_revision is None
+    assert events[1].old_revision == events[0].new_revision
+    assert events[2].old_revision == events[1].new_revision
+    assert events[2].new_revision > events[1].new_revision
+
+
+@pytest.mark.asyncio
+async def test_tracing(etcd: AsyncEtcd) -> None:
+    from opentelemetry.sdk.trace import TracerProvider
+    from opentelemetry.sdk.trace.export import SimpleSpanProcessor
+    from opentelemetry.sdk.trace.export.in_memory_span_exporter import (
+        InMemorySpanExporter,
+    )
+    from opentelemetry.trace import SpanKind, StatusCode
+
+    etcd = await etcd
+    exporter = InMemorySpanExporter()
+    provider = TracerProvider()
+    provider.add_span_processor(SimpleSpanProcessor(exporter))
+    tracer = provider.get_tracer("test")
+
+    options = ConnectOptions().with_tracing(tracer)
+    async with etcd.etcd.connect(options) as communicator:
+        with tracer.start_as_current_span("parent") as parent:
+            await communicator.put(b"tracing", b"1")
+            with pytest.raises(NotFoundError):
+                await communicator.lease_revoke(0x7EADBEEF)
+
+        ready = asyncio.Event()
+
+        async def _watch():
+            async for _ in communicator.watch(b"tracing", once=True, ready_event=ready):
+                pass
+
+        async with asyncio.timeout(10):
+            watch = asyncio.create_task(_watch())
+            await ready.wait()
+            await communicator.delete(b"tracing")
+            await watch
+
+    spans = {span.name: span for span in exporter.get_finished_spans()}
+    put = spans["etcd put"]
+    assert put.kind == SpanKind.CLIENT
+    assert put.parent.span_id == parent.get_span_context().span_id
+    assert put.attributes["db.system"] == "etcd"
+    assert put.attributes["db.etcd.key"] == "tracing"
+    assert put.status.is_ok
+    assert spans["etcd lease_revoke"].status.status_code == StatusCode.ERROR
+    assert "etcd watch" in spans
+
+
+@pytest.mark.asyncio
+async def test_communicator_views(etcd: AsyncEtcd) -> None:
+    etcd = await etcd
+
+    async with etcd.etcd.connect() as communicator:
+        await communicator.put(b"views", b"1")
+
+        decoding = ConnectOptions().with_text_decoding(TextDecoding.STRICT)
+        assert await communicator.with_options(decoding).get(b"views") == "1"
+        assert await communicator.with_timeout(5).get(b"views") == b"1"
+
+        with pytest.raises(RequestTimeoutError) as exc_info:
+            await communicator.with_timeout(1e-9).get(b"views")
+        assert exc_info.value.retryable is True
+
+        with pytest.raises(InvalidArgsError):
+            communicator.with_timeout(0)
+
+        # Views share the connection, so closing one closes them all.
+        view = communicator.with_timeout(5)
+        await view.close()
+        with pytest.raises(ClientError):
+            await communicator.get(b"views")